@@ -114,14 +114,191 @@ impl Font {
     }
 
     /// Attempt to write the font into the provided [`Writer`][std::io::Write];
+    ///
+    /// Fails without writing anything if a modified `glyf` table contains a
+    /// glyph which cannot be encoded.
     pub fn write(&mut self, mut writer: impl std::io::Write) -> Result<(), Box<dyn Error>> {
-        self.tables.compile_glyf_loca_maxp();
+        self.tables.compile_glyf_loca_maxp()?;
         self.tables.compile_gsub_gpos();
         let mut bytes = Vec::new();
         self.to_bytes(&mut bytes)?;
         writer.write_all(&bytes).map_err(Into::into)
     }
 
+    /// Brings the tables which depend on `glyf` up to date with it.
+    ///
+    /// If the `glyf` table has been modified since it was loaded, this
+    /// recalculates the glyph bounds, the `loca` offsets, the `maxp` glyph
+    /// count and statistics, and the bounding box and `indexToLocFormat` of
+    /// the `head` table. Other tables are left untouched. Nothing is done if
+    /// the font has no `glyf` table or it has not been modified. Returns an
+    /// error if a glyph cannot be encoded.
+    pub fn rebuild(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.tables.is_serialized(tables::glyf::TAG).unwrap_or(true) {
+            return Ok(());
+        }
+        if let Ok(Some(mut glyf)) = self.tables.glyf() {
+            glyf.recalc_bounds_preserving_components();
+            self.tables.insert(glyf);
+        }
+        self.tables.compile_glyf_loca_maxp()?;
+        self._numGlyphs = None;
+        Ok(())
+    }
+
+    /// Changes the order of the glyphs in the font.
+    ///
+    /// `new_order` gives, for each new glyph ID, the old ID of the glyph to
+    /// place there; it must contain every glyph ID in the font exactly once.
+    /// The `glyf` table (including component references), `loca`, `hmtx`,
+    /// `post` glyph names, `cmap` mappings and `gvar` variations are all
+    /// updated together, so the font remains consistent. Glyph IDs in other
+    /// tables, such as the layout tables, are not rewritten. If any of these
+    /// tables refers to a glyph which does not exist, or is missing entries
+    /// for some glyphs, or if a glyph cannot be encoded, an error is returned
+    /// and the font is left unchanged.
+    pub fn reorder_glyphs(&mut self, new_order: &[u16]) -> Result<(), Box<dyn Error>> {
+        let num_glyphs = self.num_glyphs() as usize;
+        if new_order.len() != num_glyphs {
+            return Err(format!(
+                "New glyph order has {} glyphs, but the font has {}",
+                new_order.len(),
+                num_glyphs
+            )
+            .into());
+        }
+        let mut remap: Vec<Option<u16>> = vec![None; num_glyphs];
+        for (new_gid, &old_gid) in new_order.iter().enumerate() {
+            match remap.get_mut(old_gid as usize) {
+                None => return Err(format!("Glyph {} not found", old_gid).into()),
+                Some(Some(_)) => {
+                    return Err(format!("Glyph {} appears more than once", old_gid).into())
+                }
+                Some(slot) => *slot = Some(new_gid as u16),
+            }
+        }
+        let remap: Vec<u16> = remap.into_iter().flatten().collect();
+
+        // Build every table before inserting any, so that an error leaves
+        // the font unchanged
+        let gvar = match self.tables.gvar()? {
+            Some(gvar) => {
+                let mut gvar = gvar.into_owned();
+                gvar.variations = new_order
+                    .iter()
+                    .map(|&gid| {
+                        gvar.variations
+                            .get(gid as usize)
+                            .cloned()
+                            .ok_or_else(|| format!("Glyph {} has no entry in gvar", gid))
+                    })
+                    .collect::<Result<_, _>>()?;
+                Some(gvar)
+            }
+            None => None,
+        };
+        let glyf = match self.tables.glyf()? {
+            Some(glyf) => {
+                let glyf = glyf.subset(new_order)?.0;
+                // Check that every glyph can be encoded before anything is
+                // changed, as the table is compiled once it is inserted
+                glyf.write_to(std::io::sink())?;
+                Some(glyf)
+            }
+            None => None,
+        };
+        let hmtx = match self.tables.hmtx()? {
+            Some(hmtx) => {
+                let mut hmtx = hmtx.into_owned();
+                hmtx.metrics = new_order
+                    .iter()
+                    .map(|&gid| {
+                        hmtx.metrics
+                            .get(gid as usize)
+                            .copied()
+                            .ok_or_else(|| format!("Glyph {} has no horizontal metrics", gid))
+                    })
+                    .collect::<Result<_, _>>()?;
+                Some(hmtx)
+            }
+            None => None,
+        };
+        let post = match self.tables.post()? {
+            Some(post) => {
+                let mut post = post.into_owned();
+                if let Some(names) = post.glyphnames.as_mut() {
+                    *names = new_order
+                        .iter()
+                        .map(|&gid| {
+                            names
+                                .get(gid as usize)
+                                .cloned()
+                                .ok_or_else(|| format!("Glyph {} has no name in post", gid))
+                        })
+                        .collect::<Result<_, _>>()?;
+                }
+                Some(post)
+            }
+            None => None,
+        };
+        let remap_gid = |gid: &mut u16| -> Result<(), String> {
+            *gid = *remap
+                .get(*gid as usize)
+                .ok_or_else(|| format!("cmap maps to glyph {}, which does not exist", gid))?;
+            Ok(())
+        };
+        let cmap = match self.tables.cmap()? {
+            Some(cmap) => {
+                let mut cmap = cmap.into_owned();
+                for subtable in cmap.subtables.iter_mut() {
+                    subtable.mapping.values_mut().try_for_each(remap_gid)?;
+                    if let Some(uvs_mapping) = subtable.uvs_mapping.as_mut() {
+                        uvs_mapping.values_mut().try_for_each(remap_gid)?;
+                    }
+                }
+                Some(cmap)
+            }
+            None => None,
+        };
+
+        if let Some(gvar) = gvar {
+            self.tables.insert(gvar);
+        }
+        if let Some(glyf) = glyf {
+            self.tables.insert(glyf);
+            self.tables.compile_glyf_loca_maxp()?;
+        }
+        if let Some(hmtx) = hmtx {
+            self.tables.insert(hmtx);
+        }
+        if let Some(post) = post {
+            self.tables.insert(post);
+        }
+        if let Some(cmap) = cmap {
+            self.tables.insert(cmap);
+        }
+        Ok(())
+    }
+
+    /// Looks up a glyph by its name in the `post` table.
+    ///
+    /// Only the named glyph is parsed if the `glyf` table has not been
+    /// loaded yet (see
+    /// [`TableSet::glyph`](crate::table_store::TableSet::glyph)). Returns `None` if no glyph has
+    /// that name, if the font has no glyph names, or if the glyph cannot be
+    /// read.
+    pub fn glyph_by_name(&self, name: &str) -> Option<tables::glyf::Glyph> {
+        let post = self.tables.post().ok()??;
+        let gid = post.glyphnames.as_ref()?.iter().position(|n| n == name)?;
+        match self.tables.glyph(u16::try_from(gid).ok()?) {
+            Ok(glyph) => glyph,
+            Err(e) => {
+                log::warn!("Could not read glyph {}: {}", name, e);
+                None
+            }
+        }
+    }
+
     /// Total number of glyphs in the font, from the maxp table.
     ///
     /// Deserializes the maxp table if this is not already done.
@@ -264,6 +441,7 @@ impl Deserialize for Font {
 mod tests {
 
     use super::*;
+    use crate::tables::glyf::tests::sample_font;
     use crate::tables::head::head;
     use crate::tables::hhea::hhea;
     use crate::tables::maxp;
@@ -399,6 +577,291 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_rebuild() {
+        let mut font = sample_font();
+        // Nothing to do for an unmodified font
+        font.rebuild().unwrap();
+        assert!(font.tables.is_serialized(tables::glyf::TAG).unwrap());
+
+        let mut glyf = font.tables.glyf().unwrap().unwrap();
+        glyf.glyphs[2].contours[0][0].x = 1200;
+        glyf.glyphs[2].contours[0].push(crate::tables::glyf::Point {
+            x: 0,
+            y: 0,
+            on_curve: true,
+        });
+        font.tables.insert(glyf.clone());
+        font.rebuild().unwrap();
+
+        let mut serialized = vec![];
+        font.write(&mut serialized).unwrap();
+        let reparsed: Font = otspec::de::from_bytes(&serialized).unwrap();
+        let reparsed_glyf = reparsed.tables.glyf().unwrap().unwrap();
+        assert_eq!(reparsed_glyf.glyphs.len(), glyf.glyphs.len());
+        assert_eq!(reparsed_glyf.glyphs[2].contours, glyf.glyphs[2].contours);
+        assert_eq!(reparsed_glyf.glyphs[2].xMax, 1200);
+        assert_eq!(reparsed_glyf.glyphs[3], glyf.glyphs[3]);
+        let head = reparsed.tables.head().unwrap().unwrap();
+        assert_eq!(head.xMax, 1200);
+        let maxp = reparsed.tables.maxp().unwrap().unwrap();
+        assert_eq!(maxp.num_glyphs() as usize, glyf.glyphs.len());
+        if let (maxp::MaxpVariant::Maxp10(maxp), maxp::MaxpVariant::Maxp10(expected)) =
+            (&maxp.table, glyf.as_maxp10().table)
+        {
+            assert_eq!(maxp.maxPoints, expected.maxPoints);
+            assert_eq!(maxp.maxContours, expected.maxContours);
+        }
+    }
+
+    #[test]
+    fn test_reorder_glyphs() {
+        let mut font = sample_font();
+        let old_glyf = font.tables.glyf().unwrap().unwrap().into_owned();
+        let old_hmtx = font.tables.hmtx().unwrap().unwrap().into_owned();
+        let old_a = font
+            .tables
+            .cmap()
+            .unwrap()
+            .unwrap()
+            .get_best_mapping()
+            .unwrap()[&0x41];
+
+        // Swap A and Aacute
+        let new_order = [1, 0, 2, 3, 4, 5, 6, 7];
+        assert!(font.reorder_glyphs(&[0, 1, 2]).is_err());
+        assert!(font.reorder_glyphs(&[0, 0, 2, 3, 4, 5, 6, 7]).is_err());
+        font.reorder_glyphs(&new_order).unwrap();
+
+        let mut serialized = vec![];
+        font.write(&mut serialized).unwrap();
+        let reparsed: Font = otspec::de::from_bytes(&serialized).unwrap();
+        let glyf = reparsed.tables.glyf().unwrap().unwrap();
+        let hmtx = reparsed.tables.hmtx().unwrap().unwrap();
+        let cmap = reparsed.tables.cmap().unwrap().unwrap();
+        assert_eq!(cmap.get_best_mapping().unwrap()[&0x41], 1);
+        assert_eq!(
+            glyf.glyphs[1].contours,
+            old_glyf.glyphs[old_a as usize].contours
+        );
+        assert_eq!(hmtx.metrics[1], old_hmtx.metrics[old_a as usize]);
+        // Aacute now uses A at its new position
+        assert_eq!(glyf.glyphs[0].components[0].glyph_index, 1);
+        let post = reparsed.tables.post().unwrap().unwrap();
+        assert_eq!(post.glyph_name(0), Some("Aacute"));
+        assert_eq!(post.glyph_name(1), Some("A"));
+    }
+
+    #[test]
+    fn test_reorder_glyphs_out_of_range() {
+        let new_order = [1, 0, 2, 3, 4, 5, 6, 7];
+        let unchanged = sample_font();
+        let glyf = unchanged.tables.glyf().unwrap().unwrap().into_owned();
+
+        // A cmap entry pointing past the last glyph
+        let mut font = sample_font();
+        let mut cmap = font.tables.cmap().unwrap().unwrap().into_owned();
+        cmap.subtables[0].mapping.insert(0x42, 8);
+        font.tables.insert(cmap);
+        assert!(font.reorder_glyphs(&new_order).is_err());
+        // Nothing was changed
+        assert_eq!(*font.tables.glyf().unwrap().unwrap(), glyf);
+
+        // A short hmtx table
+        let mut font = sample_font();
+        let mut hmtx = font.tables.hmtx().unwrap().unwrap().into_owned();
+        hmtx.metrics.truncate(4);
+        font.tables.insert(hmtx);
+        assert!(font.reorder_glyphs(&new_order).is_err());
+
+        // A short list of glyph names
+        let mut font = sample_font();
+        let mut post = font.tables.post().unwrap().unwrap().into_owned();
+        post.glyphnames.as_mut().unwrap().truncate(4);
+        font.tables.insert(post);
+        assert!(font.reorder_glyphs(&new_order).is_err());
+        // A gvar table with too few entries
+        let mut font = sample_font();
+        font.tables.insert(tables::gvar::gvar {
+            variations: vec![None; 4],
+        });
+        assert!(font.reorder_glyphs(&new_order).is_err());
+        assert_eq!(font.tables.gvar().unwrap().unwrap().variations.len(), 4);
+    }
+
+    #[test]
+    fn test_write_unencodable_glyph() {
+        let mut font = sample_font();
+        let mut glyf = font.tables.glyf().unwrap().unwrap();
+        glyf.glyphs[2].instructions = vec![0; u16::MAX as usize + 1];
+        font.tables.insert(glyf);
+        let mut serialized = vec![];
+        assert!(font.write(&mut serialized).is_err());
+        assert!(serialized.is_empty());
+        assert!(font.rebuild().is_err());
+        assert!(font.reorder_glyphs(&[0, 1, 2, 3, 4, 5, 6, 7]).is_err());
+    }
+
+    #[test]
+    fn test_unchanged_glyphs_preserved() {
+        let glyf_bytes = |font: &Font| {
+            let mut data = vec![];
+            font.tables
+                .write_table(tables::glyf::TAG, &mut data)
+                .unwrap();
+            data
+        };
+        let original = sample_font();
+        let original_glyf = glyf_bytes(&original);
+        let loca = original.tables.loca().unwrap().unwrap();
+
+        // Replacing the glyf table with an unedited copy changes nothing
+        let mut font = sample_font();
+        let glyf = font.tables.glyf().unwrap().unwrap().into_owned();
+        font.tables.insert(glyf.clone());
+        let mut serialized = vec![];
+        font.write(&mut serialized).unwrap();
+        let reparsed: Font = otspec::de::from_bytes(&serialized).unwrap();
+        assert_eq!(glyf_bytes(&reparsed), original_glyf);
+
+        // Editing one glyph leaves the ones before it untouched
+        let mut font = sample_font();
+        let mut edited = glyf.clone();
+        edited.glyphs[2].contours[0][0].x += 1;
+        font.tables.insert(edited);
+        let mut serialized = vec![];
+        font.write(&mut serialized).unwrap();
+        let reparsed: Font = otspec::de::from_bytes(&serialized).unwrap();
+        let start_of_o = loca.indices[2].unwrap() as usize;
+        assert_eq!(
+            glyf_bytes(&reparsed)[..start_of_o],
+            original_glyf[..start_of_o]
+        );
+        assert_ne!(glyf_bytes(&reparsed), original_glyf);
+    }
+
+    #[test]
+    fn test_glyph_by_name() {
+        let font = sample_font();
+        let dollar_bold = font.glyph_by_name("dollar.bold").unwrap();
+        assert!(font.tables.is_serialized(tables::glyf::TAG).unwrap());
+        assert_eq!(font.glyph_by_name("nonexistent"), None);
+        // An empty glyph
+        assert_eq!(
+            font.glyph_by_name("space"),
+            Some(tables::glyf::Glyph::empty())
+        );
+
+        let glyf = font.tables.glyf().unwrap().unwrap();
+        assert_eq!(dollar_bold, glyf.glyphs[6]);
+        assert_eq!(font.glyph_by_name("Aacute").as_ref(), Some(&glyf.glyphs[1]));
+        assert_eq!(font.tables.glyph(8).unwrap(), None);
+    }
+
+    #[test]
+    fn test_preserve_glyf_gaps() {
+        let original = sample_font();
+        let mut original_glyf = vec![];
+        original
+            .tables
+            .write_table(tables::glyf::TAG, &mut original_glyf)
+            .unwrap();
+        let original_loca = original.tables.loca().unwrap().unwrap().into_owned();
+
+        // Leave eight bytes of padding after every glyph
+        let mut gapped_glyf = vec![];
+        let mut gapped_loca = tables::loca::loca { indices: vec![] };
+        for (gid, offset) in original_loca.indices.iter().enumerate() {
+            let offset = match offset {
+                Some(offset) => *offset as usize,
+                None => {
+                    gapped_loca.indices.push(None);
+                    continue;
+                }
+            };
+            let next = original_loca.indices[gid + 1..]
+                .iter()
+                .flatten()
+                .next()
+                .map_or(original_glyf.len(), |&x| x as usize);
+            gapped_loca.indices.push(Some(gapped_glyf.len() as u32));
+            gapped_glyf.extend(&original_glyf[offset..next]);
+            gapped_glyf.extend([0; 8]);
+        }
+
+        let regenerated_loca = |preserve: bool| {
+            let mut font = sample_font();
+            font.tables
+                .insert_raw(tables::glyf::TAG, gapped_glyf.clone());
+            font.tables.insert(gapped_loca.clone());
+            font.tables.set_preserve_glyf_gaps(preserve);
+            let glyf = font.tables.glyf().unwrap().unwrap().into_owned();
+            font.tables.insert(glyf);
+            let mut serialized = vec![];
+            font.write(&mut serialized).unwrap();
+            let reparsed: Font = otspec::de::from_bytes(&serialized).unwrap();
+            let loca = reparsed.tables.loca().unwrap().unwrap().into_owned();
+            loca.indices
+        };
+        assert_eq!(regenerated_loca(true), gapped_loca.indices);
+        assert_ne!(regenerated_loca(false), gapped_loca.indices);
+    }
+
+    #[test]
+    fn test_four_byte_aligned_glyf_round_trip() {
+        let original = sample_font();
+        let mut original_glyf = vec![];
+        original
+            .tables
+            .write_table(tables::glyf::TAG, &mut original_glyf)
+            .unwrap();
+        let original_loca = original.tables.loca().unwrap().unwrap().into_owned();
+
+        // Lay the glyphs out again, padded to four bytes instead of two
+        let mut aligned_glyf = vec![];
+        let mut aligned_loca = tables::loca::loca { indices: vec![] };
+        for (gid, offset) in original_loca.indices.iter().enumerate() {
+            let offset = match offset {
+                Some(offset) => *offset as usize,
+                None => {
+                    aligned_loca.indices.push(None);
+                    continue;
+                }
+            };
+            let next = original_loca.indices[gid + 1..]
+                .iter()
+                .flatten()
+                .next()
+                .map_or(original_glyf.len(), |&x| x as usize);
+            aligned_loca.indices.push(Some(aligned_glyf.len() as u32));
+            aligned_glyf.extend(&original_glyf[offset..next]);
+            while aligned_glyf.len() % 4 != 0 {
+                aligned_glyf.push(0);
+            }
+        }
+        assert_ne!(aligned_glyf.len(), original_glyf.len());
+
+        let mut font = sample_font();
+        font.tables
+            .insert_raw(tables::glyf::TAG, aligned_glyf.clone());
+        font.tables.insert(aligned_loca.clone());
+        let glyf = font.tables.glyf().unwrap().unwrap().into_owned();
+        font.tables.insert(glyf);
+        let mut serialized = vec![];
+        font.write(&mut serialized).unwrap();
+        let reparsed: Font = otspec::de::from_bytes(&serialized).unwrap();
+        let mut reparsed_glyf = vec![];
+        reparsed
+            .tables
+            .write_table(tables::glyf::TAG, &mut reparsed_glyf)
+            .unwrap();
+        assert_eq!(reparsed_glyf, aligned_glyf);
+        assert_eq!(
+            reparsed.tables.loca().unwrap().unwrap().indices,
+            aligned_loca.indices
+        );
+    }
+
     // #[test]
     // fn test_load() {
     //     let f = font::load("data/test1.ttf").unwrap();
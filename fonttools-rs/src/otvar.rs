@@ -6,6 +6,8 @@ mod itemvariationstore;
 pub mod iup;
 /// Structs to store locations (user and normalized)
 mod locations;
+/// Access to tuple values by axis tag
+mod namedtuple;
 /// Structs for storing packed deltas within a tuple variation store
 mod packeddeltas;
 /// Structs for storing packed points
@@ -19,6 +21,7 @@ pub mod instancer;
 
 pub use itemvariationstore::{ItemVariationData, ItemVariationStore, RegionAxisCoordinates};
 pub use locations::NormalizedLocation;
+pub use namedtuple::NamedTuple;
 pub use otmath::{support_scalar, Location, VariationModel};
 use otspec::types::int16;
 pub use packeddeltas::PackedDeltas;
@@ -448,13 +448,14 @@ fn can_iup_between(
         &Some(Delta::Delta2D(deltas[j])),
     );
     assert_eq!(interp.len(), delta_portion.len());
-    let can_iup = delta_portion
+    // X and Y are interpolated independently, so each must be reproduced
+    // within the tolerance on its own.
+    delta_portion
         .iter()
         .zip(interp.iter())
         .all(|((x, y), (p, q))| {
-            ((x - p) as f32 * (x - p) as f32 + (y - q) as f32 * (y - q) as f32) <= tolerance
-        });
-    can_iup
+            ((x - p) as f32).abs() <= tolerance && ((y - q) as f32).abs() <= tolerance
+        })
 }
 
 #[cfg(test)]
@@ -511,4 +512,41 @@ mod tests {
         let check_optimized = iup_contour_optimize(&unoptimized, &coords, 0.5);
         assert_eq!(check_optimized, optimized);
     }
+
+    #[test]
+    fn test_iup_straight_line_optimize() {
+        // Points along a straight line whose deltas grow linearly can all be
+        // inferred from the two ends of the line.
+        let coords = vec![(0, 0), (100, 0), (200, 0), (300, 0), (300, 300), (0, 300)];
+        let deltas = vec![(0, 5), (10, 5), (20, 5), (30, 5), (-7, 9), (4, -3)];
+        let optimized = iup_contour_optimize(&deltas, &coords, 0.5);
+        assert_eq!(
+            optimized,
+            vec![
+                Some(Delta::Delta2D((0, 5))),
+                None,
+                None,
+                Some(Delta::Delta2D((30, 5))),
+                Some(Delta::Delta2D((-7, 9))),
+                Some(Delta::Delta2D((4, -3))),
+            ]
+        );
+        let mut newdeltas = vec![];
+        iup_contour(&mut newdeltas, &optimized, &coords);
+        assert_eq!(newdeltas, deltas);
+    }
+
+    #[test]
+    fn test_iup_per_axis_optimize() {
+        // The second point is a third of the way between its neighbours along
+        // the x axis, and its x delta is a third of the way between theirs;
+        // along the y axis all three share a position and a delta.
+        let coords = vec![(0, 0), (1, 0), (3, 0), (3, 3)];
+        let deltas = vec![(0, 5), (1, 5), (3, 5), (3, 5)];
+        let optimized = iup_contour_optimize(&deltas, &coords, 0.5);
+        assert_eq!(optimized[1], None);
+        let mut newdeltas = vec![];
+        iup_contour(&mut newdeltas, &optimized, &coords);
+        assert_eq!(newdeltas, deltas);
+    }
 }
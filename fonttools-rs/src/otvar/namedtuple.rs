@@ -0,0 +1,80 @@
+use otspec::types::Tag;
+
+/// A view of a tuple of coordinates paired with the axis tag of each entry.
+///
+/// Tuples in `gvar` (and elsewhere) are stored as a bare list of values, one
+/// per axis, in the order of the axes in the `fvar` table. This view allows
+/// the values to be looked up by axis tag. The underlying tuple is unchanged,
+/// so it continues to serialize as a plain array.
+///
+/// ```
+/// use fonttools::otvar::NamedTuple;
+/// use fonttools::tag;
+///
+/// let axes = [tag!("wght"), tag!("wdth")];
+/// let peak = vec![1.0, -0.5];
+/// let named = NamedTuple::new(&axes, &peak);
+/// assert_eq!(named.get("wdth"), Some(-0.5));
+/// assert_eq!(named.get("slnt"), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NamedTuple<'a> {
+    axes: &'a [Tag],
+    values: &'a [f32],
+}
+
+impl<'a> NamedTuple<'a> {
+    /// Pairs a tuple with a list of axis tags, usually from [`fvar::axis_tags`].
+    ///
+    /// [`fvar::axis_tags`]: crate::tables::fvar::fvar::axis_tags
+    pub fn new(axes: &'a [Tag], values: &'a [f32]) -> Self {
+        if axes.len() != values.len() {
+            log::warn!(
+                "Tuple has {} values but {} axis tags were given",
+                values.len(),
+                axes.len()
+            );
+        }
+        NamedTuple { axes, values }
+    }
+
+    /// Returns the value for the given axis, if it is present in the tuple.
+    pub fn get(&self, axis: &str) -> Option<f32> {
+        let tag = Tag::from_raw(axis).ok()?;
+        self.axes
+            .iter()
+            .position(|t| *t == tag)
+            .and_then(|ix| self.values.get(ix).copied())
+    }
+
+    /// Iterates over the axis tags and values of this tuple.
+    pub fn iter(&self) -> impl Iterator<Item = (Tag, f32)> + 'a {
+        self.axes.iter().copied().zip(self.values.iter().copied())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tag;
+
+    #[test]
+    fn test_named_tuple() {
+        let axes = [tag!("wght"), tag!("wdth"), tag!("opsz")];
+        let tuple = vec![0.0, 1.0, -0.25];
+        let named = NamedTuple::new(&axes, &tuple);
+        assert_eq!(named.get("wght"), Some(0.0));
+        assert_eq!(named.get("wdth"), Some(1.0));
+        assert_eq!(named.get("opsz"), Some(-0.25));
+        assert_eq!(named.get("ital"), None);
+        assert_eq!(named.get("toolong"), None);
+        assert_eq!(
+            named.iter().collect::<Vec<_>>(),
+            vec![
+                (tag!("wght"), 0.0),
+                (tag!("wdth"), 1.0),
+                (tag!("opsz"), -0.25)
+            ]
+        );
+    }
+}
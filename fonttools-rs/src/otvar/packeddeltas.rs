@@ -17,32 +17,49 @@ const DELTAS_ARE_ZERO: u8 = 0x80;
 /// Mask off a run control byte to find the number of deltas in the run
 const DELTA_RUN_COUNT_MASK: u8 = 0x3f;
 
-/// Deserialize the packed deltas array from a binary buffer.
-/// The number of points must be provided.
-#[allow(dead_code)] // We *do* use it, I promise. Like, just a few lines below.
-pub fn from_bytes(
+/// Decode exactly `count` packed deltas from a binary buffer.
+///
+/// Each run is introduced by a control byte which says whether the run holds
+/// zeros (which take up no further space), bytes or words. It is an error
+/// for the final run to extend past `count` deltas.
+pub fn read_packed_deltas(
     c: &mut ReaderContext,
-    num_points: usize,
-) -> Result<PackedDeltas, DeserializationError> {
-    let mut res = vec![];
-    while res.len() < num_points {
+    count: usize,
+) -> Result<Vec<int16>, DeserializationError> {
+    let mut res = Vec::with_capacity(count);
+    while res.len() < count {
         let control_byte: u8 = c.de()?;
         let deltas_are_words = (control_byte & DELTAS_ARE_WORDS) > 0;
         // "The low 6 bits specify the number of delta values in the run minus 1."
         // MINUS ONE.
-        let run_count = (control_byte & DELTA_RUN_COUNT_MASK) + 1;
-        let deltas: Vec<i16>;
+        let run_count = ((control_byte & DELTA_RUN_COUNT_MASK) + 1) as usize;
+        if res.len() + run_count > count {
+            return Err(DeserializationError(format!(
+                "Packed delta run overflows the expected {} deltas",
+                count
+            )));
+        }
         if control_byte & DELTAS_ARE_ZERO > 0 {
-            deltas = std::iter::repeat(0).take(run_count as usize).collect();
+            res.resize(res.len() + run_count, 0);
         } else if deltas_are_words {
-            deltas = c.de_counted(run_count.into())?;
+            let deltas: Vec<i16> = c.de_counted(run_count)?;
+            res.extend(deltas);
         } else {
-            let delta_bytes: Vec<i8> = c.de_counted(run_count.into())?;
-            deltas = delta_bytes.iter().map(|x| *x as i16).collect();
+            let delta_bytes: Vec<i8> = c.de_counted(run_count)?;
+            res.extend(delta_bytes.iter().map(|x| *x as i16));
         }
-        res.extend(deltas);
     }
-    Ok(PackedDeltas(res))
+    Ok(res)
+}
+
+/// Deserialize the packed deltas array from a binary buffer.
+/// The number of points must be provided.
+#[allow(dead_code)] // Used in tests
+pub fn from_bytes(
+    c: &mut ReaderContext,
+    num_points: usize,
+) -> Result<PackedDeltas, DeserializationError> {
+    Ok(PackedDeltas(read_packed_deltas(c, num_points)?))
 }
 
 impl Serialize for PackedDeltas {
@@ -140,9 +157,30 @@ impl Serialize for PackedDeltas {
 
 #[cfg(test)]
 mod tests {
-    use crate::otvar::packeddeltas::{from_bytes, PackedDeltas};
+    use crate::otvar::packeddeltas::{from_bytes, read_packed_deltas, PackedDeltas};
     use otspec::ReaderContext;
 
+    #[test]
+    fn test_read_packed_deltas() {
+        let packed = vec![
+            0x81, /* two zeros */
+            0x01, 0x05, 0xfb, /* two bytes */
+            0x40, 0x01, 0x00, /* one word */
+            0x00, 0x07, /* not part of these deltas */
+        ];
+        let mut c = ReaderContext::new(packed);
+        assert_eq!(
+            read_packed_deltas(&mut c, 5).unwrap(),
+            vec![0, 0, 5, -5, 256]
+        );
+        assert_eq!(c.ptr, 7);
+        assert_eq!(read_packed_deltas(&mut c, 1).unwrap(), vec![7]);
+
+        // A run longer than the number of deltas wanted
+        let mut c = ReaderContext::new(vec![0x83]);
+        assert!(read_packed_deltas(&mut c, 2).is_err());
+    }
+
     #[test]
     fn test_packed_delta_de() {
         let packed = vec![
@@ -17,42 +17,64 @@ pub struct PackedPoints {
     pub points: Option<Vec<uint16>>,
 }
 
+/// In a run control byte, signifies that the point numbers are two-byte values
+const POINTS_ARE_WORDS: u8 = 0x80;
+/// Mask off a run control byte to find the number of point numbers in the run
+const POINT_RUN_COUNT_MASK: u8 = 0x7f;
+
+/// Decode a packed point number list from a binary buffer.
+///
+/// The point numbers are returned in the order they are stored. An empty
+/// vector is returned if the list has a count of zero, which means that the
+/// deltas apply to all points in the glyph.
+pub fn read_packed_points(c: &mut ReaderContext) -> Result<Vec<uint16>, DeserializationError> {
+    let count1_u8: u8 = c.de()?;
+    let mut count: u16 = count1_u8 as u16;
+    if count > 127 {
+        let count2: u8 = c.de()?;
+        count = (count & 0x7f) << 8 | count2 as u16;
+    }
+    let mut res = vec![];
+    while res.len() < count as usize {
+        let control_byte: u8 = c.de()?;
+        let points_are_words = (control_byte & POINTS_ARE_WORDS) > 0;
+        // "The low 7 bits specify the number of elements in the run minus 1."
+        // MINUS ONE.
+        let run_count = (control_byte & POINT_RUN_COUNT_MASK) + 1;
+        let deltas: Vec<u16> = if points_are_words {
+            c.de_counted(run_count.into())?
+        } else {
+            let delta_bytes: Vec<u8> = c.de_counted(run_count.into())?;
+            delta_bytes.iter().map(|x| *x as u16).collect()
+        };
+        res.extend(deltas);
+    }
+    if res.len() > count as usize {
+        return Err(DeserializationError(format!(
+            "Packed point runs contain {} points, but the count is {}",
+            res.len(),
+            count
+        )));
+    }
+    // Point numbers are stored as differences from the previous one
+    Ok(res
+        .iter()
+        .scan(0_u16, |acc, &x| {
+            *acc = acc.wrapping_add(x);
+            Some(*acc)
+        })
+        .collect())
+}
+
 impl Deserialize for PackedPoints {
     fn from_bytes(c: &mut ReaderContext) -> Result<Self, DeserializationError> {
-        let count1_u8: u8 = c.de()?;
-        let mut count: u16 = count1_u8 as u16;
-        if count > 127 {
-            let count2: u8 = c.de()?;
-            count = (count & 0xff) << 8 | count2 as u16;
-        }
-        if count == 0 {
+        let points = read_packed_points(c)?;
+        if points.is_empty() {
             // All of them
             return Ok(PackedPoints { points: None });
         }
-        let mut res = vec![];
-        while res.len() < count as usize {
-            let control_byte: u8 = c.de()?;
-            let points_are_words = (control_byte & 0x80) > 0;
-            // "The low 7 bits specify the number of elements in the run minus 1."
-            // MINUS ONE.
-            let run_count = (control_byte & 0x7f) + 1;
-            let deltas: Vec<u16> = if points_are_words {
-                c.de_counted(run_count.into())?
-            } else {
-                let delta_bytes: Vec<u8> = c.de_counted(run_count.into())?;
-                delta_bytes.iter().map(|x| *x as u16).collect()
-            };
-            res.extend(deltas);
-        }
-        let cumsum: Vec<u16> = res
-            .iter()
-            .scan(0, |acc, &x| {
-                *acc += x;
-                Some(*acc)
-            })
-            .collect();
         Ok(PackedPoints {
-            points: Some(cumsum),
+            points: Some(points),
         })
     }
 }
@@ -106,7 +128,34 @@ impl Serialize for PackedPoints {
 
 #[cfg(test)]
 mod tests {
+    use super::read_packed_points;
     use crate::otvar::PackedPoints;
+    use otspec::ReaderContext;
+
+    #[test]
+    fn test_read_packed_points() {
+        // A byte run of two points, then a word run of one point
+        let packed = vec![0x03, 0x01, 0x02, 0x05, 0x80, 0x01, 0x00];
+        let mut c = ReaderContext::new(packed);
+        assert_eq!(read_packed_points(&mut c).unwrap(), vec![2, 7, 263]);
+        assert_eq!(c.ptr, 7);
+
+        // A count of zero means all points
+        let mut c = ReaderContext::new(vec![0x00]);
+        assert!(read_packed_points(&mut c).unwrap().is_empty());
+
+        // A two-byte count: 0x80 0x81 is 129 points
+        let mut packed = vec![0x80, 0x81, 0x7f];
+        packed.extend(vec![1; 128]);
+        packed.extend(vec![0x00, 1]);
+        let mut c = ReaderContext::new(packed);
+        let points = read_packed_points(&mut c).unwrap();
+        assert_eq!(points, (1..=129).collect::<Vec<u16>>());
+
+        // Runs which overshoot the count are an error
+        let mut c = ReaderContext::new(vec![0x01, 0x01, 0x00, 0x01]);
+        assert!(read_packed_points(&mut c).is_err());
+    }
 
     #[test]
     fn test_packed_point_de() {
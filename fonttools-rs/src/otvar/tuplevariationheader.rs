@@ -68,7 +68,7 @@ impl TupleVariationHeader {
             res.peakTuple = Some(
                 (c.de_counted(axis_count.into())? as Vec<i16>)
                     .iter()
-                    .map(|x| F2DOT14::from_packed(*x).into())
+                    .map(|x| f2dot14_to_f32(*x))
                     .collect(),
             );
         }
@@ -76,13 +76,13 @@ impl TupleVariationHeader {
             res.startTuple = Some(
                 (c.de_counted(axis_count.into())? as Vec<i16>)
                     .iter()
-                    .map(|x| F2DOT14::from_packed(*x).into())
+                    .map(|x| f2dot14_to_f32(*x))
                     .collect(),
             );
             res.endTuple = Some(
                 (c.de_counted(axis_count.into())? as Vec<i16>)
                     .iter()
-                    .map(|x| F2DOT14::from_packed(*x).into())
+                    .map(|x| f2dot14_to_f32(*x))
                     .collect(),
             );
         }
@@ -100,7 +100,7 @@ impl Serialize for TupleVariationHeader {
         if self.flags.contains(TupleIndexFlags::EMBEDDED_PEAK_TUPLE) {
             if self.peakTuple.is_some() {
                 for coord in self.peakTuple.as_ref().unwrap() {
-                    F2DOT14::from(*coord).to_bytes(data)?;
+                    data.put(f32_to_f2dot14(*coord))?;
                 }
             } else {
                 panic!("EMBEDDED_PEAK_TUPLE was set, but there wasn't one.");
@@ -109,14 +109,14 @@ impl Serialize for TupleVariationHeader {
         if self.flags.contains(TupleIndexFlags::INTERMEDIATE_REGION) {
             if self.startTuple.is_some() {
                 for coord in self.startTuple.as_ref().unwrap() {
-                    F2DOT14::from(*coord).to_bytes(data)?;
+                    data.put(f32_to_f2dot14(*coord))?;
                 }
             } else {
                 panic!("INTERMEDIATE_REGION was set, but there was no start tuple.");
             }
             if self.endTuple.is_some() {
                 for coord in self.endTuple.as_ref().unwrap() {
-                    F2DOT14::from(*coord).to_bytes(data)?;
+                    data.put(f32_to_f2dot14(*coord))?;
                 }
             } else {
                 panic!("INTERMEDIATE_REGION was set, but there was no end tuple.");
@@ -149,6 +149,26 @@ mod tests {
         assert_eq!(deserialized, tvh);
     }
 
+    #[test]
+    fn test_tvh_f2dot14_edges() {
+        let tuple = vec![-1.0, 1.0 / 16384.0, 16383.0 / 16384.0];
+        let tvh = TupleVariationHeader {
+            size: 0,
+            flags: TupleIndexFlags::EMBEDDED_PEAK_TUPLE | TupleIndexFlags::INTERMEDIATE_REGION,
+            sharedTupleIndex: 0,
+            peakTuple: Some(tuple.clone()),
+            startTuple: Some(tuple.clone()),
+            endTuple: Some(tuple),
+        };
+        let serialized = otspec::ser::to_bytes(&tvh).unwrap();
+        assert_eq!(&serialized[4..10], &[0xc0, 0x00, 0x00, 0x01, 0x3f, 0xff]);
+        let deserialized =
+            TupleVariationHeader::from_bytes(&mut ReaderContext::new(serialized.clone()), 3)
+                .unwrap();
+        assert_eq!(deserialized, tvh);
+        assert_eq!(otspec::ser::to_bytes(&deserialized).unwrap(), serialized);
+    }
+
     #[test]
     fn test_tvh_deser() {
         let binary_tvh: Vec<u8> = vec![0, 33, 128, 0, 32, 0];
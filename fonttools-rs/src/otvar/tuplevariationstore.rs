@@ -1,11 +1,10 @@
 use super::iup::iup_contour;
-use super::packeddeltas::from_bytes as packed_deltas_from_bytes;
+use super::packeddeltas::read_packed_deltas;
 use super::{Delta, PackedDeltas, PackedPoints, TupleIndexFlags, TupleVariationHeader};
 use otspec::types::*;
 use otspec::{
     DeserializationError, Deserializer, ReaderContext, SerializationError, Serialize, Serializer,
 };
-use std::collections::VecDeque;
 
 /// A record within a tuple variation store
 ///
@@ -73,11 +72,12 @@ impl TupleVariationStore {
         point_count: uint16,
     ) -> Result<Self, DeserializationError> {
         // Begin with the "GlyphVariationData header"
+        let start = c.ptr;
         let packed_count: uint16 = c.de()?;
         let count = packed_count & 0x0FFF;
         let points_are_shared = (packed_count & 0x8000) != 0;
-        let mut shared_points = vec![];
-        let _data_offset: uint16 = c.de()?;
+        let mut shared_points = None;
+        let data_offset: uint16 = c.de()?;
 
         // Read the headers
         let mut headers: Vec<TupleVariationHeader> = vec![];
@@ -89,53 +89,58 @@ impl TupleVariationStore {
 
         // Now we are into the "serialized data block"
         // ...which begins with Shared "point" numbers (optional per flag in the header)
+        c.ptr = start + data_offset as usize;
+        // These apply to every tuple which does not have its own points
         if points_are_shared {
             let pp: PackedPoints = c.de()?;
-            shared_points = match pp.points {
+            shared_points = Some(match pp.points {
                 Some(pts) => pts,
                 None => (0..point_count).collect(),
-            };
+            });
         }
 
         // And finally per-tuple variation data
         for header in headers {
-            let mut points_for_this_header: VecDeque<u16>;
+            // Each tuple's data is located by its size, not by how much we read
+            let next_tuple = c.ptr + header.size as usize;
             /* Private points? */
-            if header
+            let points_for_this_header: Vec<u16> = if header
                 .flags
                 .contains(TupleIndexFlags::PRIVATE_POINT_NUMBERS)
             {
                 let private_points: PackedPoints = c.de()?;
-                if private_points.points.is_some() {
-                    points_for_this_header = private_points.points.unwrap().clone().into();
-                } else {
-                    points_for_this_header = (0..point_count).collect();
+                match private_points.points {
+                    Some(pts) => pts,
+                    None => (0..point_count).collect(),
                 }
+            } else if let Some(shared_points) = &shared_points {
+                shared_points.clone()
             } else {
-                points_for_this_header = shared_points.clone().into();
-            }
+                log::warn!("Tuple variation has neither private nor shared point numbers");
+                vec![]
+            };
             #[allow(clippy::branches_sharing_code)] // Just easier to understand this way
-            let mut deltas: VecDeque<Delta> = if is_gvar {
-                let packed_x = packed_deltas_from_bytes(c, points_for_this_header.len())?.0;
-                let packed_y = packed_deltas_from_bytes(c, points_for_this_header.len())?.0;
+            let deltas: Vec<Delta> = if is_gvar {
+                let packed_x = read_packed_deltas(c, points_for_this_header.len())?;
+                let packed_y = read_packed_deltas(c, points_for_this_header.len())?;
                 packed_x
                     .iter()
                     .zip(packed_y.iter())
                     .map(|(x, y)| Delta::Delta2D((*x, *y)))
                     .collect()
             } else {
-                let packed = packed_deltas_from_bytes(c, points_for_this_header.len())?.0;
+                let packed = read_packed_deltas(c, points_for_this_header.len())?;
                 packed.iter().map(|x| Delta::Delta1D(*x)).collect()
             };
-            let mut all_deltas: Vec<Option<Delta>> = vec![];
-            for i in 0..point_count {
-                if !points_for_this_header.is_empty() && i == points_for_this_header[0] {
-                    all_deltas.push(Some(deltas.pop_front().unwrap()));
-                    points_for_this_header.pop_front();
-                } else {
-                    all_deltas.push(None); // IUP needed later
+            // Points without a delta are left as None; IUP is needed later
+            let mut all_deltas: Vec<Option<Delta>> = vec![None; point_count.into()];
+            for (pt, delta) in points_for_this_header.iter().zip(deltas) {
+                match all_deltas.get_mut(*pt as usize) {
+                    Some(slot) => *slot = Some(delta),
+                    None => log::warn!("Ignoring delta for out-of-range point {}", pt),
                 }
             }
+            c.ptr = next_tuple;
             variations.push(TupleVariation(header, all_deltas))
         }
 
@@ -275,6 +280,77 @@ mod tests {
         assert_eq!(tvs, expected);
     }
 
+    #[test]
+    fn test_tvs_de_shared_and_private_points() {
+        let binary_tvs: Vec<u8> = vec![
+            0x80, 0x02, /* tupleVariationCount. SHARED_POINT_NUMBERS */
+            0x00, 0x10, /* dataOffset */
+            /* TVH 1 */
+            0x00, 0x06, /* variationDataSize: 6 bytes */
+            0x80, 0x00, /* tuple index. EMBEDDED_PEAK_TUPLE */
+            0x40, 0x00, /* Peak tuple record */
+            /* TVH 2 */
+            0x00, 0x07, /* variationDataSize: 7 bytes */
+            0xa0, 0x00, /* tuple index. EMBEDDED_PEAK_TUPLE | PRIVATE_POINT_NUMBERS */
+            0xc0, 0x00, /* Peak tuple record */
+            /* Shared point numbers: 0, 2 */
+            0x02, 0x01, 0x00, 0x02, /* TVH 1 data: x deltas, y deltas */
+            0x01, 0x0a, 0x14, 0x01, 0xfb, 0x00,
+            /* TVH 2 data: private point numbers (1), x deltas, y deltas */
+            0x01, 0x00, 0x01, 0x00, 0x07, 0x00, 0x08,
+        ];
+        let tvs = TupleVariationStore::from_bytes(&mut ReaderContext::new(binary_tvs), 1, true, 4)
+            .unwrap();
+        assert_eq!(tvs.0.len(), 2);
+        assert_eq!(tvs.0[0].0.peakTuple, Some(vec![1.0]));
+        assert_eq!(
+            tvs.0[0].1,
+            vec![Some(Delta2D((10, -5))), None, Some(Delta2D((20, 0))), None]
+        );
+        assert!(tvs.0[1]
+            .0
+            .flags
+            .contains(TupleIndexFlags::PRIVATE_POINT_NUMBERS));
+        assert_eq!(tvs.0[1].0.peakTuple, Some(vec![-1.0]));
+        assert_eq!(tvs.0[1].1, vec![None, Some(Delta2D((7, 8))), None, None]);
+    }
+
+    #[test]
+    fn test_tvs_de_shared_matches_private() {
+        // The same deltas for points 0 and 2, once using shared point
+        // numbers and once using private point numbers in each tuple
+        let shared: Vec<u8> = vec![
+            0x80, 0x02, /* tupleVariationCount. SHARED_POINT_NUMBERS */
+            0x00, 0x10, /* dataOffset */
+            0x00, 0x06, 0x80, 0x00, 0x40, 0x00, /* TVH 1, peak 1.0 */
+            0x00, 0x06, 0x80, 0x00, 0xc0, 0x00, /* TVH 2, peak -1.0 */
+            0x02, 0x01, 0x00, 0x02, /* Shared point numbers: 0, 2 */
+            0x01, 0x0a, 0x14, 0x01, 0xfb, 0x00, /* TVH 1 data */
+            0x01, 0x03, 0x04, 0x01, 0x05, 0x06, /* TVH 2 data */
+        ];
+        let private: Vec<u8> = vec![
+            0x00, 0x02, /* tupleVariationCount */
+            0x00, 0x10, /* dataOffset */
+            0x00, 0x0a, 0xa0, 0x00, 0x40, 0x00, /* TVH 1, peak 1.0, private points */
+            0x00, 0x0a, 0xa0, 0x00, 0xc0, 0x00, /* TVH 2, peak -1.0, private points */
+            0x02, 0x01, 0x00, 0x02, 0x01, 0x0a, 0x14, 0x01, 0xfb, 0x00, /* TVH 1 */
+            0x02, 0x01, 0x00, 0x02, 0x01, 0x03, 0x04, 0x01, 0x05, 0x06, /* TVH 2 */
+        ];
+        let shared =
+            TupleVariationStore::from_bytes(&mut ReaderContext::new(shared), 1, true, 4).unwrap();
+        let private =
+            TupleVariationStore::from_bytes(&mut ReaderContext::new(private), 1, true, 4).unwrap();
+        assert_eq!(shared.0.len(), 2);
+        for (s, p) in shared.0.iter().zip(private.0.iter()) {
+            assert_eq!(s.0.peakTuple, p.0.peakTuple);
+            assert_eq!(s.1, p.1);
+        }
+        assert_eq!(
+            shared.0[1].1,
+            vec![Some(Delta2D((3, 5))), None, Some(Delta2D((4, 6))), None]
+        );
+    }
+
     #[test]
     fn test_tvs_ser() {
         let expected: Vec<u8> = vec![
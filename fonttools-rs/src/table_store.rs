@@ -8,13 +8,14 @@
 use std::borrow::Borrow;
 use std::cell::RefCell;
 use std::collections::BTreeMap;
-use std::convert::{TryFrom, TryInto};
+use std::convert::TryFrom;
+use std::error::Error;
 use std::fmt::Debug;
-use std::ops::Deref;
+use std::ops::{Deref, Range};
 use std::rc::Rc;
 
 use otspec::types::Tag;
-use otspec::{DeserializationError, ReaderContext, SerializationError, Serialize};
+use otspec::{DeserializationError, Deserializer, ReaderContext, SerializationError, Serialize};
 
 use crate::tables;
 
@@ -43,6 +44,25 @@ pub(crate) struct TableLoader {
 #[derive(Debug, Default)]
 pub struct TableSet {
     tables: BTreeMap<Tag, RefCell<LazyItem>>,
+    /// The `glyf` data and `loca` offsets the font was loaded with, kept
+    /// after the `glyf` table is replaced so that unchanged glyphs can be
+    /// written out exactly as they were.
+    original_glyf: Option<OriginalGlyf>,
+    /// Whether unchanged glyphs drop the padding they had in the original
+    /// `glyf` data when it is regenerated; see
+    /// [`TableSet::set_preserve_glyf_gaps`].
+    pack_glyf: bool,
+}
+
+/// Binary `glyf` data along with the span of each glyph within it.
+#[derive(Debug)]
+struct OriginalGlyf {
+    data: Rc<[u8]>,
+    /// The table decoded from `data`, which edited glyphs are told apart from.
+    glyphs: Rc<tables::glyf::glyf>,
+    /// The range from each glyph's offset up to the next glyph's data,
+    /// including any padding; `None` for glyphs with no data.
+    spans: Vec<Option<Range<usize>>>,
 }
 
 /// A table in a font, which may or may not have been loaded yet.
@@ -294,6 +314,9 @@ impl TableSet {
     pub fn insert(&mut self, table: impl Into<Table>) {
         let table = table.into();
         let tag = table.tag;
+        if tag == tables::glyf::TAG {
+            self.remember_original_glyf();
+        }
         let table = RefCell::new(LazyItem::Loaded(table));
         self.tables.insert(tag, table);
     }
@@ -307,6 +330,110 @@ impl TableSet {
             .insert(tag, RefCell::new(LazyItem::Unloaded(data.into())));
     }
 
+    /// Reads a single glyph from the `glyf` table.
+    ///
+    /// If the `glyf` table has not been loaded yet, only the requested glyph
+    /// is parsed, using the `loca` offsets, and the table is left unloaded.
+    /// Otherwise the glyph is copied from the loaded table. Returns `None` if
+    /// there is no `glyf` table or no glyph with that ID.
+    pub fn glyph(&self, gid: u16) -> Result<Option<tables::glyf::Glyph>, DeserializationError> {
+        let data = match self.tables.get(&tables::glyf::TAG) {
+            Some(item) => match &*item.borrow() {
+                LazyItem::Unloaded(data) => Some(data.clone()),
+                LazyItem::Loaded(_) => None,
+            },
+            None => return Ok(None),
+        };
+        let data = match data {
+            Some(data) => data,
+            None => {
+                return Ok(self
+                    .glyf()?
+                    .and_then(|glyf| glyf.glyphs.get(gid as usize).cloned()))
+            }
+        };
+        let loca = self
+            .loca()?
+            .ok_or_else(|| DeserializationError("No loca table".into()))?;
+        let gid = gid as usize;
+        let start = match loca.indices.get(gid) {
+            Some(Some(start)) => *start as usize,
+            Some(None) => return Ok(Some(tables::glyf::Glyph::empty())),
+            None => return Ok(None),
+        };
+        let end = loca.indices[gid + 1..]
+            .iter()
+            .flatten()
+            .next()
+            .map_or(data.len(), |&x| x as usize);
+        let glyph_data = data.get(start..end).ok_or_else(|| {
+            DeserializationError(format!(
+                "Glyph {} runs from {} to {}, outside the glyf table of {} bytes",
+                gid,
+                start,
+                end,
+                data.len()
+            ))
+        })?;
+        otspec::de::from_bytes(glyph_data).map(Some)
+    }
+
+    /// Sets whether the space between glyphs in the `glyf` table as loaded is
+    /// kept when the table is regenerated.
+    ///
+    /// Fonts pad their glyphs for alignment (to two or four bytes), and some
+    /// pad them further, leaving gaps between them. By default, each glyph is
+    /// given at least the length of its original span (from its offset to the
+    /// next glyph's), so an unedited font keeps its offsets exactly, as do
+    /// later glyphs after an edit which does not make a glyph longer. Turning
+    /// this off packs unchanged glyphs tightly, padding them only to an even
+    /// length.
+    pub fn set_preserve_glyf_gaps(&mut self, preserve: bool) {
+        self.pack_glyf = !preserve;
+    }
+
+    /// If the current `glyf` table is still in its binary form, keeps hold of
+    /// it (along with the `loca` offsets) before it is replaced.
+    fn remember_original_glyf(&mut self) {
+        let data = match self.tables.get(&tables::glyf::TAG) {
+            Some(item) => match &*item.borrow() {
+                LazyItem::Unloaded(data) => data.clone(),
+                LazyItem::Loaded(Table {
+                    raw: Some(data), ..
+                }) => data.clone(),
+                LazyItem::Loaded(_) => return,
+            },
+            None => return,
+        };
+        let glyphs = match self.glyf() {
+            Ok(Some(glyf)) => glyf.inner,
+            _ => return,
+        };
+        if let Ok(Some(loca)) = self.loca() {
+            let mut starts: Vec<usize> =
+                loca.indices.iter().flatten().map(|&x| x as usize).collect();
+            starts.sort_unstable();
+            // Offsets need not increase with glyph ID, so a glyph's data runs
+            // up to the nearest offset after its own.
+            let spans = loca
+                .indices
+                .iter()
+                .map(|offset| {
+                    let start = (*offset)? as usize;
+                    let end = starts
+                        .get(starts.partition_point(|&x| x <= start))
+                        .map_or(data.len(), |&x| x);
+                    Some(start..end)
+                })
+                .collect();
+            self.original_glyf = Some(OriginalGlyf {
+                data,
+                glyphs,
+                spans,
+            });
+        }
+    }
+
     fn load_if_needed(&self, tag: Tag) -> Result<(), DeserializationError> {
         let item = match self.tables.get(&tag) {
             Some(item) => item,
@@ -337,14 +464,14 @@ impl TableSet {
                     .maxp()?
                     .map(|maxp| maxp.num_glyphs())
                     .ok_or_else(|| DeserializationError("deserialize head before loca".into()))?;
-                tables::GPOS::from_bytes(&mut ReaderContext::new(data.to_vec()), num_glyphs)?.into()
+                tables::GPOS::from_bytes(&mut ReaderContext::from_slice(&data), num_glyphs)?.into()
             }
             b"GSUB" => {
                 let num_glyphs = self
                     .maxp()?
                     .map(|maxp| maxp.num_glyphs())
                     .ok_or_else(|| DeserializationError("deserialize head before loca".into()))?;
-                tables::GSUB::from_bytes(&mut ReaderContext::new(data.to_vec()), num_glyphs)?.into()
+                tables::GSUB::from_bytes(&mut ReaderContext::from_slice(&data), num_glyphs)?.into()
             }
             b"head" => otspec::de::from_bytes::<tables::head::head>(&data)?.into(),
             b"hhea" => otspec::de::from_bytes::<tables::hhea::hhea>(&data)?.into(),
@@ -364,18 +491,15 @@ impl TableSet {
                     //TODO: are we allowed to not have hhea?
                     .ok_or_else(|| DeserializationError("deserialize hhea before hmtx".into()))?;
 
-                tables::hmtx::from_bytes(
-                    &mut ReaderContext::new(data.to_vec()),
-                    number_of_hmetrics,
-                )?
-                .into()
+                tables::hmtx::from_bytes(&mut ReaderContext::from_slice(&data), number_of_hmetrics)?
+                    .into()
             }
             b"loca" => {
                 let is_32bit = self
                     .head()?
                     .map(|head| head.indexToLocFormat == 1)
                     .ok_or_else(|| DeserializationError("deserialize head before loca".into()))?;
-                tables::loca::from_bytes(&mut ReaderContext::new(data.to_vec()), is_32bit)?.into()
+                tables::loca::from_bytes(&mut ReaderContext::from_slice(&data), is_32bit)?.into()
             }
             b"glyf" => {
                 let loca = self
@@ -393,7 +517,11 @@ impl TableSet {
                     .map(|g| g.gvar_coords_and_ends())
                     .collect();
 
-                tables::gvar::from_bytes(&data, coords_and_ends)?.into()
+                let gvar = tables::gvar::from_bytes(&data, coords_and_ends)?;
+                if let Some(fvar) = self.fvar()? {
+                    gvar.validate_against_fvar(fvar.axes.len())?;
+                }
+                gvar.into()
             }
             _ => LoadedTable::Unknown(data.clone()),
         };
@@ -405,7 +533,7 @@ impl TableSet {
         })
     }
 
-    fn is_serialized(&self, tag: Tag) -> Option<bool> {
+    pub(crate) fn is_serialized(&self, tag: Tag) -> Option<bool> {
         self.tables
             .get(&tag)
             .map(|table| match table.borrow().deref() {
@@ -414,42 +542,71 @@ impl TableSet {
             })
     }
 
-    pub(crate) fn compile_glyf_loca_maxp(&mut self) {
+    /// Serializes a modified `glyf` table, regenerating `loca` and updating
+    /// `maxp`, `head` and `hhea` to match. Fails if a glyph cannot be
+    /// encoded, leaving the tables as they were.
+    pub(crate) fn compile_glyf_loca_maxp(&mut self) -> Result<(), Box<dyn Error>> {
         // leave early if we have no work to do.
         if self.is_serialized(tables::glyf::TAG).unwrap_or(true)
             && self.is_serialized(tables::loca::TAG).unwrap_or(true)
             && self.is_serialized(tables::maxp::TAG).unwrap_or(true)
         {
-            return;
+            return Ok(());
         }
-        let glyf = match self.glyf().unwrap() {
+        let glyf = match self.glyf()? {
             Some(table) => table,
             None => {
                 log::warn!("No glyf table");
-                return;
+                return Ok(());
             }
         };
-        let glyf_count = glyf.glyphs.len();
+        // Glyphs which are unchanged from the font as loaded are copied over
+        // byte for byte, preserving their encoding and padding. A glyph
+        // counts as unchanged if it is equal to the one decoded when the
+        // table was loaded; if the table itself was never modified, every
+        // glyph is.
+        let pack = self.pack_glyf;
+        let original = self.original_glyf.as_ref();
+        let table_unchanged = original.is_some_and(|o| Rc::ptr_eq(&o.glyphs, &glyf.inner));
         let mut glyf_output: Vec<u8> = vec![];
-        let mut loca_indices: Vec<u32> = vec![];
-
-        for g in &glyf.glyphs {
-            let cur_len: u32 = glyf_output.len().try_into().unwrap();
-            loca_indices.push(cur_len);
-            if g.is_empty() {
-                continue;
-            }
-            glyf_output.extend(otspec::ser::to_bytes(&g).unwrap());
-            // Add multiple-of-four padding
-            while glyf_output.len() % 4 != 0 {
-                glyf_output.push(0);
+        let loca_indices = glyf.write_glyphs_to(&mut glyf_output, |gid, g, data| {
+            let mut original_span = None;
+            let original_bytes = original.and_then(|o| {
+                let span = o.spans.get(gid)?.clone()?;
+                original_span = Some(span.len());
+                if !table_unchanged && o.glyphs.glyphs.get(gid) != Some(g) {
+                    return None;
+                }
+                if pack {
+                    // Only the glyph's own data is wanted, so find where it
+                    // ends within the span.
+                    let mut c = ReaderContext::from_slice(&o.data[span.clone()]);
+                    let _: tables::glyf::Glyph = c.de().ok()?;
+                    return Some(&o.data[span.start..span.start + c.ptr]);
+                }
+                Some(&o.data[span])
+            });
+            let padding = match original_bytes {
+                Some(bytes) => {
+                    data.extend(bytes);
+                    2
+                }
+                None => {
+                    g.to_bytes(data)
+                        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+                    4
+                }
+            };
+            // Add padding: offsets must be even, and new glyphs are
+            // padded to a multiple of four
+            data.resize(data.len().div_ceil(padding) * padding, 0);
+            if let Some(span) = original_span.filter(|_| !pack) {
+                if data.len() < span {
+                    data.resize(span, 0);
+                }
             }
-        }
-        if glyf_output.is_empty() {
-            // Sad special case
-            glyf_output.push(0);
-        }
-        loca_indices.push(glyf_output.len().try_into().unwrap());
+            Ok(())
+        })?;
         let loca_is32bit = u16::try_from(glyf_output.len()).is_err();
 
         let loca_data = if loca_is32bit {
@@ -463,22 +620,25 @@ impl TableSet {
             data
         };
 
+        let mut maxp = self.maxp()?.ok_or("No maxp table")?;
+        let mut head = self.head()?.ok_or("No head table")?;
         self.insert_raw(tables::glyf::TAG, glyf_output);
         self.insert_raw(tables::loca::TAG, loca_data);
 
-        let mut maxp = self.maxp().unwrap().unwrap();
-        maxp.set_num_glyphs(glyf_count.try_into().unwrap());
+        maxp.recalc_from_glyf(&glyf);
+        self.insert(maxp);
 
-        let mut head = self.head().unwrap().unwrap();
         head.indexToLocFormat = if loca_is32bit { 1 } else { 0 };
+        head.recalc_bbox_from_glyf(&glyf);
         self.insert(head);
 
-        if let Some(hmetric_count) = self.hmtx().unwrap().map(|t| t.number_of_hmetrics()) {
-            if let Some(mut hhea) = self.hhea().unwrap() {
+        if let Some(hmetric_count) = self.hmtx()?.map(|t| t.number_of_hmetrics()) {
+            if let Some(mut hhea) = self.hhea()? {
                 hhea.numberOfHMetrics = hmetric_count;
                 self.insert(hhea);
             }
         }
+        Ok(())
     }
 
     pub(crate) fn compile_gsub_gpos(&mut self) {
@@ -258,41 +258,70 @@ impl cmap4 {
         }
     }
 
+    /// Resolves the segments of this subtable into a character map.
+    ///
+    /// Each segment maps its range of codes either by adding `idDelta` to
+    /// the code (if its `idRangeOffset` is zero) or by looking the code up in
+    /// the glyph ID array and adding `idDelta` to any nonzero result, modulo
+    /// 65536. `idRangeOffset` is a byte offset from its own position in the
+    /// `idRangeOffsets` array, so the index into the glyph ID array is
+    /// `idRangeOffset / 2 + (code - startCode) - (segCount - segment index)`.
+    /// A zero in the glyph ID array, or a lookup which falls outside it, maps
+    /// the code to glyph 0 (the missing glyph) without adding `idDelta`. As in
+    /// fontTools, the final segment for code 0xFFFF which terminates the
+    /// table is skipped.
     fn to_mapping(&self) -> BTreeMap<uint32, uint16> {
         let mut map = BTreeMap::new();
-        for i in 0..(self.startCode.len() - 1) {
+        let seg_count = self
+            .startCode
+            .len()
+            .min(self.endCode.len())
+            .min(self.idDelta.len())
+            .min(self.idRangeOffsets.len());
+        for i in 0..seg_count {
             let start = self.startCode[i];
             let end = self.endCode[i];
-            let delta = self.idDelta[i];
-            let range_offset = self.idRangeOffsets[i];
-            let partial = ((range_offset / 2) as i32 - (start as i32) + (i as i32)
-                - (self.idRangeOffsets.len() as i32)) as i32;
-            if end == 0xffff {
+            if i == seg_count - 1 && start == 0xffff {
                 break;
             }
-            let range_char_codes = start..(1 + end);
-            for char_code in range_char_codes {
-                if range_offset == 0 {
-                    map.insert(char_code as u32, (char_code as i32 + delta as i32) as u16);
+            let delta = self.idDelta[i];
+            let range_offset = self.idRangeOffsets[i];
+            for char_code in start..=end {
+                let glyph_id = if range_offset == 0 {
+                    char_code.wrapping_add(delta as u16)
                 } else {
-                    let index = (char_code as i32 + partial) as usize;
-                    if index >= self.glyphIdArray.len() {
-                        break;
-                    }
-                    assert!(index < self.glyphIdArray.len());
-                    if self.glyphIdArray[index] != 0 {
-                        let glyph_id = self.glyphIdArray[index] as i16 + delta;
-                        map.insert(char_code as u32, glyph_id as u16);
-                    } else {
-                        map.insert(char_code as u32, 0);
+                    let index = (range_offset / 2) as usize + (char_code - start) as usize + i;
+                    match index
+                        .checked_sub(seg_count)
+                        .and_then(|ix| self.glyphIdArray.get(ix))
+                    {
+                        Some(0) | None => 0,
+                        Some(&gid) => gid.wrapping_add(delta as u16),
                     }
-                }
+                };
+                map.insert(char_code as u32, glyph_id);
             }
         }
         map
     }
 }
 
+/// Parses a format 4 `cmap` subtable into a mapping from character codes to
+/// glyph IDs.
+///
+/// Characters explicitly mapped to glyph 0 are included, as are characters
+/// looked up in the glyph ID array whose entry is zero (the missing glyph).
+pub fn parse_format4(bytes: &[u8]) -> Result<BTreeMap<uint32, uint16>, DeserializationError> {
+    let subtable: cmap4 = otspec::de::from_bytes(bytes)?;
+    if subtable.format != 4 {
+        return Err(DeserializationError(format!(
+            "Expected a format 4 cmap subtable, found format {}",
+            subtable.format
+        )));
+    }
+    Ok(subtable.to_mapping())
+}
+
 impl Deserialize for cmap4 {
     fn from_bytes(c: &mut ReaderContext) -> Result<Self, DeserializationError> {
         let format: uint16 = c.de()?;
@@ -307,7 +336,7 @@ impl Deserialize for cmap4 {
         let id_delta: Vec<int16> = c.de_counted(segcount)?;
         let id_range_offsets: Vec<uint16> = c.de_counted(segcount)?;
         let len_so_far = 16 + (segcount * 2 * 4);
-        let remainder = length as usize - len_so_far;
+        let remainder = (length as usize).saturating_sub(len_so_far);
         let glyph_id_array: Vec<u16> = c.de_counted(remainder / 2).unwrap_or_default();
         Ok(cmap4 {
             format,
@@ -667,7 +696,68 @@ impl Deserialize for cmap {
     }
 }
 
+/// Which platform's subtables [`cmap::gid_for`] should look at first, when
+/// several subtables map the same codepoint.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum PlatformPreference {
+    /// Prefer Windows (platform 3) subtables, then Unicode (platform 0).
+    #[default]
+    Windows,
+    /// Prefer Unicode (platform 0) subtables, then Windows (platform 3).
+    Unicode,
+}
+
+impl CmapSubtable {
+    /// Whether this subtable can map codepoints outside the Basic
+    /// Multilingual Plane.
+    fn is_full_repertoire(&self) -> bool {
+        matches!(
+            (self.platformID, self.encodingID),
+            (3, 10) | (0, 4) | (0, 6)
+        ) || self.format == 12
+    }
+}
+
 impl cmap {
+    /// Looks up the glyph ID for a Unicode codepoint.
+    ///
+    /// Of the subtables which map the codepoint, the best one is used.
+    /// Unicode subtables come before others (such as Macintosh or Windows
+    /// Symbol subtables), which are only consulted as a fallback. For
+    /// codepoints outside the BMP, subtables covering the full Unicode
+    /// repertoire (such as format 12) come first; within the BMP, format 4
+    /// subtables do. Ties are broken by `preference` and then by the order of
+    /// the subtables. A mapping to glyph 0 (`.notdef`) counts as no mapping,
+    /// so the next best subtable is used instead. Returns `None` if no
+    /// subtable maps the codepoint.
+    pub fn gid_for(&self, cp: u32, preference: PlatformPreference) -> Option<u16> {
+        let supplementary = cp > 0xffff;
+        let (first, second) = match preference {
+            PlatformPreference::Windows => (3, 0),
+            PlatformPreference::Unicode => (0, 3),
+        };
+        self.subtables
+            .iter()
+            .filter_map(|st| Some((st, *st.mapping.get(&cp)?)))
+            .filter(|&(_, gid)| gid != 0)
+            .min_by_key(|(st, _)| {
+                let repertoire = if supplementary {
+                    !st.is_full_repertoire()
+                } else {
+                    st.format != 4
+                };
+                let platform = if st.platformID == first {
+                    0
+                } else if st.platformID == second {
+                    1
+                } else {
+                    2
+                };
+                (!st.is_unicode(), repertoire, platform)
+            })
+            .map(|(_, gid)| gid)
+    }
+
     /// Tries to find a mapping targetted at the the given platform and
     /// encoding. Returns a `Some<map>` if one is found, or `None` otherwise.
     pub fn get_mapping(
@@ -724,6 +814,7 @@ impl cmap {
 
 #[cfg(test)]
 mod tests {
+    use crate::tables::glyf::tests::sample_font;
     use otspec::btreemap;
     use otspec::Serialize;
     use pretty_assertions::assert_eq;
@@ -866,6 +957,104 @@ mod tests {
         assert_eq!(serialized, binary_cmap);
     }
 
+    #[test]
+    fn cmap_parse_format4() {
+        let font = sample_font();
+        let mut binary_cmap = vec![];
+        font.tables
+            .write_table(super::TAG, &mut binary_cmap)
+            .unwrap();
+        let num_tables = u16::from_be_bytes([binary_cmap[2], binary_cmap[3]]) as usize;
+        let offsets: Vec<usize> = (0..num_tables)
+            .map(|i| {
+                let record = &binary_cmap[4 + i * 8..][..8];
+                u32::from_be_bytes(record[4..8].try_into().unwrap()) as usize
+            })
+            .filter(|&offset| binary_cmap[offset..offset + 2] == [0, 4])
+            .collect();
+        assert!(!offsets.is_empty());
+        // A is glyph 0 in this font, and acutecomb is mapped by a segment
+        // with a large negative delta
+        let expected = btreemap!(
+            0x20 => 4, 0x24 => 5, 0x41 => 0, 0x4f => 2, 0x56 => 3, 0xc1 => 1, 0x301 => 7
+        );
+        for offset in offsets {
+            let mapping = super::parse_format4(&binary_cmap[offset..]).unwrap();
+            assert_eq!(mapping, expected);
+        }
+
+        // A segment using the glyph ID array, with a hole in it
+        let subtable = super::cmap4 {
+            format: 4,
+            length: 0,
+            language: 0,
+            segCountX2: 4,
+            searchRange: 0,
+            entrySelector: 0,
+            rangeShift: 0,
+            endCode: vec![0x33, 0xffff],
+            reservedPad: 0,
+            startCode: vec![0x30, 0xffff],
+            idDelta: vec![10, 1],
+            idRangeOffsets: vec![4, 0],
+            glyphIdArray: vec![1, 0, 3],
+        };
+        assert_eq!(
+            subtable.to_mapping(),
+            btreemap!(0x30 => 11, 0x31 => 0, 0x32 => 13, 0x33 => 0)
+        );
+        let binary = otspec::ser::to_bytes(&subtable).unwrap();
+        assert!(super::parse_format4(&binary[..4]).is_err());
+        assert!(super::parse_format4(&binary_cmap[..20]).is_err());
+    }
+
+    #[test]
+    fn cmap_gid_for() {
+        use super::PlatformPreference::{Unicode, Windows};
+        let subtable = |format, platform, encoding, mapping| super::CmapSubtable {
+            format,
+            platformID: platform,
+            encodingID: encoding,
+            languageID: 0,
+            mapping,
+            uvs_mapping: None,
+        };
+        let fcmap = super::cmap {
+            subtables: vec![
+                subtable(4, 0, 3, btreemap!(0x41 => 3, 0x42 => 4)),
+                subtable(4, 3, 1, btreemap!(0x41 => 1, 0x42 => 2)),
+                subtable(12, 3, 10, btreemap!(0x41 => 5, 0x1f600 => 9)),
+                subtable(0, 1, 0, btreemap!(0x41 => 7, 0x43 => 8)),
+            ],
+        };
+        // BMP codepoints come from the format 4 subtables
+        assert_eq!(fcmap.gid_for(0x41, Windows), Some(1));
+        assert_eq!(fcmap.gid_for(0x41, Unicode), Some(3));
+        assert_eq!(fcmap.gid_for(0x42, Windows), Some(2));
+        // Supplementary codepoints come from the format 12 subtable
+        assert_eq!(fcmap.gid_for(0x1f600, Windows), Some(9));
+        assert_eq!(fcmap.gid_for(0x1f600, Unicode), Some(9));
+        // Falling back to the Macintosh subtable
+        assert_eq!(fcmap.gid_for(0x43, Windows), Some(8));
+        assert_eq!(fcmap.gid_for(0x44, Windows), None);
+
+        let only = super::cmap {
+            subtables: vec![subtable(12, 0, 4, btreemap!(0x41 => 1, 0x1f600 => 2))],
+        };
+        assert_eq!(only.gid_for(0x41, Windows), Some(1));
+        assert_eq!(only.gid_for(0x1f600, Windows), Some(2));
+
+        // A mapping to .notdef falls through to the next subtable
+        let notdef = super::cmap {
+            subtables: vec![
+                subtable(4, 3, 1, btreemap!(0x41 => 0, 0x42 => 0)),
+                subtable(4, 0, 3, btreemap!(0x41 => 3)),
+            ],
+        };
+        assert_eq!(notdef.gid_for(0x41, Windows), Some(3));
+        assert_eq!(notdef.gid_for(0x42, Windows), None);
+    }
+
     #[test]
     fn cmap_deser_14() {
         let binary_cmap = vec![
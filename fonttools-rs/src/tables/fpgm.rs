@@ -14,7 +14,7 @@ pub struct fpgm(Vec<uint8>);
 impl Deserialize for fpgm {
     fn from_bytes(c: &mut ReaderContext) -> Result<Self, DeserializationError> {
         // This is very silly
-        Ok(fpgm(c.input.clone()))
+        Ok(fpgm(c.input.to_vec()))
     }
 }
 
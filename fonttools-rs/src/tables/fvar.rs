@@ -81,6 +81,14 @@ pub struct fvar {
     pub instances: Vec<InstanceRecord>,
 }
 
+impl fvar {
+    /// Returns the tags of the font's axes, in the order used by tuples in
+    /// variation tables.
+    pub fn axis_tags(&self) -> Vec<Tag> {
+        self.axes.iter().map(|axis| axis.axisTag).collect()
+    }
+}
+
 impl Deserialize for fvar {
     fn from_bytes(c: &mut ReaderContext) -> Result<Self, DeserializationError> {
         c.push();
@@ -1,21 +1,41 @@
-use self::glyph::CompositeMaxpValues;
 use core::cmp::max;
+use std::collections::{HashMap, HashSet};
 
 use super::maxp::maxp;
-use otspec::{DeserializationError, Deserializer, ReaderContext};
+use otmath::ot_round;
+use otspec::{DeserializationError, Deserializer, ReaderContext, Serialize};
 
+/// A builder for constructing simple glyphs
+mod builder;
 /// Structures for handling components within a composite glyph
 mod component;
 /// Utilities for handling contours
 pub mod contourutils;
+/// Comparing two glyf tables
+mod diff;
+/// Errors raised while parsing the glyf table
+mod error;
 /// Structures for handling simple glyph descriptions
 mod glyph;
+/// Checking whether glyphs can be interpolated
+mod interpolatable;
+/// Checking glyphs against the limits declared in the maxp table
+mod limits;
 /// A representation of a contour point
 mod point;
+/// Glyph outlines as kurbo shapes
+mod shape;
 
+pub use builder::GlyphBuilder;
 pub use component::{Component, ComponentFlags};
-pub use glyph::Glyph;
+pub use diff::{diff, GlyphChange, GlyphDiff, PointChange};
+pub use error::GlyfError;
+pub use glyph::{sidebearings, Glyph, GlyphKind, Orientation, Segment};
+pub use interpolatable::{check_interpolatable, IncompatibilityReport};
+pub use limits::LimitViolation;
+pub use otmath::RoundMode;
 pub use point::Point;
+pub use shape::GlyphShape;
 
 /// The 'glyf' OpenType tag.
 pub const TAG: otspec::types::Tag = crate::tag!("glyf");
@@ -28,65 +48,150 @@ pub struct glyf {
     pub glyphs: Vec<Glyph>,
 }
 
+/// A breakdown of the space used by a glyf table.
+///
+/// All sizes are in bytes and include the padding which aligns each glyph to a
+/// four-byte boundary.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct SizeReport {
+    /// The size of the serialized table.
+    pub total: usize,
+    /// The bytes used by simple glyphs.
+    pub simple: usize,
+    /// The bytes used by composite glyphs.
+    pub composite: usize,
+    /// The bytes of TrueType instructions, in both simple and composite glyphs.
+    pub instructions: usize,
+}
+
 /// Deserialize the glyf table from a binary buffer.
 ///
-/// loca_offsets must be obtained from the `loca` table.
-pub fn from_bytes(c: &[u8], loca_offsets: &[Option<u32>]) -> Result<glyf, DeserializationError> {
-    from_rc(&mut ReaderContext::new(c.to_vec()), loca_offsets)
+/// loca_offsets must be obtained from the `loca` table. If a glyph cannot be
+/// parsed, the error reports its glyph ID and offset. The buffer is read in
+/// place, without being copied.
+pub fn from_bytes(c: &[u8], loca_offsets: &[Option<u32>]) -> Result<glyf, GlyfError> {
+    from_rc(&mut ReaderContext::from_slice(c), loca_offsets)
 }
 
 /// Deserialize the glyf table from a `ReaderContext` object.
 ///
-/// loca_offsets must be obtained from the `loca` table.
-pub fn from_rc(
-    c: &mut ReaderContext,
-    loca_offsets: &[Option<u32>],
-) -> Result<glyf, DeserializationError> {
+/// loca_offsets must be obtained from the `loca` table. If a glyph cannot be
+/// parsed, the error reports its glyph ID and offset. Offsets which decrease
+/// from one glyph to the next, or which point past the end of the table, are
+/// also reported as errors. Each glyph is parsed from the data between its
+/// offset and the next glyph's, so it cannot read into the following glyph.
+pub fn from_rc(c: &mut ReaderContext, loca_offsets: &[Option<u32>]) -> Result<glyf, GlyfError> {
+    let mut previous = 0;
+    for (gid, item) in loca_offsets.iter().enumerate() {
+        if let Some(item) = item {
+            let problem = if *item < previous {
+                Some(format!("offset is before previous glyph's at {}", previous))
+            } else if *item as usize >= c.input.len() {
+                Some(format!(
+                    "offset is past the end of the table ({} bytes)",
+                    c.input.len()
+                ))
+            } else {
+                None
+            };
+            if let Some(problem) = problem {
+                return Err(GlyfError {
+                    glyph_id: gid as u16,
+                    offset: *item,
+                    source: DeserializationError(problem),
+                });
+            }
+            previous = *item;
+        }
+    }
     let mut res = glyf { glyphs: Vec::new() };
-    for item in loca_offsets {
+    for (gid, item) in loca_offsets.iter().enumerate() {
         match item {
-            None => res.glyphs.push(Glyph {
-                contours: vec![],
-                components: vec![],
-                overlap: false,
-                xMax: 0,
-                xMin: 0,
-                yMax: 0,
-                yMin: 0,
-                instructions: vec![],
-            }),
+            None => res.glyphs.push(Glyph::empty()),
             Some(item) => {
-                let old = c.ptr;
-                c.ptr = *item as usize;
-                let glyph: Glyph = c.de()?;
+                // Each glyph is read from its own span, up to the next glyph
+                let end = loca_offsets[gid + 1..]
+                    .iter()
+                    .flatten()
+                    .next()
+                    .map_or(c.input.len(), |&x| x as usize);
+                let mut glyph_rc = ReaderContext::from_slice(&c.input[*item as usize..end]);
+                let glyph: Glyph = glyph_rc.de().map_err(|source| GlyfError {
+                    glyph_id: gid as u16,
+                    offset: *item,
+                    source,
+                })?;
                 res.glyphs.push(glyph);
-                c.ptr = old;
             }
         }
     }
     Ok(res)
 }
 
+/// Computes the bounds of a glyph's own contour points, ignoring components.
+fn simple_bounds(g: &Glyph) -> kurbo::Rect {
+    let (x_pts, y_pts): (Vec<i16>, Vec<i16>) =
+        g.contours.iter().flatten().map(|pt| (pt.x, pt.y)).unzip();
+    kurbo::Rect::new(
+        (*x_pts.iter().min().unwrap_or(&0)).into(),
+        (*y_pts.iter().min().unwrap_or(&0)).into(),
+        (*x_pts.iter().max().unwrap_or(&0)).into(),
+        (*y_pts.iter().max().unwrap_or(&0)).into(),
+    )
+}
+
+/// The default limit on how deeply components may be nested when they are
+/// flattened or decomposed.
+pub const MAX_COMPONENT_DEPTH: u32 = 64;
+
 impl glyf {
     /// Given a `Glyph` object, return all components used by this glyph,
     /// including recursively descending into nested components and positioning
     /// them accordingly.
-    pub fn flat_components(&self, g: &Glyph) -> Vec<Component> {
-        self._flat_components(g, 0)
+    ///
+    /// Components may be nested up to [`MAX_COMPONENT_DEPTH`] levels deep;
+    /// deeper nesting (usually a loop of components) is an error, as is a
+    /// component referring to a glyph which does not exist.
+    ///
+    /// Note that this used to return a plain `Vec<Component>`, cut short
+    /// (with a warning) at the nesting limit and panicking on a missing
+    /// glyph; callers now need to handle the `Result`.
+    pub fn flat_components(&self, g: &Glyph) -> Result<Vec<Component>, String> {
+        self.flat_components_with_max_depth(g, MAX_COMPONENT_DEPTH)
+    }
+
+    /// Flattens a glyph's components as [`glyf::flat_components`] does, with
+    /// a limit of `max_depth` levels of nesting. A glyph whose components
+    /// refer to simple glyphs has a depth of one.
+    pub fn flat_components_with_max_depth(
+        &self,
+        g: &Glyph,
+        max_depth: u32,
+    ) -> Result<Vec<Component>, String> {
+        self._flat_components(g, 1, max_depth)
     }
-    fn _flat_components(&self, g: &Glyph, depth: u32) -> Vec<Component> {
+
+    fn _flat_components(
+        &self,
+        g: &Glyph,
+        depth: u32,
+        max_depth: u32,
+    ) -> Result<Vec<Component>, String> {
         let mut new_components = vec![];
-        if depth > 64 {
-            log::warn!(
-                "Extremely deeply nested component in glyph {:?}. Possible loop?",
-                g
-            );
-            return new_components;
+        if depth > max_depth {
+            return Err(format!(
+                "Components nested more than {} deep in glyph ({}). Possible loop?",
+                max_depth,
+                g.summary()
+            ));
         }
         for comp in &g.components {
-            let component_glyph = &self.glyphs[comp.glyph_index as usize];
+            let component_glyph = self
+                .glyphs
+                .get(comp.glyph_index as usize)
+                .ok_or_else(|| format!("Component glyph {} not found", comp.glyph_index))?;
             if component_glyph.has_components() {
-                let mut flattened = self._flat_components(component_glyph, depth + 1);
+                let mut flattened = self._flat_components(component_glyph, depth + 1, max_depth)?;
                 for f in flattened.iter_mut() {
                     f.transformation = comp.transformation * f.transformation;
                     // This may be the wrong way around...
@@ -96,7 +201,171 @@ impl glyf {
                 new_components.push(comp.clone());
             }
         }
-        new_components
+        Ok(new_components)
+    }
+
+    /// Decomposes the glyph with ID `gid` into a simple glyph, descending
+    /// into nested components.
+    ///
+    /// Components may be nested up to [`MAX_COMPONENT_DEPTH`] levels deep.
+    /// The glyph's bounds are not recalculated.
+    pub fn decompose_glyph(&self, gid: usize) -> Result<Glyph, String> {
+        self.decompose_glyph_with_max_depth(gid, MAX_COMPONENT_DEPTH)
+    }
+
+    /// Decomposes a glyph as [`glyf::decompose_glyph`] does, with a limit of
+    /// `max_depth` levels of nesting.
+    pub fn decompose_glyph_with_max_depth(
+        &self,
+        gid: usize,
+        max_depth: u32,
+    ) -> Result<Glyph, String> {
+        let glyph = self
+            .glyphs
+            .get(gid)
+            .ok_or_else(|| format!("Glyph {} not found", gid))?;
+        if !glyph.has_components() {
+            return Ok(glyph.clone());
+        }
+        let flat = Glyph {
+            components: self.flat_components_with_max_depth(glyph, max_depth)?,
+            ..glyph.clone()
+        };
+        Ok(flat.decompose(&self.glyphs))
+    }
+
+    /// Computes the effective offsets of components positioned by point matching.
+    ///
+    /// Components may be positioned by aligning a point in the component with
+    /// a point in the parent glyph (`match_points`) rather than by an xy
+    /// offset. For each such component in the glyph with ID `gid`, returns
+    /// a tuple of the component's index within the glyph and the x and y
+    /// translation which brings the two points together. Parent point numbers
+    /// refer to the points of the glyph's own contours followed by the points
+    /// of the preceding components, as positioned.
+    ///
+    /// Components may be nested up to [`MAX_COMPONENT_DEPTH`] levels deep.
+    pub fn resolve_match_points(&self, gid: usize) -> Result<Vec<(usize, i16, i16)>, String> {
+        self.resolve_match_points_with_max_depth(gid, MAX_COMPONENT_DEPTH)
+    }
+
+    /// Resolves point-matched offsets as [`glyf::resolve_match_points`] does,
+    /// with a limit of `max_depth` levels of nesting.
+    pub fn resolve_match_points_with_max_depth(
+        &self,
+        gid: usize,
+        max_depth: u32,
+    ) -> Result<Vec<(usize, i16, i16)>, String> {
+        let mut resolved = vec![];
+        self._positioned_points(gid, 1, max_depth, &mut resolved)?;
+        Ok(resolved)
+    }
+
+    /// Returns every point of the glyph with ID `gid` in the glyph's own
+    /// coordinate space, with all (possibly nested) components positioned.
+    ///
+    /// Points of the glyph's own contours come first, followed by the points
+    /// of each component in turn, as a rasterizer would see them. Component
+    /// transformations are applied, including offsets positioned by matching
+    /// points and offsets which are scaled along with the component (the
+    /// `SCALED_COMPONENT_OFFSET` flag). Coordinates are rounded once all
+    /// transformations have been applied. If the glyph does not exist, or its
+    /// components are nested more than [`MAX_COMPONENT_DEPTH`] levels deep or
+    /// cannot be positioned, a warning is logged and no points are returned.
+    pub fn rendered_points(&self, gid: usize) -> Vec<Point> {
+        self.rendered_points_with_max_depth(gid, MAX_COMPONENT_DEPTH)
+    }
+
+    /// Returns the positioned points of a glyph as [`glyf::rendered_points`]
+    /// does, with a limit of `max_depth` levels of nesting.
+    pub fn rendered_points_with_max_depth(&self, gid: usize, max_depth: u32) -> Vec<Point> {
+        match self._positioned_points(gid, 1, max_depth, &mut vec![]) {
+            Ok(points) => points
+                .into_iter()
+                .map(|(pt, on_curve)| Point {
+                    x: ot_round(pt.x) as i16,
+                    y: ot_round(pt.y) as i16,
+                    on_curve,
+                })
+                .collect(),
+            Err(e) => {
+                log::warn!("Could not position points of glyph {}: {}", gid, e);
+                vec![]
+            }
+        }
+    }
+
+    /// Returns the points of a glyph with its components positioned, recording
+    /// any offsets resolved from point matching. As in `_flat_components`, the
+    /// glyph's components are at nesting level `depth`, starting from one.
+    fn _positioned_points(
+        &self,
+        gid: usize,
+        depth: u32,
+        max_depth: u32,
+        resolved: &mut Vec<(usize, i16, i16)>,
+    ) -> Result<Vec<(kurbo::Point, bool)>, String> {
+        let glyph = self
+            .glyphs
+            .get(gid)
+            .ok_or_else(|| format!("Glyph {} not found", gid))?;
+        if glyph.has_components() && depth > max_depth {
+            return Err(format!(
+                "Components nested more than {} deep in glyph {}. Possible loop?",
+                max_depth, gid
+            ));
+        }
+        let mut points: Vec<(kurbo::Point, bool)> = glyph
+            .contours
+            .iter()
+            .flatten()
+            .map(|pt| (kurbo::Point::new(pt.x as f64, pt.y as f64), pt.on_curve))
+            .collect();
+        for (ix, comp) in glyph.components.iter().enumerate() {
+            let component_points = self._positioned_points(
+                comp.glyph_index as usize,
+                depth + 1,
+                max_depth,
+                &mut vec![],
+            )?;
+            let mut transformation = comp.transformation;
+            if comp.flags.contains(ComponentFlags::SCALED_COMPONENT_OFFSET)
+                && !comp
+                    .flags
+                    .contains(ComponentFlags::UNSCALED_COMPONENT_OFFSET)
+            {
+                let [xx, yx, xy, yy, dx, dy] = transformation.as_coeffs();
+                let linear = kurbo::Affine::new([xx, yx, xy, yy, 0.0, 0.0]);
+                let offset = linear * kurbo::Point::new(dx, dy);
+                transformation = kurbo::Affine::translate(offset.to_vec2()) * linear;
+            }
+            if let Some((parent_ix, child_ix)) = comp.match_points {
+                let parent_pt = points.get(parent_ix as usize).ok_or_else(|| {
+                    format!(
+                        "Point {} not found in glyph {} when positioning component {}",
+                        parent_ix, gid, ix
+                    )
+                })?;
+                let child_pt = component_points.get(child_ix as usize).ok_or_else(|| {
+                    format!(
+                        "Point {} not found in component glyph {}",
+                        child_ix, comp.glyph_index
+                    )
+                })?;
+                let [xx, yx, xy, yy, _, _] = transformation.as_coeffs();
+                let linear = kurbo::Affine::new([xx, yx, xy, yy, 0.0, 0.0]);
+                let offset = parent_pt.0 - linear * child_pt.0;
+                let (dx, dy) = (offset.x.round(), offset.y.round());
+                resolved.push((ix, dx as i16, dy as i16));
+                transformation = kurbo::Affine::translate((dx, dy)) * linear;
+            }
+            points.extend(
+                component_points
+                    .into_iter()
+                    .map(|(pt, on_curve)| (transformation * pt, on_curve)),
+            );
+        }
+        Ok(points)
     }
 
     /// Flattens all components in this table, replacing nested components with
@@ -107,15 +376,123 @@ impl glyf {
             if !g.has_components() {
                 continue;
             }
-            let flat = self.flat_components(g);
-            if g.components != flat {
-                needs_flattening.push((id, flat));
+            match self.flat_components(g) {
+                Ok(flat) if g.components != flat => needs_flattening.push((id, flat)),
+                Ok(_) => {}
+                Err(e) => log::warn!("Could not flatten glyph {}: {}", id, e),
             }
         }
         for (id, comp) in needs_flattening {
             self.glyphs[id].components = comp;
         }
     }
+    /// Decomposes the glyph with ID `gid` if any of its components is scaled,
+    /// flipped or otherwise transformed beyond a translation.
+    ///
+    /// Some rasterizers render transformed components poorly, while purely
+    /// translated components are harmless and save space, so glyphs using only
+    /// translations are left as they are. A glyph in the glyf table cannot
+    /// mix contours and components, so if any component needs decomposing,
+    /// the translated components are decomposed along with it. The 2x2 part
+    /// is compared as it will be stored (see [`Component::quantized_transform`]).
+    pub fn decompose_transformed(&mut self, gid: usize) {
+        let glyph = match self.glyphs.get(gid) {
+            Some(glyph) => glyph,
+            None => {
+                log::warn!("Glyph {} not found", gid);
+                return;
+            }
+        };
+        let is_transformed = |comp: &Component| {
+            let [xx, yx, xy, yy, _, _] = comp.quantized_transform().as_coeffs();
+            [xx, yx, xy, yy] != [1.0, 0.0, 0.0, 1.0]
+        };
+        if !glyph.components.iter().any(is_transformed) {
+            return;
+        }
+        let mut decomposed = match self.decompose_glyph(gid) {
+            Ok(decomposed) => decomposed,
+            Err(e) => {
+                log::warn!("Could not decompose glyph {}: {}", gid, e);
+                return;
+            }
+        };
+        decomposed.set_bounds_rect(simple_bounds(&decomposed));
+        self.glyphs[gid] = decomposed;
+    }
+
+    /// Applies an affine transformation to every glyph in the table, for
+    /// example to slant a font for a synthetic oblique.
+    ///
+    /// The points of simple glyphs are transformed directly. As the glyphs
+    /// used by a composite have already been transformed, each component's
+    /// own transformation `c` is replaced by `t * c * t⁻¹`, so that it is not
+    /// applied twice; for a plain offset, this just moves the offset by the
+    /// linear part of `t`. Components positioned by matching points are left
+    /// alone. Instructions are removed from all glyphs, since they are no
+    /// longer valid, and bounds are then recalculated without flattening
+    /// components (see [`glyf::recalc_bounds_preserving_components`]).
+    /// Transformed coordinates are truncated towards zero, as with
+    /// [`Point::transform`].
+    pub fn transform_all(&mut self, t: kurbo::Affine) {
+        self.transform_all_by(t, |pt| pt.transform(t))
+    }
+
+    /// Applies an affine transformation to every glyph in the table,
+    /// rounding transformed coordinates with the given strategy.
+    ///
+    /// This is otherwise the same as [`glyf::transform_all`].
+    pub fn transform_all_with_rounding(&mut self, t: kurbo::Affine, rounding: RoundMode) {
+        self.transform_all_by(t, |pt| pt.transform_with_rounding(t, rounding))
+    }
+
+    fn transform_all_by(&mut self, t: kurbo::Affine, transform_point: impl Fn(&Point) -> Point) {
+        let inverse = if t.determinant() != 0.0 {
+            Some(t.inverse())
+        } else {
+            log::warn!("Transformation is not invertible; leaving components unchanged");
+            None
+        };
+        for g in self.glyphs.iter_mut() {
+            for contour in g.contours.iter_mut() {
+                for pt in contour.iter_mut() {
+                    *pt = transform_point(pt);
+                }
+            }
+            if let Some(inverse) = inverse {
+                for comp in g.components.iter_mut() {
+                    if comp.match_points.is_none() {
+                        comp.transformation = t * comp.transformation * inverse;
+                    }
+                }
+            }
+        }
+        self.strip_all_instructions();
+        self.recalc_bounds_preserving_components();
+    }
+
+    /// Determines whether the outline of the glyph with ID `gid` overlaps
+    /// itself, decomposing any components first.
+    ///
+    /// See [`Glyph::detect_overlap`] for how overlaps are found. Returns false
+    /// if the glyph does not exist or cannot be decomposed.
+    pub fn detect_overlap(&self, gid: usize) -> bool {
+        self.decompose_glyph(gid)
+            .map(|glyph| glyph.detect_overlap())
+            .unwrap_or(false)
+    }
+
+    /// Approximates the outline of the glyph with ID `gid` by closed
+    /// polygons, decomposing any components first.
+    ///
+    /// See [`Glyph::flatten_to_polygons`]. Returns no polygons if the glyph
+    /// does not exist or cannot be decomposed.
+    pub fn flatten_to_polygons(&self, gid: usize, tolerance: f64) -> Vec<Vec<(f64, f64)>> {
+        self.decompose_glyph(gid)
+            .map(|glyph| glyph.flatten_to_polygons(tolerance))
+            .unwrap_or_default()
+    }
+
     /// Recalculate the bounds of all glyphs within the table.
     /// *Note* that this flattens nested components.
     pub fn recalc_bounds(&mut self) {
@@ -123,12 +500,8 @@ impl glyf {
         // First do simple glyphs
         for g in self.glyphs.iter_mut() {
             if !g.has_components() {
-                let (x_pts, y_pts): (Vec<i16>, Vec<i16>) =
-                    g.contours.iter().flatten().map(|pt| (pt.x, pt.y)).unzip();
-                g.xMin = *x_pts.iter().min().unwrap_or(&0);
-                g.xMax = *x_pts.iter().max().unwrap_or(&0);
-                g.yMin = *y_pts.iter().min().unwrap_or(&0);
-                g.yMax = *y_pts.iter().max().unwrap_or(&0);
+                let bounds = simple_bounds(g);
+                g.set_bounds_rect(bounds);
             }
         }
 
@@ -166,54 +539,575 @@ impl glyf {
         }
     }
 
-    /// Returns a maxp version 1.0 table reflecting the statistics in this glyf table
-    pub fn as_maxp10(&self) -> maxp {
-        let num_glyphs = self.glyphs.len() as u16;
-        let max_points = self
-            .glyphs
-            .iter()
-            .map(|x| x.contours.iter().map(|c| c.len()).sum())
-            .max()
-            .unwrap_or(0) as u16;
+    /// Recalculate the bounds of the glyph with ID `gid` only.
+    ///
+    /// This gives the glyph the same bounds as [`glyf::recalc_bounds`] would,
+    /// but only this glyph's nested components are flattened, and no other
+    /// glyph is changed. It is much cheaper than recalculating the whole table
+    /// after editing a single glyph; note that composites which use the glyph
+    /// will need their bounds recalculated too.
+    pub fn recalc_bounds_for(&mut self, gid: usize) {
+        let glyph = match self.glyphs.get(gid) {
+            Some(glyph) => glyph,
+            None => {
+                log::warn!("Glyph {} not found", gid);
+                return;
+            }
+        };
+        if glyph.has_components() {
+            match self.flat_components(glyph) {
+                Ok(flat) => self.glyphs[gid].components = flat,
+                Err(e) => {
+                    log::warn!("Could not flatten glyph {}: {}", gid, e);
+                    return;
+                }
+            }
+        }
+        match self.recalculated_bounds(gid) {
+            Ok(bounds) => self.glyphs[gid].set_bounds_rect(bounds),
+            Err(e) => log::warn!("Could not find the bounds of glyph {}: {}", gid, e),
+        }
+    }
 
-        let max_contours = self
-            .glyphs
-            .iter()
-            .map(|x| x.contours.len())
-            .max()
-            .unwrap_or(0) as u16;
-        let max_component_info: CompositeMaxpValues = self
+    /// Computes the bounds of the glyph with ID `gid` without modifying the table.
+    ///
+    /// Components are descended into and their transformations applied, as
+    /// in `recalc_bounds`; however, the stored bounds are left untouched and
+    /// nested components are not flattened. Every component counts towards
+    /// the bounds, including when one of them has `USE_MY_METRICS` set
+    /// (which `recalc_bounds` takes the bounds from alone). If the glyph does
+    /// not exist or its components cannot be flattened, a warning is logged
+    /// and the bounds are empty.
+    pub fn glyph_bounds(&self, gid: usize) -> kurbo::Rect {
+        self.try_glyph_bounds(gid).unwrap_or_else(|e| {
+            log::warn!("Could not find the bounds of glyph {}: {}", gid, e);
+            kurbo::Rect::ZERO
+        })
+    }
+
+    fn try_glyph_bounds(&self, gid: usize) -> Result<kurbo::Rect, String> {
+        let g = self
             .glyphs
+            .get(gid)
+            .ok_or_else(|| format!("Glyph {} not found", gid))?;
+        if !g.has_components() {
+            return Ok(simple_bounds(g));
+        }
+        Ok(self
+            .flat_components(g)?
             .iter()
-            .filter(|x| x.has_components())
-            .flat_map(|x| x.composite_maxp_values(&self.glyphs))
-            .fold(CompositeMaxpValues::default(), |l, r| CompositeMaxpValues {
-                num_points: max(l.num_points, r.num_points),
-                num_contours: max(l.num_contours, r.num_contours),
-                max_depth: max(l.max_depth, r.max_depth),
-            });
-        let max_component_elements = self
+            .map(|comp| {
+                let component_bounds = simple_bounds(&self.glyphs[comp.glyph_index as usize]);
+                comp.transformation.transform_rect_bbox(component_bounds)
+            })
+            .reduce(|a, b| a.union(b))
+            .unwrap_or(kurbo::Rect::ZERO))
+    }
+
+    /// The bounds which `recalc_bounds` gives the glyph with ID `gid`: those
+    /// of the glyph used by its `USE_MY_METRICS` component if it has one, and
+    /// otherwise those of its whole outline.
+    fn recalculated_bounds(&self, gid: usize) -> Result<kurbo::Rect, String> {
+        let g = self
             .glyphs
-            .iter()
-            .map(|x| x.components.len())
-            .max()
-            .unwrap_or(0) as u16;
+            .get(gid)
+            .ok_or_else(|| format!("Glyph {} not found", gid))?;
+        if g.has_components() {
+            if let Some(comp) = self
+                .flat_components(g)?
+                .iter()
+                .find(|comp| comp.flags.contains(ComponentFlags::USE_MY_METRICS))
+            {
+                return Ok(simple_bounds(&self.glyphs[comp.glyph_index as usize]));
+            }
+        }
+        self.try_glyph_bounds(gid)
+    }
+
+    /// Recalculate the bounds of all glyphs within the table, leaving their
+    /// components as they are.
+    ///
+    /// This sets the same bounds as [`glyf::recalc_bounds`], but each glyph's
+    /// components are descended into as by [`glyf::glyph_bounds`] rather than
+    /// flattened first. Glyphs whose components cannot be
+    /// flattened keep their stored bounds, and a warning is logged.
+    pub fn recalc_bounds_preserving_components(&mut self) {
+        let bounds: Vec<Option<kurbo::Rect>> = (0..self.glyphs.len())
+            .map(|gid| {
+                self.recalculated_bounds(gid)
+                    .map_err(|e| log::warn!("Could not find the bounds of glyph {}: {}", gid, e))
+                    .ok()
+            })
+            .collect();
+        for (g, bounds) in self.glyphs.iter_mut().zip(bounds) {
+            if let Some(bounds) = bounds {
+                g.set_bounds_rect(bounds);
+            }
+        }
+    }
+
+    /// Finds glyphs with identical binary representations.
+    ///
+    /// Returns a map from the ID of each duplicate glyph to the lowest ID of
+    /// a glyph identical to it, so that the caller can collapse duplicates in
+    /// `cmap` and other tables. Components within this table which refer to
+    /// a duplicate are redirected to the lowest ID, which may in turn make
+    /// further composite glyphs identical; this is repeated until no new
+    /// duplicates are found. Empty glyphs are not considered duplicates of
+    /// each other. The glyphs themselves are not removed.
+    pub fn dedupe(&mut self) -> HashMap<u16, u16> {
+        let mut remap: HashMap<u16, u16> = HashMap::new();
+        loop {
+            let mut seen: HashMap<Vec<u8>, u16> = HashMap::new();
+            let mut found = false;
+            for (gid, glyph) in self.glyphs.iter().enumerate() {
+                let gid = gid as u16;
+                if glyph.is_empty() || remap.contains_key(&gid) {
+                    continue;
+                }
+                let bytes = match otspec::ser::to_bytes(glyph) {
+                    Ok(bytes) => bytes,
+                    Err(_) => continue,
+                };
+                match seen.get(&bytes) {
+                    Some(&original) => {
+                        remap.insert(gid, original);
+                        found = true;
+                    }
+                    None => {
+                        seen.insert(bytes, gid);
+                    }
+                }
+            }
+            if !found {
+                break;
+            }
+            for glyph in self.glyphs.iter_mut() {
+                for comp in glyph.components.iter_mut() {
+                    if let Some(&original) = remap.get(&comp.glyph_index) {
+                        comp.glyph_index = original;
+                    }
+                }
+            }
+        }
+        remap
+    }
+
+    /// Expands a set of glyph IDs to include all glyphs used as components.
+    ///
+    /// Component references are followed recursively, so a composite glyph
+    /// which uses another composite glyph pulls in that glyph's components
+    /// too. Each glyph is only visited once, so reference loops terminate.
+    /// References to glyphs which are not in the table are ignored.
+    pub fn closure(&self, keep: &HashSet<u16>) -> HashSet<u16> {
+        let mut closed = keep.clone();
+        let mut todo: Vec<u16> = keep.iter().copied().collect();
+        while let Some(gid) = todo.pop() {
+            let glyph = match self.glyphs.get(gid as usize) {
+                Some(glyph) => glyph,
+                None => {
+                    log::warn!("Glyph {} not found while computing closure", gid);
+                    continue;
+                }
+            };
+            for comp in &glyph.components {
+                if closed.insert(comp.glyph_index) {
+                    todo.push(comp.glyph_index);
+                }
+            }
+        }
+        closed
+    }
+
+    /// Counts how many times each glyph is used as a component.
+    ///
+    /// Every component reference counts, so a glyph used twice in one
+    /// composite is counted twice. Glyphs which are never used as components
+    /// are not included. References made through nested composites are not
+    /// counted; use [`glyf::nested_component_usage`] for that.
+    pub fn component_usage(&self) -> HashMap<u16, u32> {
+        let mut usage = HashMap::new();
+        for comp in self.glyphs.iter().flat_map(|g| &g.components) {
+            *usage.entry(comp.glyph_index).or_insert(0) += 1;
+        }
+        usage
+    }
+
+    /// Counts how many times each glyph is used as a component, including
+    /// references made through nested composites.
+    ///
+    /// If one composite uses another which uses `A`, then `A` is counted once
+    /// for each of them. Components nested more than [`MAX_COMPONENT_DEPTH`]
+    /// levels deep (usually a loop of components) are an error, rather than
+    /// giving partial counts.
+    pub fn nested_component_usage(&self) -> Result<HashMap<u16, u32>, String> {
+        self.nested_component_usage_with_max_depth(MAX_COMPONENT_DEPTH)
+    }
+
+    /// Counts component usage as [`glyf::nested_component_usage`] does, with
+    /// a limit of `max_depth` levels of nesting.
+    pub fn nested_component_usage_with_max_depth(
+        &self,
+        max_depth: u32,
+    ) -> Result<HashMap<u16, u32>, String> {
+        let mut usage = HashMap::new();
+        for (gid, glyph) in self.glyphs.iter().enumerate() {
+            let mut todo: Vec<(&Glyph, u32)> = vec![(glyph, 1)];
+            while let Some((g, depth)) = todo.pop() {
+                if depth > max_depth {
+                    return Err(format!(
+                        "Components nested more than {} deep in glyph {}. Possible loop?",
+                        max_depth, gid
+                    ));
+                }
+                for comp in &g.components {
+                    *usage.entry(comp.glyph_index).or_insert(0) += 1;
+                    if let Some(component_glyph) = self.glyphs.get(comp.glyph_index as usize) {
+                        if component_glyph.has_components() {
+                            todo.push((component_glyph, depth + 1));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(usage)
+    }
+
+    /// Creates a new table containing only the given glyphs, in the given order.
+    ///
+    /// Returns the new table, the `loca` offsets for it (one more than the
+    /// number of glyphs, with each glyph padded to a four-byte boundary as
+    /// when the font is compiled), and a map from old glyph IDs to new ones.
+    /// Component references are rewritten through the map. Every component of
+    /// a kept glyph must itself be kept; use [`glyf::closure`] to ensure this.
+    #[allow(clippy::type_complexity)]
+    pub fn subset(
+        &self,
+        keep_order: &[u16],
+    ) -> Result<(glyf, Vec<u32>, HashMap<u16, u16>), String> {
+        let mut remap: HashMap<u16, u16> = HashMap::new();
+        for (new_gid, &old_gid) in keep_order.iter().enumerate() {
+            if old_gid as usize >= self.glyphs.len() {
+                return Err(format!("Glyph {} not found", old_gid));
+            }
+            let new_gid = u16::try_from(new_gid).map_err(|_| "Too many glyphs".to_string())?;
+            if remap.insert(old_gid, new_gid).is_some() {
+                return Err(format!("Glyph {} kept more than once", old_gid));
+            }
+        }
+
+        let mut glyphs = Vec::with_capacity(keep_order.len());
+        let mut offsets = Vec::with_capacity(keep_order.len() + 1);
+        let mut offset: u32 = 0;
+        for &old_gid in keep_order {
+            let mut glyph = self.glyphs[old_gid as usize].clone();
+            for comp in glyph.components.iter_mut() {
+                comp.glyph_index = *remap.get(&comp.glyph_index).ok_or_else(|| {
+                    format!(
+                        "Glyph {} uses component {} which is not kept",
+                        old_gid, comp.glyph_index
+                    )
+                })?;
+            }
+            offsets.push(offset);
+            if !glyph.has_no_data() {
+                let len = otspec::ser::to_bytes(&glyph)
+                    .map_err(|e| e.to_string())?
+                    .len();
+                offset += ((len + 3) & !3) as u32;
+            }
+            glyphs.push(glyph);
+        }
+        offsets.push(offset);
+        Ok((glyf { glyphs }, offsets, remap))
+    }
+
+    /// Removes TrueType instructions from all glyphs in this table.
+    ///
+    /// Outlines are left unchanged.
+    pub fn strip_all_instructions(&mut self) {
+        for g in self.glyphs.iter_mut() {
+            g.strip_instructions();
+        }
+    }
+
+    /// Reports how many bytes this table will use when serialized, without
+    /// serializing it.
+    pub fn size_report(&self) -> SizeReport {
+        let mut report = SizeReport::default();
+        for g in self.glyphs.iter().filter(|g| !g.has_no_data()) {
+            let len = (g.ot_binary_size() + 3) & !3;
+            if g.has_components() {
+                report.composite += len;
+            } else {
+                report.simple += len;
+            }
+            report.instructions += g.instructions.len();
+        }
+        // An empty table is written as a single zero byte
+        report.total = (report.simple + report.composite).max(1);
+        report
+    }
+
+    /// Serializes this table to binary, writing it to `writer` one glyph at a
+    /// time, and returns the `loca` offsets of the glyphs.
+    ///
+    /// Each glyph is padded to a multiple of four bytes, and glyphs without
+    /// data take up no space. There is one more offset than there are
+    /// glyphs, giving the end of the last glyph; an empty table is written as
+    /// a single zero byte, as in a compiled font. Only one glyph's binary form
+    /// is held in memory at once, so this is suitable for writing large
+    /// tables straight to a file.
+    pub fn write_to(&self, writer: impl std::io::Write) -> std::io::Result<Vec<u32>> {
+        self.write_glyphs_to(writer, |_, g, data| {
+            g.to_bytes(data)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+            data.resize((data.len() + 3) & !3, 0);
+            Ok(())
+        })
+    }
+
+    /// Writes this table to `writer` as [`glyf::write_to`] does, but with each
+    /// glyph which has data encoded (and padded) into the buffer by `encode`,
+    /// which is given the glyph's ID.
+    pub(crate) fn write_glyphs_to(
+        &self,
+        mut writer: impl std::io::Write,
+        mut encode: impl FnMut(usize, &Glyph, &mut Vec<u8>) -> std::io::Result<()>,
+    ) -> std::io::Result<Vec<u32>> {
+        let mut offsets = Vec::with_capacity(self.glyphs.len() + 1);
+        let mut offset: u32 = 0;
+        let mut data = vec![];
+        for (gid, g) in self.glyphs.iter().enumerate() {
+            offsets.push(offset);
+            if g.has_no_data() {
+                continue;
+            }
+            data.clear();
+            encode(gid, g, &mut data)?;
+            writer.write_all(&data)?;
+            offset = u32::try_from(data.len())
+                .ok()
+                .and_then(|len| offset.checked_add(len))
+                .ok_or_else(|| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        "glyf table is too large for 32-bit offsets",
+                    )
+                })?;
+        }
+        if offset == 0 {
+            writer.write_all(&[0])?;
+            offset = 1;
+        }
+        offsets.push(offset);
+        Ok(offsets)
+    }
+
+    /// Computes the outline statistics which the `maxp` table records for
+    /// this table.
+    ///
+    /// Composite glyphs are measured after resolving their components
+    /// recursively, so their point and contour totals include those of
+    /// nested composites, and their depth is the true depth of nesting (a
+    /// composite of simple glyphs has a depth of one). Components nested more
+    /// than [`MAX_COMPONENT_DEPTH`] levels deep, or which refer to missing
+    /// glyphs, are not counted. Totals which do not fit in 16 bits saturate.
+    pub fn build_maxp(&self) -> MaxpStats {
+        let clamp = |x: usize| x.min(u16::MAX as usize) as u16;
+        let mut stats = MaxpStats {
+            num_glyphs: clamp(self.glyphs.len()),
+            ..MaxpStats::default()
+        };
+        for glyph in &self.glyphs {
+            stats.max_contours = max(stats.max_contours, clamp(glyph.contours.len()));
+            stats.max_component_elements =
+                max(stats.max_component_elements, clamp(glyph.components.len()));
+            stats.max_size_of_instructions = max(
+                stats.max_size_of_instructions,
+                clamp(glyph.instructions.len()),
+            );
+            if !glyph.has_components() {
+                stats.max_points = max(stats.max_points, clamp(glyph.num_points(false)));
+            } else if let Some(info) = glyph.composite_maxp_values(&self.glyphs) {
+                stats.max_composite_points = max(stats.max_composite_points, info.num_points);
+                stats.max_composite_contours = max(stats.max_composite_contours, info.num_contours);
+                stats.max_component_depth = max(stats.max_component_depth, info.max_depth);
+            }
+        }
+        stats
+    }
+
+    /// Returns a maxp version 1.0 table reflecting the statistics in this glyf table
+    pub fn as_maxp10(&self) -> maxp {
+        let stats = self.build_maxp();
         maxp::new10(
-            num_glyphs,
-            max_points,
-            max_contours,
-            max_component_info.num_points,
-            max_component_info.num_contours,
-            max_component_elements,
-            max_component_info.max_depth,
+            stats.num_glyphs,
+            stats.max_points,
+            stats.max_contours,
+            stats.max_composite_points,
+            stats.max_composite_contours,
+            stats.max_component_elements,
+            stats.max_component_depth,
         )
     }
 }
 
+/// The outline statistics of a `glyf` table which are recorded in the `maxp`
+/// table, as computed by [`glyf::build_maxp`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct MaxpStats {
+    /// The number of glyphs.
+    pub num_glyphs: u16,
+    /// The largest number of points in a simple glyph.
+    pub max_points: u16,
+    /// The largest number of contours in a simple glyph.
+    pub max_contours: u16,
+    /// The largest number of points in a composite glyph, once all its
+    /// components are resolved.
+    pub max_composite_points: u16,
+    /// The largest number of contours in a composite glyph, once all its
+    /// components are resolved.
+    pub max_composite_contours: u16,
+    /// The largest number of components referenced directly by a glyph.
+    pub max_component_elements: u16,
+    /// The deepest nesting of components.
+    pub max_component_depth: u16,
+    /// The largest number of bytes of instructions in a glyph.
+    pub max_size_of_instructions: u16,
+}
+
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use crate::font;
-    use crate::tables::glyf::{Component, ComponentFlags, Glyph, Point};
+    use crate::tables::glyf::{glyf, Component, ComponentFlags, Glyph, GlyphBuilder, Point};
+    use otspec::Serialize;
+    use std::collections::{HashMap, HashSet};
+
+    /// A small TrueType font with simple and composite glyphs, used by tests
+    /// throughout the crate.
+    pub(crate) const SAMPLE_FONT: &[u8] = &[
+        0x00, 0x01, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x80, 0x00, 0x03, 0x00, 0x20, 0x4f, 0x53, 0x2f,
+        0x32, 0x47, 0x36, 0x45, 0x90, 0x00, 0x00, 0x01, 0x28, 0x00, 0x00, 0x00, 0x60, 0x63, 0x6d,
+        0x61, 0x70, 0x01, 0x5c, 0x04, 0x51, 0x00, 0x00, 0x01, 0xa8, 0x00, 0x00, 0x00, 0x64, 0x67,
+        0x6c, 0x79, 0x66, 0x01, 0x73, 0xbf, 0xf8, 0x00, 0x00, 0x02, 0x20, 0x00, 0x00, 0x02, 0x1e,
+        0x68, 0x65, 0x61, 0x64, 0x1a, 0x46, 0x65, 0x4f, 0x00, 0x00, 0x00, 0xac, 0x00, 0x00, 0x00,
+        0x36, 0x68, 0x68, 0x65, 0x61, 0x05, 0x85, 0x01, 0xc2, 0x00, 0x00, 0x00, 0xe4, 0x00, 0x00,
+        0x00, 0x24, 0x68, 0x6d, 0x74, 0x78, 0x10, 0xf6, 0xff, 0xda, 0x00, 0x00, 0x01, 0x88, 0x00,
+        0x00, 0x00, 0x20, 0x6c, 0x6f, 0x63, 0x61, 0x02, 0x55, 0x01, 0xd6, 0x00, 0x00, 0x02, 0x0c,
+        0x00, 0x00, 0x00, 0x12, 0x6d, 0x61, 0x78, 0x70, 0x00, 0x12, 0x00, 0x47, 0x00, 0x00, 0x01,
+        0x08, 0x00, 0x00, 0x00, 0x20, 0x6e, 0x61, 0x6d, 0x65, 0xff, 0x72, 0x0d, 0x88, 0x00, 0x00,
+        0x04, 0x40, 0x00, 0x00, 0x00, 0xb4, 0x70, 0x6f, 0x73, 0x74, 0x16, 0xf9, 0xc6, 0xb7, 0x00,
+        0x00, 0x04, 0xf4, 0x00, 0x00, 0x00, 0x48, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00,
+        0x7e, 0x62, 0x06, 0x11, 0x5f, 0x0f, 0x3c, 0xf5, 0x00, 0x03, 0x03, 0xe8, 0x00, 0x00, 0x00,
+        0x00, 0xdc, 0x27, 0x59, 0x19, 0x00, 0x00, 0x00, 0x00, 0xdc, 0xa5, 0xc8, 0x08, 0xff, 0x73,
+        0xff, 0xb4, 0x02, 0xef, 0x03, 0x93, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x02, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x03, 0x20, 0xff, 0x38, 0x00, 0x00, 0x02, 0xf4,
+        0xff, 0x73, 0xff, 0x8d, 0x02, 0xef, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x08, 0x00, 0x01, 0x00, 0x00, 0x00, 0x08,
+        0x00, 0x34, 0x00, 0x03, 0x00, 0x10, 0x00, 0x04, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x00, 0x01, 0x00, 0x03, 0x02, 0x1f,
+        0x01, 0x90, 0x00, 0x05, 0x00, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x43, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x3f, 0x3f, 0x3f, 0x3f, 0x00, 0x00,
+        0x00, 0x20, 0x03, 0x01, 0x03, 0x20, 0xff, 0x38, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0xf4, 0x02, 0xbc, 0x00, 0x00, 0x00, 0x20,
+        0x00, 0x00, 0x02, 0xf4, 0x00, 0x05, 0x02, 0xf4, 0x00, 0x05, 0x02, 0x98, 0x00, 0x1e, 0x02,
+        0xf4, 0x00, 0x05, 0x00, 0xc8, 0x00, 0x00, 0x02, 0x58, 0x00, 0x1d, 0x02, 0x58, 0x00, 0x1d,
+        0x00, 0x0a, 0xff, 0x73, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00,
+        0x14, 0x00, 0x03, 0x00, 0x01, 0x00, 0x00, 0x00, 0x14, 0x00, 0x04, 0x00, 0x50, 0x00, 0x00,
+        0x00, 0x10, 0x00, 0x10, 0x00, 0x03, 0x00, 0x00, 0x00, 0x20, 0x00, 0x24, 0x00, 0x41, 0x00,
+        0x4f, 0x00, 0x56, 0x00, 0xc1, 0x03, 0x01, 0xff, 0xff, 0x00, 0x00, 0x00, 0x20, 0x00, 0x24,
+        0x00, 0x41, 0x00, 0x4f, 0x00, 0x56, 0x00, 0xc1, 0x03, 0x01, 0xff, 0xff, 0xff, 0xe4, 0xff,
+        0xe1, 0xff, 0xbf, 0xff, 0xb3, 0xff, 0xad, 0xff, 0x40, 0xfd, 0x06, 0x00, 0x01, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x1e, 0x00, 0x2a, 0x00, 0x51, 0x00, 0x66, 0x00, 0x66, 0x00, 0xb6, 0x01, 0x01,
+        0x01, 0x0f, 0x00, 0x00, 0x00, 0x03, 0x00, 0x05, 0x00, 0x00, 0x02, 0xef, 0x02, 0xbc, 0x00,
+        0x03, 0x00, 0x07, 0x00, 0x0b, 0x00, 0x00, 0x01, 0x01, 0x33, 0x01, 0x23, 0x01, 0x33, 0x01,
+        0x13, 0x35, 0x21, 0x15, 0x01, 0x43, 0x01, 0x3e, 0x6e, 0xfe, 0xc2, 0x6e, 0xfe, 0xc2, 0x6e,
+        0x01, 0x3e, 0x86, 0xfe, 0x61, 0x02, 0xbc, 0xfd, 0x44, 0x02, 0xbc, 0xfd, 0x44, 0x02, 0xbc,
+        0xfe, 0x10, 0x50, 0x50, 0xff, 0xff, 0x00, 0x05, 0x00, 0x00, 0x02, 0xef, 0x03, 0x93, 0x00,
+        0x26, 0x00, 0x00, 0x00, 0x00, 0x00, 0x07, 0x00, 0x07, 0x01, 0x92, 0x00, 0x82, 0x00, 0x02,
+        0x00, 0x1e, 0xff, 0xf6, 0x02, 0x7a, 0x02, 0xc6, 0x00, 0x0b, 0x00, 0x17, 0x00, 0x00, 0x01,
+        0x14, 0x06, 0x23, 0x22, 0x26, 0x35, 0x34, 0x36, 0x33, 0x32, 0x16, 0x05, 0x14, 0x16, 0x33,
+        0x32, 0x36, 0x35, 0x34, 0x26, 0x23, 0x22, 0x06, 0x02, 0x7a, 0x96, 0x98, 0x97, 0x97, 0x97,
+        0x97, 0x98, 0x96, 0xfd, 0xfe, 0x6a, 0x6a, 0x6a, 0x6a, 0x6a, 0x6a, 0x6a, 0x6a, 0x01, 0x5e,
+        0xb5, 0xb3, 0xb3, 0xb5, 0xb5, 0xb3, 0xb3, 0xb5, 0x8a, 0x8a, 0x8a, 0x8a, 0x8a, 0x8a, 0x8a,
+        0x00, 0x00, 0x02, 0x00, 0x05, 0x00, 0x00, 0x02, 0xef, 0x02, 0xbc, 0x00, 0x03, 0x00, 0x07,
+        0x00, 0x00, 0x21, 0x23, 0x01, 0x33, 0x01, 0x23, 0x01, 0x33, 0x01, 0xb1, 0x6e, 0x01, 0x3e,
+        0x6e, 0xfe, 0xc2, 0x6e, 0xfe, 0xc2, 0x6e, 0x02, 0xbc, 0xfd, 0x44, 0x02, 0xbc, 0x00, 0x03,
+        0x00, 0x1d, 0xff, 0xbc, 0x02, 0x44, 0x02, 0xf7, 0x00, 0x23, 0x00, 0x2b, 0x00, 0x33, 0x00,
+        0x00, 0x01, 0x35, 0x33, 0x15, 0x16, 0x16, 0x17, 0x07, 0x26, 0x26, 0x27, 0x15, 0x16, 0x16,
+        0x15, 0x14, 0x06, 0x07, 0x15, 0x23, 0x35, 0x26, 0x26, 0x27, 0x37, 0x16, 0x16, 0x17, 0x35,
+        0x27, 0x26, 0x26, 0x35, 0x34, 0x36, 0x36, 0x17, 0x06, 0x06, 0x15, 0x14, 0x16, 0x16, 0x17,
+        0x17, 0x15, 0x36, 0x36, 0x35, 0x34, 0x26, 0x26, 0x01, 0x08, 0x5a, 0x3d, 0x68, 0x2b, 0x35,
+        0x24, 0x4b, 0x2c, 0x71, 0x71, 0x79, 0x69, 0x5a, 0x48, 0x78, 0x2b, 0x34, 0x2a, 0x54, 0x39,
+        0x0f, 0x65, 0x69, 0x38, 0x64, 0x41, 0x3d, 0x42, 0x17, 0x36, 0x2f, 0x5d, 0x45, 0x3f, 0x18,
+        0x39, 0x02, 0xcb, 0x2c, 0x2c, 0x06, 0x2f, 0x2a, 0x48, 0x24, 0x25, 0x06, 0xfe, 0x16, 0x58,
+        0x4f, 0x52, 0x6e, 0x0a, 0x32, 0x32, 0x06, 0x2e, 0x28, 0x48, 0x24, 0x25, 0x04, 0xe8, 0x03,
+        0x13, 0x61, 0x52, 0x39, 0x5b, 0x39, 0x50, 0x09, 0x41, 0x33, 0x20, 0x2a, 0x1a, 0x0a, 0x6b,
+        0xd8, 0x07, 0x3b, 0x30, 0x1c, 0x27, 0x19, 0x00, 0x00, 0x01, 0x00, 0x1d, 0xff, 0xb4, 0x02,
+        0x44, 0x02, 0xf7, 0x00, 0x32, 0x00, 0x00, 0x01, 0x35, 0x33, 0x15, 0x16, 0x16, 0x17, 0x07,
+        0x26, 0x26, 0x23, 0x22, 0x06, 0x15, 0x14, 0x16, 0x16, 0x17, 0x17, 0x16, 0x16, 0x15, 0x14,
+        0x06, 0x06, 0x07, 0x15, 0x23, 0x35, 0x26, 0x26, 0x27, 0x37, 0x1e, 0x02, 0x33, 0x32, 0x36,
+        0x35, 0x34, 0x26, 0x26, 0x27, 0x27, 0x26, 0x26, 0x35, 0x34, 0x36, 0x36, 0x01, 0x08, 0x5a,
+        0x3d, 0x68, 0x2b, 0x35, 0x2d, 0x5e, 0x3e, 0x52, 0x59, 0x17, 0x36, 0x2f, 0x63, 0x6d, 0x6f,
+        0x37, 0x65, 0x46, 0x5a, 0x48, 0x78, 0x2b, 0x34, 0x22, 0x41, 0x4f, 0x33, 0x5d, 0x53, 0x19,
+        0x3c, 0x35, 0x63, 0x65, 0x69, 0x38, 0x64, 0x02, 0xcb, 0x2c, 0x2c, 0x06, 0x2f, 0x2a, 0x48,
+        0x2c, 0x26, 0x44, 0x3c, 0x20, 0x2a, 0x1a, 0x0a, 0x14, 0x16, 0x57, 0x4f, 0x36, 0x57, 0x36,
+        0x07, 0x3a, 0x3a, 0x06, 0x2e, 0x28, 0x48, 0x1c, 0x23, 0x10, 0x3d, 0x37, 0x1d, 0x27, 0x1a,
+        0x09, 0x12, 0x13, 0x61, 0x52, 0x39, 0x5b, 0x39, 0x00, 0x01, 0xff, 0x73, 0x02, 0x76, 0x00,
+        0x7d, 0x03, 0x11, 0x00, 0x03, 0x00, 0x00, 0x13, 0x07, 0x07, 0x37, 0x7d, 0xf3, 0x17, 0xf3,
+        0x03, 0x11, 0x45, 0x56, 0x45, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x08, 0x00, 0x66, 0x00,
+        0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x0f, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x10, 0x00, 0x0f, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x01,
+        0x00, 0x00, 0x06, 0x00, 0x0f, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x01, 0x01, 0x00, 0x05,
+        0x00, 0x15, 0x00, 0x03, 0x00, 0x01, 0x04, 0x09, 0x00, 0x01, 0x00, 0x1e, 0x00, 0x1a, 0x00,
+        0x03, 0x00, 0x01, 0x04, 0x09, 0x00, 0x10, 0x00, 0x1e, 0x00, 0x1a, 0x00, 0x03, 0x00, 0x01,
+        0x04, 0x09, 0x01, 0x00, 0x00, 0x0c, 0x00, 0x38, 0x00, 0x03, 0x00, 0x01, 0x04, 0x09, 0x01,
+        0x01, 0x00, 0x0a, 0x00, 0x44, 0x53, 0x69, 0x6d, 0x70, 0x6c, 0x65, 0x20, 0x54, 0x77, 0x6f,
+        0x20, 0x41, 0x78, 0x69, 0x73, 0x57, 0x65, 0x69, 0x67, 0x68, 0x74, 0x53, 0x6c, 0x61, 0x6e,
+        0x74, 0x00, 0x53, 0x00, 0x69, 0x00, 0x6d, 0x00, 0x70, 0x00, 0x6c, 0x00, 0x65, 0x00, 0x20,
+        0x00, 0x54, 0x00, 0x77, 0x00, 0x6f, 0x00, 0x20, 0x00, 0x41, 0x00, 0x78, 0x00, 0x69, 0x00,
+        0x73, 0x00, 0x57, 0x00, 0x65, 0x00, 0x69, 0x00, 0x67, 0x00, 0x68, 0x00, 0x74, 0x00, 0x53,
+        0x00, 0x6c, 0x00, 0x61, 0x00, 0x6e, 0x00, 0x74, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x08, 0x00, 0x24, 0x00,
+        0xc9, 0x00, 0x32, 0x00, 0x39, 0x00, 0x03, 0x00, 0x07, 0x01, 0x02, 0x01, 0x03, 0x0b, 0x64,
+        0x6f, 0x6c, 0x6c, 0x61, 0x72, 0x2e, 0x62, 0x6f, 0x6c, 0x64, 0x09, 0x61, 0x63, 0x75, 0x74,
+        0x65, 0x63, 0x6f, 0x6d, 0x62,
+    ];
+
+    /// Parses [`SAMPLE_FONT`].
+    pub(crate) fn sample_font() -> font::Font {
+        otspec::de::from_bytes(SAMPLE_FONT).unwrap()
+    }
+
+    /// Returns the `glyf` table of [`SAMPLE_FONT`].
+    pub(crate) fn sample_glyf() -> glyf {
+        sample_font().tables.glyf().unwrap().unwrap().into_owned()
+    }
+
+    /// A 100 unit square with a corner at the origin.
+    pub(crate) fn square_glyph() -> Glyph {
+        GlyphBuilder::new()
+            .move_to(0, 0)
+            .line_to(100, 0)
+            .line_to(100, 100)
+            .line_to(0, 100)
+            .build()
+    }
+
+    /// A component placing glyph `glyph_index` without transforming it.
+    pub(crate) fn component(glyph_index: u16, flags: ComponentFlags) -> Component {
+        Component {
+            glyph_index,
+            transformation: kurbo::Affine::IDENTITY,
+            match_points: None,
+            flags,
+        }
+    }
 
     #[test]
     fn glyf_de() {
@@ -281,6 +1175,8 @@ mod tests {
             instructions: vec![],
             components: vec![],
             overlap: false,
+            explicit_empty: false,
+            bounds_dirty: false,
         };
         assert_eq!(deserialized, glyph);
         let serialized = otspec::ser::to_bytes(&glyph).unwrap();
@@ -291,105 +1187,7 @@ mod tests {
 
     #[test]
     fn test_glyf_de() {
-        let binary_font = vec![
-            0x00, 0x01, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x80, 0x00, 0x03, 0x00, 0x20, 0x4f, 0x53,
-            0x2f, 0x32, 0x47, 0x36, 0x45, 0x90, 0x00, 0x00, 0x01, 0x28, 0x00, 0x00, 0x00, 0x60,
-            0x63, 0x6d, 0x61, 0x70, 0x01, 0x5c, 0x04, 0x51, 0x00, 0x00, 0x01, 0xa8, 0x00, 0x00,
-            0x00, 0x64, 0x67, 0x6c, 0x79, 0x66, 0x01, 0x73, 0xbf, 0xf8, 0x00, 0x00, 0x02, 0x20,
-            0x00, 0x00, 0x02, 0x1e, 0x68, 0x65, 0x61, 0x64, 0x1a, 0x46, 0x65, 0x4f, 0x00, 0x00,
-            0x00, 0xac, 0x00, 0x00, 0x00, 0x36, 0x68, 0x68, 0x65, 0x61, 0x05, 0x85, 0x01, 0xc2,
-            0x00, 0x00, 0x00, 0xe4, 0x00, 0x00, 0x00, 0x24, 0x68, 0x6d, 0x74, 0x78, 0x10, 0xf6,
-            0xff, 0xda, 0x00, 0x00, 0x01, 0x88, 0x00, 0x00, 0x00, 0x20, 0x6c, 0x6f, 0x63, 0x61,
-            0x02, 0x55, 0x01, 0xd6, 0x00, 0x00, 0x02, 0x0c, 0x00, 0x00, 0x00, 0x12, 0x6d, 0x61,
-            0x78, 0x70, 0x00, 0x12, 0x00, 0x47, 0x00, 0x00, 0x01, 0x08, 0x00, 0x00, 0x00, 0x20,
-            0x6e, 0x61, 0x6d, 0x65, 0xff, 0x72, 0x0d, 0x88, 0x00, 0x00, 0x04, 0x40, 0x00, 0x00,
-            0x00, 0xb4, 0x70, 0x6f, 0x73, 0x74, 0x16, 0xf9, 0xc6, 0xb7, 0x00, 0x00, 0x04, 0xf4,
-            0x00, 0x00, 0x00, 0x48, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x7e, 0x62,
-            0x06, 0x11, 0x5f, 0x0f, 0x3c, 0xf5, 0x00, 0x03, 0x03, 0xe8, 0x00, 0x00, 0x00, 0x00,
-            0xdc, 0x27, 0x59, 0x19, 0x00, 0x00, 0x00, 0x00, 0xdc, 0xa5, 0xc8, 0x08, 0xff, 0x73,
-            0xff, 0xb4, 0x02, 0xef, 0x03, 0x93, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x02, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x03, 0x20, 0xff, 0x38, 0x00, 0x00,
-            0x02, 0xf4, 0xff, 0x73, 0xff, 0x8d, 0x02, 0xef, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x08, 0x00, 0x01,
-            0x00, 0x00, 0x00, 0x08, 0x00, 0x34, 0x00, 0x03, 0x00, 0x10, 0x00, 0x04, 0x00, 0x02,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02,
-            0x00, 0x01, 0x00, 0x03, 0x02, 0x1f, 0x01, 0x90, 0x00, 0x05, 0x00, 0x04, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x43, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x3f, 0x3f, 0x3f, 0x3f, 0x00, 0x00, 0x00, 0x20, 0x03, 0x01,
-            0x03, 0x20, 0xff, 0x38, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x01, 0xf4, 0x02, 0xbc, 0x00, 0x00, 0x00, 0x20, 0x00, 0x00,
-            0x02, 0xf4, 0x00, 0x05, 0x02, 0xf4, 0x00, 0x05, 0x02, 0x98, 0x00, 0x1e, 0x02, 0xf4,
-            0x00, 0x05, 0x00, 0xc8, 0x00, 0x00, 0x02, 0x58, 0x00, 0x1d, 0x02, 0x58, 0x00, 0x1d,
-            0x00, 0x0a, 0xff, 0x73, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00,
-            0x00, 0x14, 0x00, 0x03, 0x00, 0x01, 0x00, 0x00, 0x00, 0x14, 0x00, 0x04, 0x00, 0x50,
-            0x00, 0x00, 0x00, 0x10, 0x00, 0x10, 0x00, 0x03, 0x00, 0x00, 0x00, 0x20, 0x00, 0x24,
-            0x00, 0x41, 0x00, 0x4f, 0x00, 0x56, 0x00, 0xc1, 0x03, 0x01, 0xff, 0xff, 0x00, 0x00,
-            0x00, 0x20, 0x00, 0x24, 0x00, 0x41, 0x00, 0x4f, 0x00, 0x56, 0x00, 0xc1, 0x03, 0x01,
-            0xff, 0xff, 0xff, 0xe4, 0xff, 0xe1, 0xff, 0xbf, 0xff, 0xb3, 0xff, 0xad, 0xff, 0x40,
-            0xfd, 0x06, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1e, 0x00, 0x2a, 0x00, 0x51,
-            0x00, 0x66, 0x00, 0x66, 0x00, 0xb6, 0x01, 0x01, 0x01, 0x0f, 0x00, 0x00, 0x00, 0x03,
-            0x00, 0x05, 0x00, 0x00, 0x02, 0xef, 0x02, 0xbc, 0x00, 0x03, 0x00, 0x07, 0x00, 0x0b,
-            0x00, 0x00, 0x01, 0x01, 0x33, 0x01, 0x23, 0x01, 0x33, 0x01, 0x13, 0x35, 0x21, 0x15,
-            0x01, 0x43, 0x01, 0x3e, 0x6e, 0xfe, 0xc2, 0x6e, 0xfe, 0xc2, 0x6e, 0x01, 0x3e, 0x86,
-            0xfe, 0x61, 0x02, 0xbc, 0xfd, 0x44, 0x02, 0xbc, 0xfd, 0x44, 0x02, 0xbc, 0xfe, 0x10,
-            0x50, 0x50, 0xff, 0xff, 0x00, 0x05, 0x00, 0x00, 0x02, 0xef, 0x03, 0x93, 0x00, 0x26,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x07, 0x00, 0x07, 0x01, 0x92, 0x00, 0x82, 0x00, 0x02,
-            0x00, 0x1e, 0xff, 0xf6, 0x02, 0x7a, 0x02, 0xc6, 0x00, 0x0b, 0x00, 0x17, 0x00, 0x00,
-            0x01, 0x14, 0x06, 0x23, 0x22, 0x26, 0x35, 0x34, 0x36, 0x33, 0x32, 0x16, 0x05, 0x14,
-            0x16, 0x33, 0x32, 0x36, 0x35, 0x34, 0x26, 0x23, 0x22, 0x06, 0x02, 0x7a, 0x96, 0x98,
-            0x97, 0x97, 0x97, 0x97, 0x98, 0x96, 0xfd, 0xfe, 0x6a, 0x6a, 0x6a, 0x6a, 0x6a, 0x6a,
-            0x6a, 0x6a, 0x01, 0x5e, 0xb5, 0xb3, 0xb3, 0xb5, 0xb5, 0xb3, 0xb3, 0xb5, 0x8a, 0x8a,
-            0x8a, 0x8a, 0x8a, 0x8a, 0x8a, 0x00, 0x00, 0x02, 0x00, 0x05, 0x00, 0x00, 0x02, 0xef,
-            0x02, 0xbc, 0x00, 0x03, 0x00, 0x07, 0x00, 0x00, 0x21, 0x23, 0x01, 0x33, 0x01, 0x23,
-            0x01, 0x33, 0x01, 0xb1, 0x6e, 0x01, 0x3e, 0x6e, 0xfe, 0xc2, 0x6e, 0xfe, 0xc2, 0x6e,
-            0x02, 0xbc, 0xfd, 0x44, 0x02, 0xbc, 0x00, 0x03, 0x00, 0x1d, 0xff, 0xbc, 0x02, 0x44,
-            0x02, 0xf7, 0x00, 0x23, 0x00, 0x2b, 0x00, 0x33, 0x00, 0x00, 0x01, 0x35, 0x33, 0x15,
-            0x16, 0x16, 0x17, 0x07, 0x26, 0x26, 0x27, 0x15, 0x16, 0x16, 0x15, 0x14, 0x06, 0x07,
-            0x15, 0x23, 0x35, 0x26, 0x26, 0x27, 0x37, 0x16, 0x16, 0x17, 0x35, 0x27, 0x26, 0x26,
-            0x35, 0x34, 0x36, 0x36, 0x17, 0x06, 0x06, 0x15, 0x14, 0x16, 0x16, 0x17, 0x17, 0x15,
-            0x36, 0x36, 0x35, 0x34, 0x26, 0x26, 0x01, 0x08, 0x5a, 0x3d, 0x68, 0x2b, 0x35, 0x24,
-            0x4b, 0x2c, 0x71, 0x71, 0x79, 0x69, 0x5a, 0x48, 0x78, 0x2b, 0x34, 0x2a, 0x54, 0x39,
-            0x0f, 0x65, 0x69, 0x38, 0x64, 0x41, 0x3d, 0x42, 0x17, 0x36, 0x2f, 0x5d, 0x45, 0x3f,
-            0x18, 0x39, 0x02, 0xcb, 0x2c, 0x2c, 0x06, 0x2f, 0x2a, 0x48, 0x24, 0x25, 0x06, 0xfe,
-            0x16, 0x58, 0x4f, 0x52, 0x6e, 0x0a, 0x32, 0x32, 0x06, 0x2e, 0x28, 0x48, 0x24, 0x25,
-            0x04, 0xe8, 0x03, 0x13, 0x61, 0x52, 0x39, 0x5b, 0x39, 0x50, 0x09, 0x41, 0x33, 0x20,
-            0x2a, 0x1a, 0x0a, 0x6b, 0xd8, 0x07, 0x3b, 0x30, 0x1c, 0x27, 0x19, 0x00, 0x00, 0x01,
-            0x00, 0x1d, 0xff, 0xb4, 0x02, 0x44, 0x02, 0xf7, 0x00, 0x32, 0x00, 0x00, 0x01, 0x35,
-            0x33, 0x15, 0x16, 0x16, 0x17, 0x07, 0x26, 0x26, 0x23, 0x22, 0x06, 0x15, 0x14, 0x16,
-            0x16, 0x17, 0x17, 0x16, 0x16, 0x15, 0x14, 0x06, 0x06, 0x07, 0x15, 0x23, 0x35, 0x26,
-            0x26, 0x27, 0x37, 0x1e, 0x02, 0x33, 0x32, 0x36, 0x35, 0x34, 0x26, 0x26, 0x27, 0x27,
-            0x26, 0x26, 0x35, 0x34, 0x36, 0x36, 0x01, 0x08, 0x5a, 0x3d, 0x68, 0x2b, 0x35, 0x2d,
-            0x5e, 0x3e, 0x52, 0x59, 0x17, 0x36, 0x2f, 0x63, 0x6d, 0x6f, 0x37, 0x65, 0x46, 0x5a,
-            0x48, 0x78, 0x2b, 0x34, 0x22, 0x41, 0x4f, 0x33, 0x5d, 0x53, 0x19, 0x3c, 0x35, 0x63,
-            0x65, 0x69, 0x38, 0x64, 0x02, 0xcb, 0x2c, 0x2c, 0x06, 0x2f, 0x2a, 0x48, 0x2c, 0x26,
-            0x44, 0x3c, 0x20, 0x2a, 0x1a, 0x0a, 0x14, 0x16, 0x57, 0x4f, 0x36, 0x57, 0x36, 0x07,
-            0x3a, 0x3a, 0x06, 0x2e, 0x28, 0x48, 0x1c, 0x23, 0x10, 0x3d, 0x37, 0x1d, 0x27, 0x1a,
-            0x09, 0x12, 0x13, 0x61, 0x52, 0x39, 0x5b, 0x39, 0x00, 0x01, 0xff, 0x73, 0x02, 0x76,
-            0x00, 0x7d, 0x03, 0x11, 0x00, 0x03, 0x00, 0x00, 0x13, 0x07, 0x07, 0x37, 0x7d, 0xf3,
-            0x17, 0xf3, 0x03, 0x11, 0x45, 0x56, 0x45, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x08,
-            0x00, 0x66, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x0f, 0x00, 0x00,
-            0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x0f, 0x00, 0x00, 0x00, 0x01,
-            0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x06, 0x00, 0x0f, 0x00, 0x01, 0x00, 0x00,
-            0x00, 0x00, 0x01, 0x01, 0x00, 0x05, 0x00, 0x15, 0x00, 0x03, 0x00, 0x01, 0x04, 0x09,
-            0x00, 0x01, 0x00, 0x1e, 0x00, 0x1a, 0x00, 0x03, 0x00, 0x01, 0x04, 0x09, 0x00, 0x10,
-            0x00, 0x1e, 0x00, 0x1a, 0x00, 0x03, 0x00, 0x01, 0x04, 0x09, 0x01, 0x00, 0x00, 0x0c,
-            0x00, 0x38, 0x00, 0x03, 0x00, 0x01, 0x04, 0x09, 0x01, 0x01, 0x00, 0x0a, 0x00, 0x44,
-            0x53, 0x69, 0x6d, 0x70, 0x6c, 0x65, 0x20, 0x54, 0x77, 0x6f, 0x20, 0x41, 0x78, 0x69,
-            0x73, 0x57, 0x65, 0x69, 0x67, 0x68, 0x74, 0x53, 0x6c, 0x61, 0x6e, 0x74, 0x00, 0x53,
-            0x00, 0x69, 0x00, 0x6d, 0x00, 0x70, 0x00, 0x6c, 0x00, 0x65, 0x00, 0x20, 0x00, 0x54,
-            0x00, 0x77, 0x00, 0x6f, 0x00, 0x20, 0x00, 0x41, 0x00, 0x78, 0x00, 0x69, 0x00, 0x73,
-            0x00, 0x57, 0x00, 0x65, 0x00, 0x69, 0x00, 0x67, 0x00, 0x68, 0x00, 0x74, 0x00, 0x53,
-            0x00, 0x6c, 0x00, 0x61, 0x00, 0x6e, 0x00, 0x74, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x08,
-            0x00, 0x24, 0x00, 0xc9, 0x00, 0x32, 0x00, 0x39, 0x00, 0x03, 0x00, 0x07, 0x01, 0x02,
-            0x01, 0x03, 0x0b, 0x64, 0x6f, 0x6c, 0x6c, 0x61, 0x72, 0x2e, 0x62, 0x6f, 0x6c, 0x64,
-            0x09, 0x61, 0x63, 0x75, 0x74, 0x65, 0x63, 0x6f, 0x6d, 0x62,
-        ];
-        let deserialized: font::Font = otspec::de::from_bytes(&binary_font).unwrap();
+        let deserialized = sample_font();
         deserialized.fully_deserialize();
         let glyf = deserialized.tables.glyf().unwrap().unwrap();
         /*
@@ -441,7 +1239,9 @@ mod tests {
             ],
             components: vec![],
             instructions: vec![],
-            overlap: false // There is, though.
+            overlap: false, // There is, though.
+            explicit_empty: false,
+            bounds_dirty: false,
         });
 
         /*
@@ -502,6 +1302,827 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_resolve_match_points() {
+        let square = square_glyph();
+        let accent = GlyphBuilder::new()
+            .move_to(0, 0)
+            .line_to(10, 0)
+            .line_to(5, 10)
+            .build();
+        let mut composite = Glyph {
+            xMin: 0,
+            xMax: 0,
+            yMin: 0,
+            yMax: 0,
+            contours: vec![],
+            instructions: vec![],
+            overlap: false,
+            explicit_empty: false,
+            bounds_dirty: false,
+            components: vec![
+                component(0, ComponentFlags::empty()),
+                Component {
+                    glyph_index: 1,
+                    transformation: kurbo::Affine::IDENTITY,
+                    match_points: Some((2, 2)),
+                    flags: ComponentFlags::empty(),
+                },
+            ],
+        };
+        let table = glyf {
+            glyphs: vec![square.clone(), accent.clone(), composite.clone()],
+        };
+        assert_eq!(table.resolve_match_points(2).unwrap(), vec![(1, 95, 90)]);
+        assert_eq!(table.resolve_match_points(0).unwrap(), vec![]);
+
+        composite.components[1].match_points = Some((4, 2));
+        let table = glyf {
+            glyphs: vec![square.clone(), accent.clone(), composite.clone()],
+        };
+        assert!(table.resolve_match_points(2).is_err());
+
+        composite.components[1].match_points = Some((2, 3));
+        let table = glyf {
+            glyphs: vec![square, accent, composite],
+        };
+        assert!(table.resolve_match_points(2).is_err());
+    }
+
+    #[test]
+    fn test_rendered_points() {
+        let mut glyf = sample_glyf();
+        let a_points: Vec<Point> = glyf.glyphs[0].contours.iter().flatten().copied().collect();
+        let acute_points: Vec<Point> = glyf.glyphs[7].contours.iter().flatten().copied().collect();
+        let shifted_acute: Vec<Point> = acute_points
+            .iter()
+            .map(|pt| Point {
+                x: pt.x + 402,
+                y: pt.y + 130,
+                on_curve: pt.on_curve,
+            })
+            .collect();
+
+        let rendered = glyf.rendered_points(1);
+        assert_eq!(rendered.len(), a_points.len() + acute_points.len());
+        assert_eq!(rendered[..a_points.len()], a_points[..]);
+        assert_eq!(rendered[a_points.len()..], shifted_acute[..]);
+        assert_eq!(glyf.rendered_points(0), a_points);
+        assert!(glyf.rendered_points(100).is_empty());
+
+        // With a scaled offset, the offset is scaled along with the accent
+        let acute = &mut glyf.glyphs[1].components[1];
+        acute.transformation = kurbo::Affine::new([0.5, 0.0, 0.0, 0.5, 402.0, 130.0]);
+        acute.flags = ComponentFlags::SCALED_COMPONENT_OFFSET;
+        let rendered = glyf.rendered_points(1);
+        let first_acute = rendered[a_points.len()];
+        assert_eq!(
+            first_acute.x,
+            otmath::ot_round((acute_points[0].x as f32 + 402.0) / 2.0) as i16
+        );
+        assert_eq!(
+            first_acute.y,
+            otmath::ot_round((acute_points[0].y as f32 + 130.0) / 2.0) as i16
+        );
+
+        // A component loop produces no points
+        glyf.glyphs[7].components = vec![component(1, ComponentFlags::empty())];
+        assert!(glyf.rendered_points(1).is_empty());
+    }
+
+    #[test]
+    fn test_max_component_depth() {
+        let square = square_glyph();
+        let uses = |glyph_index| Glyph {
+            components: vec![Component {
+                glyph_index,
+                transformation: kurbo::Affine::translate((10.0, 0.0)),
+                match_points: None,
+                flags: ComponentFlags::empty(),
+            }],
+            ..Glyph::empty()
+        };
+        // Glyph 3 uses 2, which uses 1, which uses the square
+        let table = glyf {
+            glyphs: vec![square, uses(0), uses(1), uses(2)],
+        };
+        assert!(table
+            .flat_components_with_max_depth(&table.glyphs[3], 2)
+            .is_err());
+        assert!(table.decompose_glyph_with_max_depth(3, 2).is_err());
+        let flat = table
+            .flat_components_with_max_depth(&table.glyphs[3], 3)
+            .unwrap();
+        assert_eq!(flat.len(), 1);
+        assert_eq!(
+            flat[0].transformation,
+            kurbo::Affine::translate((30.0, 0.0))
+        );
+        let decomposed = table.decompose_glyph_with_max_depth(3, 3).unwrap();
+        assert_eq!(decomposed.contours[0][0].x, 30);
+        assert_eq!(table.decompose_glyph(3).unwrap(), decomposed);
+
+        // A loop of components is always too deep
+        let looped = glyf {
+            glyphs: vec![uses(1), uses(0)],
+        };
+        assert!(looped.flat_components(&looped.glyphs[0]).is_err());
+        assert!(looped.decompose_glyph(0).is_err());
+    }
+
+    #[test]
+    fn test_glyph_bounds() {
+        let square = square_glyph();
+        let component = |glyph_index, transformation| Component {
+            glyph_index,
+            transformation,
+            match_points: None,
+            flags: ComponentFlags::empty(),
+        };
+        let composite = |components| Glyph {
+            xMin: 0,
+            xMax: 0,
+            yMin: 0,
+            yMax: 0,
+            contours: vec![],
+            instructions: vec![],
+            overlap: false,
+            explicit_empty: false,
+            bounds_dirty: false,
+            components,
+        };
+        let table = glyf {
+            glyphs: vec![
+                square,
+                composite(vec![
+                    component(0, kurbo::Affine::IDENTITY),
+                    component(0, kurbo::Affine::new([0.5, 0.0, 0.0, 0.5, 200.0, -50.0])),
+                ]),
+                composite(vec![component(1, kurbo::Affine::translate((10.0, 20.0)))]),
+                composite(vec![
+                    Component {
+                        flags: ComponentFlags::USE_MY_METRICS,
+                        ..component(0, kurbo::Affine::IDENTITY)
+                    },
+                    component(0, kurbo::Affine::translate((0.0, 150.0))),
+                ]),
+            ],
+        };
+        assert_eq!(
+            table.glyph_bounds(0),
+            kurbo::Rect::new(0.0, 0.0, 100.0, 100.0)
+        );
+        assert_eq!(
+            table.glyph_bounds(1),
+            kurbo::Rect::new(0.0, -50.0, 250.0, 100.0)
+        );
+        assert_eq!(
+            table.glyph_bounds(2),
+            kurbo::Rect::new(10.0, -30.0, 260.0, 120.0)
+        );
+        // USE_MY_METRICS affects the metrics, not the extent of the outline
+        assert_eq!(
+            table.glyph_bounds(3),
+            kurbo::Rect::new(0.0, 0.0, 100.0, 250.0)
+        );
+        // Nothing was flattened or recalculated
+        assert_eq!(table.glyphs[2].components.len(), 1);
+        assert_eq!(table.glyphs[2].bounds_rect(), kurbo::Rect::ZERO);
+
+        let mut recalculated = table.clone();
+        recalculated.recalc_bounds();
+        for (gid, glyph) in recalculated.glyphs.iter().enumerate().take(3) {
+            assert_eq!(table.glyph_bounds(gid), glyph.bounds_rect());
+        }
+        // recalc_bounds takes a USE_MY_METRICS component's bounds alone
+        assert_eq!(
+            recalculated.glyphs[3].bounds_rect(),
+            kurbo::Rect::new(0.0, 0.0, 100.0, 100.0)
+        );
+        assert_eq!(table.glyph_bounds(4), kurbo::Rect::ZERO);
+
+        // The same bounds can be stored without flattening glyph 2
+        let mut preserved = table.clone();
+        preserved.recalc_bounds_preserving_components();
+        for (gid, glyph) in preserved.glyphs.iter().enumerate() {
+            assert_eq!(glyph.bounds_rect(), recalculated.glyphs[gid].bounds_rect());
+            assert_eq!(glyph.components, table.glyphs[gid].components);
+        }
+
+        // Recalculating one glyph gives the same result and leaves the others
+        let mut single = table.clone();
+        single.recalc_bounds_for(2);
+        assert_eq!(single.glyphs[2], recalculated.glyphs[2]);
+        single.recalc_bounds_for(3);
+        assert_eq!(single.glyphs[3], recalculated.glyphs[3]);
+        assert_eq!(single.glyphs[1], table.glyphs[1]);
+
+        let mut edited = recalculated.clone();
+        edited.glyphs[0].contours[0][2].x = 150;
+        edited.recalc_bounds_for(0);
+        assert_eq!(
+            edited.glyphs[0].bounds_rect(),
+            kurbo::Rect::new(0.0, 0.0, 150.0, 100.0)
+        );
+        assert_eq!(edited.glyphs[1], recalculated.glyphs[1]);
+        assert_eq!(edited.glyphs[2], recalculated.glyphs[2]);
+    }
+
+    #[test]
+    fn test_bounds_out_of_range() {
+        let square = GlyphBuilder::new()
+            .move_to(0, 0)
+            .line_to(1000, 0)
+            .line_to(1000, 1000)
+            .line_to(0, 1000)
+            .build();
+        let far = Glyph {
+            components: vec![Component {
+                glyph_index: 0,
+                transformation: kurbo::Affine::translate((32000.0, -32500.0)),
+                match_points: None,
+                flags: ComponentFlags::empty(),
+            }],
+            ..Glyph::empty()
+        };
+        let mut table = glyf {
+            glyphs: vec![square, far],
+        };
+        table.recalc_bounds();
+        assert_eq!(
+            table.glyphs[1].bounds_rect(),
+            kurbo::Rect::new(32000.0, -32500.0, 32767.0, -31500.0)
+        );
+    }
+
+    #[test]
+    fn test_empty_glyph() {
+        let empty = Glyph::empty();
+        assert!(empty.is_empty());
+        assert_eq!(otspec::ser::to_bytes(&empty).unwrap(), Vec::<u8>::new());
+
+        let composite = Glyph {
+            components: vec![component(0, ComponentFlags::empty())],
+            ..Glyph::empty()
+        };
+        assert!(!composite.is_empty());
+        assert!(!otspec::ser::to_bytes(&composite).unwrap().is_empty());
+
+        let simple = GlyphBuilder::new().move_to(0, 0).line_to(10, 10).build();
+        assert!(!simple.is_empty());
+
+        let table = super::from_bytes(&[], &[None, None]).unwrap();
+        assert_eq!(table.glyphs, vec![Glyph::empty(), Glyph::empty()]);
+    }
+
+    #[test]
+    fn test_explicit_empty_glyph() {
+        // A header declaring zero contours, with one byte of instructions
+        let space = vec![0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0xb0, 0];
+        let table = super::from_bytes(&space, &[None, Some(0)]).unwrap();
+        assert_eq!(table.glyphs[0], Glyph::empty());
+        let glyph = &table.glyphs[1];
+        assert!(glyph.is_empty());
+        assert!(glyph.explicit_empty);
+        assert!(!glyph.has_no_data());
+        assert_eq!(glyph.instructions, vec![0xb0]);
+        let serialized = otspec::ser::to_bytes(glyph).unwrap();
+        assert_eq!(serialized, space[..13]);
+        assert_eq!(glyph.ot_binary_size(), 13);
+
+        // Without the flag, the same glyph has no data at all
+        let absent = Glyph {
+            explicit_empty: false,
+            ..glyph.clone()
+        };
+        assert!(otspec::ser::to_bytes(&absent).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_dedupe() {
+        let square = square_glyph();
+        let mut nearly_square = square.clone();
+        nearly_square.contours[0][2].x = 101;
+        let mut hinted_square = square.clone();
+        hinted_square.instructions = vec![0xb0, 0x00];
+        let composite = |glyph_index| Glyph {
+            components: vec![Component {
+                glyph_index,
+                transformation: kurbo::Affine::translate((10.0, 0.0)),
+                match_points: None,
+                flags: ComponentFlags::empty(),
+            }],
+            ..Glyph::empty()
+        };
+        let mut table = glyf {
+            glyphs: vec![
+                Glyph::empty(),
+                square.clone(),
+                nearly_square.clone(),
+                square,
+                hinted_square,
+                composite(1),
+                composite(3),
+                Glyph::empty(),
+            ],
+        };
+        let remap = table.dedupe();
+        let mut expected = HashMap::new();
+        expected.insert(3, 1);
+        // Identical once its component is redirected
+        expected.insert(6, 5);
+        assert_eq!(remap, expected);
+        assert_eq!(table.glyphs[6].components[0].glyph_index, 1);
+        assert_eq!(table.glyphs[2], nearly_square);
+    }
+
+    #[test]
+    fn test_closure() {
+        let glyf = sample_glyf();
+        // Aacute is made of A and acutecomb
+        let keep: HashSet<u16> = [1].into_iter().collect();
+        assert_eq!(glyf.closure(&keep), [0, 1, 7].into_iter().collect());
+
+        // A composite referencing a composite, plus a loop
+        let composite = |components| Glyph {
+            components,
+            ..Glyph::empty()
+        };
+        let table = glyf {
+            glyphs: vec![
+                Glyph::empty(),
+                GlyphBuilder::new().move_to(0, 0).line_to(1, 1).build(),
+                composite(vec![component(1, ComponentFlags::empty())]),
+                composite(vec![component(2, ComponentFlags::empty())]),
+                composite(vec![component(5, ComponentFlags::empty())]),
+                composite(vec![component(4, ComponentFlags::empty())]),
+            ],
+        };
+        let keep: HashSet<u16> = [3].into_iter().collect();
+        assert_eq!(table.closure(&keep), [1, 2, 3].into_iter().collect());
+        let keep: HashSet<u16> = [4].into_iter().collect();
+        assert_eq!(table.closure(&keep), [4, 5].into_iter().collect());
+    }
+
+    #[test]
+    fn test_subset() {
+        let glyf = sample_glyf();
+        let (subset, offsets, remap) = glyf.subset(&[4, 7, 1, 0]).unwrap();
+        assert_eq!(subset.glyphs.len(), 4);
+        assert_eq!(offsets.len(), 5);
+        assert_eq!(offsets[0], offsets[1]); // space is empty
+        assert_eq!(remap.get(&1), Some(&2));
+
+        let mut bytes = vec![];
+        for g in &subset.glyphs {
+            if !g.is_empty() {
+                bytes.extend(otspec::ser::to_bytes(g).unwrap());
+                while bytes.len() % 4 != 0 {
+                    bytes.push(0);
+                }
+            }
+        }
+        assert_eq!(bytes.len() as u32, offsets[4]);
+        let loca_offsets: Vec<Option<u32>> = offsets
+            .windows(2)
+            .map(|w| if w[0] == w[1] { None } else { Some(w[0]) })
+            .collect();
+        let reparsed = super::from_bytes(&bytes, &loca_offsets).unwrap();
+        assert_eq!(reparsed, subset);
+
+        // Aacute now points at the new IDs of A and acutecomb
+        let aacute = &reparsed.glyphs[2];
+        assert_eq!(aacute.components[0].glyph_index, 3);
+        assert_eq!(aacute.components[1].glyph_index, 1);
+        assert_eq!(reparsed.glyphs[3], glyf.glyphs[0]);
+
+        assert!(glyf.subset(&[1, 0]).is_err());
+    }
+
+    #[test]
+    fn test_decompose_transformed() {
+        let mut glyf = sample_glyf();
+
+        // Aacute only translates its components
+        let aacute = glyf.glyphs[1].clone();
+        glyf.decompose_transformed(1);
+        assert_eq!(glyf.glyphs[1], aacute);
+
+        glyf.glyphs[1].components[0].transformation = kurbo::Affine::scale(0.5);
+        glyf.decompose_transformed(1);
+        let decomposed = &glyf.glyphs[1];
+        assert!(!decomposed.has_components());
+        assert_eq!(
+            decomposed.contours.len(),
+            glyf.glyphs[0].contours.len() + glyf.glyphs[7].contours.len()
+        );
+        assert_eq!(
+            decomposed.contours[0][1].x,
+            (glyf.glyphs[0].contours[0][1].x as f64 / 2.0) as i16
+        );
+        let acute = &glyf.glyphs[7].contours[0][0];
+        let last = glyf.glyphs[0].contours.len();
+        assert_eq!(decomposed.contours[last][0].x, acute.x + 402);
+        assert_eq!(decomposed.contours[last][0].y, acute.y + 130);
+        let y_max = decomposed.contours.iter().flatten().map(|pt| pt.y).max();
+        assert_eq!(Some(decomposed.yMax), y_max);
+    }
+
+    #[test]
+    fn test_detect_overlap_composite() {
+        let mut glyf = sample_glyf();
+        // A, with its overlapping strokes
+        assert!(glyf.detect_overlap(1));
+        glyf.glyphs[1].components[0].glyph_index = 2;
+        assert!(!glyf.detect_overlap(1));
+        // Move the acute into the O's outline
+        glyf.glyphs[1].components[1].transformation = kurbo::Affine::translate((100.0, -300.0));
+        assert!(glyf.detect_overlap(1));
+        assert!(!glyf.detect_overlap(100));
+    }
+
+    #[test]
+    fn test_flatten_to_polygons_composite() {
+        let glyf = sample_glyf();
+        // Aacute has the contours of A and of the acute
+        let polygons = glyf.flatten_to_polygons(1, 1.0);
+        let expected = glyf.glyphs[0].contours.len() + glyf.glyphs[7].contours.len();
+        assert_eq!(polygons.len(), expected);
+        assert_eq!(
+            polygons[..glyf.glyphs[0].contours.len()],
+            glyf.glyphs[0].flatten_to_polygons(1.0)
+        );
+        assert!(glyf.flatten_to_polygons(100, 1.0).is_empty());
+    }
+
+    #[test]
+    fn test_transform_all() {
+        let mut glyf = sample_glyf();
+        let shear = kurbo::Affine::new([1.0, 0.0, 0.2, 1.0, 0.0, 0.0]);
+        let expected: Vec<Point> = glyf.glyphs[1]
+            .decompose(&glyf.glyphs)
+            .contours
+            .iter()
+            .flatten()
+            .map(|pt| pt.transform(shear))
+            .collect();
+        glyf.glyphs[0].instructions = vec![0xb0, 0x00];
+
+        glyf.transform_all(shear);
+        assert!(glyf.glyphs[0].instructions.is_empty());
+        // The acute keeps its offset's height and moves right with the slant
+        let offset = glyf.glyphs[1].components[1].transformation.as_coeffs();
+        assert_eq!(offset, [1.0, 0.0, 0.0, 1.0, 402.0 + 0.2 * 130.0, 130.0]);
+
+        let decomposed = glyf.glyphs[1].decompose(&glyf.glyphs);
+        let actual: Vec<&Point> = decomposed.contours.iter().flatten().collect();
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected.iter()) {
+            assert!((a.x - e.x).abs() <= 1 && a.y == e.y, "{:?} != {:?}", a, e);
+        }
+        let y_max = actual.iter().map(|pt| pt.y).max().unwrap();
+        assert_eq!(glyf.glyphs[1].yMax, y_max);
+    }
+
+    #[test]
+    fn test_transform_all_with_rounding() {
+        use super::RoundMode;
+        let square = GlyphBuilder::new()
+            .move_to(1, 1)
+            .line_to(1, 3)
+            .line_to(3, 3)
+            .line_to(3, 1)
+            .build();
+        let half = kurbo::Affine::scale(0.5);
+        let xs = |table: &glyf| -> Vec<i16> {
+            table.glyphs[0].contours[0].iter().map(|pt| pt.x).collect()
+        };
+        let mut truncated = glyf {
+            glyphs: vec![square.clone()],
+        };
+        truncated.transform_all(half);
+        assert_eq!(xs(&truncated), vec![0, 0, 1, 1]);
+        let mut rounded = glyf {
+            glyphs: vec![square],
+        };
+        rounded.transform_all_with_rounding(half, RoundMode::Round);
+        assert_eq!(xs(&rounded), vec![1, 1, 2, 2]);
+        assert_eq!(
+            rounded.glyphs[0].bounds_rect(),
+            kurbo::Rect::new(1.0, 1.0, 2.0, 2.0)
+        );
+    }
+
+    #[test]
+    fn test_truncated_glyph_error() {
+        let glyf = sample_glyf();
+        let mut bytes = otspec::ser::to_bytes(&glyf.glyphs[0]).unwrap();
+        bytes.resize((bytes.len() + 3) & !3, 0);
+        let second = bytes.len() as u32;
+        let o_bytes = otspec::ser::to_bytes(&glyf.glyphs[2]).unwrap();
+        bytes.extend(&o_bytes[..o_bytes.len() / 2]);
+
+        let err = super::from_bytes(&bytes, &[Some(0), None, Some(second)]).unwrap_err();
+        assert_eq!(err.glyph_id, 2);
+        assert_eq!(err.offset, second);
+        assert!(err.to_string().contains("glyph 2"));
+    }
+
+    #[test]
+    fn test_from_bytes_borrows() {
+        let deserialized = sample_font();
+        let loca = deserialized.tables.loca().unwrap().unwrap();
+        let glyf = deserialized.tables.glyf().unwrap().unwrap();
+        let mut bytes = vec![];
+        let mut offsets = vec![];
+        for g in glyf.glyphs.iter() {
+            offsets.push(if g.is_empty() {
+                None
+            } else {
+                Some(bytes.len() as u32)
+            });
+            bytes.extend(otspec::ser::to_bytes(g).unwrap());
+            bytes.resize((bytes.len() + 3) & !3, 0);
+        }
+        assert_eq!(loca.indices.len(), offsets.len());
+
+        let borrowed = otspec::ReaderContext::from_slice(&bytes);
+        assert!(matches!(borrowed.input, std::borrow::Cow::Borrowed(_)));
+        let from_slice = super::from_bytes(&bytes, &offsets).unwrap();
+        let owned =
+            super::from_rc(&mut otspec::ReaderContext::new(bytes.clone()), &offsets).unwrap();
+        assert_eq!(from_slice, owned);
+        assert_eq!(from_slice, *glyf);
+    }
+
+    #[test]
+    fn test_bad_loca_offsets() {
+        let glyf = sample_glyf();
+        let mut bytes = otspec::ser::to_bytes(&glyf.glyphs[0]).unwrap();
+        bytes.resize((bytes.len() + 3) & !3, 0);
+        let second = bytes.len() as u32;
+        bytes.extend(otspec::ser::to_bytes(&glyf.glyphs[2]).unwrap());
+
+        let err = super::from_bytes(&bytes, &[Some(second), Some(0)]).unwrap_err();
+        assert_eq!(err.glyph_id, 1);
+        assert_eq!(err.offset, 0);
+
+        let past_end = bytes.len() as u32;
+        let err = super::from_bytes(&bytes, &[Some(0), Some(past_end)]).unwrap_err();
+        assert_eq!(err.glyph_id, 1);
+        assert!(err.source.0.contains("past the end"));
+
+        assert!(super::from_bytes(&bytes, &[Some(0), None, Some(second)]).is_ok());
+    }
+
+    #[test]
+    fn test_build_maxp() {
+        let mut glyf = sample_glyf();
+        let points = |gid: usize| glyf.glyphs[gid].num_points(false) as u16;
+        let contours = |gid: usize| glyf.glyphs[gid].contours.len() as u16;
+        let simple = [0, 2, 3, 4, 5, 6, 7];
+        let max_points = simple.iter().map(|&gid| points(gid)).max().unwrap();
+        let max_contours = simple.iter().map(|&gid| contours(gid)).max().unwrap();
+        let aacute_points = points(0) + points(7);
+        let aacute_contours = contours(0) + contours(7);
+
+        let stats = glyf.build_maxp();
+        assert_eq!(
+            stats,
+            super::MaxpStats {
+                num_glyphs: 8,
+                max_points,
+                max_contours,
+                max_composite_points: aacute_points,
+                max_composite_contours: aacute_contours,
+                max_component_elements: 2,
+                max_component_depth: 1,
+                max_size_of_instructions: 0,
+            }
+        );
+
+        // A composite of Aacute and O twice, and one of that and Aacute
+        let o_points = points(2);
+        let o_contours = contours(2);
+        glyf.glyphs.push(Glyph {
+            components: vec![
+                component(1, ComponentFlags::empty()),
+                component(2, ComponentFlags::empty()),
+                component(2, ComponentFlags::empty()),
+            ],
+            ..Glyph::empty()
+        });
+        glyf.glyphs.push(Glyph {
+            components: vec![
+                component(8, ComponentFlags::empty()),
+                component(1, ComponentFlags::empty()),
+            ],
+            instructions: vec![0xb0, 0x00, 0x00],
+            ..Glyph::empty()
+        });
+        let stats = glyf.build_maxp();
+        assert_eq!(
+            stats,
+            super::MaxpStats {
+                num_glyphs: 10,
+                max_points,
+                max_contours,
+                max_composite_points: 2 * aacute_points + 2 * o_points,
+                max_composite_contours: 2 * aacute_contours + 2 * o_contours,
+                max_component_elements: 3,
+                max_component_depth: 3,
+                max_size_of_instructions: 3,
+            }
+        );
+        match glyf.as_maxp10().table {
+            crate::tables::maxp::MaxpVariant::Maxp10(table) => {
+                assert_eq!(table.numGlyphs, stats.num_glyphs);
+                assert_eq!(table.maxCompositePoints, stats.max_composite_points);
+                assert_eq!(table.maxCompositeContours, stats.max_composite_contours);
+                assert_eq!(table.maxComponentDepth, stats.max_component_depth);
+            }
+            _ => unreachable!(),
+        }
+        let mut recalculated = glyf.as_maxp10();
+        recalculated.recalc_from_glyf(&glyf);
+        match recalculated.table {
+            crate::tables::maxp::MaxpVariant::Maxp10(table) => {
+                assert_eq!(table.maxComponentDepth, stats.max_component_depth);
+                assert_eq!(table.maxSizeOfInstructions, stats.max_size_of_instructions);
+            }
+            _ => unreachable!(),
+        }
+
+        // A loop of components does not recurse forever
+        glyf.glyphs[8]
+            .components
+            .push(component(9, ComponentFlags::empty()));
+        assert_eq!(
+            glyf.build_maxp().max_component_depth as u32,
+            super::MAX_COMPONENT_DEPTH
+        );
+    }
+
+    #[test]
+    fn test_component_usage() {
+        let mut glyf = sample_glyf();
+        // A and acutecomb are used by Aacute
+        let usage = glyf.component_usage();
+        assert_eq!(usage.get(&0), Some(&1));
+        assert_eq!(usage.get(&7), Some(&1));
+        assert_eq!(usage.get(&1), None);
+        assert_eq!(glyf.nested_component_usage().unwrap(), usage);
+
+        // A composite of Aacute, used twice
+        let aacute = glyf.glyphs[1].components[0].clone();
+        glyf.glyphs.push(Glyph {
+            components: vec![
+                Component {
+                    glyph_index: 1,
+                    ..aacute.clone()
+                },
+                Component {
+                    glyph_index: 1,
+                    ..aacute
+                },
+            ],
+            ..Glyph::empty()
+        });
+        let usage = glyf.component_usage();
+        assert_eq!(usage.get(&0), Some(&1));
+        assert_eq!(usage.get(&1), Some(&2));
+        let nested = glyf.nested_component_usage().unwrap();
+        assert_eq!(nested.get(&0), Some(&3));
+        assert_eq!(nested.get(&7), Some(&3));
+        assert_eq!(nested.get(&1), Some(&2));
+
+        // A loop of components is an error, not a partial count
+        glyf.glyphs[7].components = vec![Component {
+            glyph_index: 1,
+            ..glyf.glyphs[1].components[0].clone()
+        }];
+        assert!(glyf.nested_component_usage().is_err());
+    }
+
+    #[test]
+    fn test_max_depth_agrees() {
+        let mut glyf = sample_glyf();
+        // A composite of Aacute, so nested two levels deep
+        glyf.glyphs.push(Glyph {
+            components: vec![Component {
+                glyph_index: 1,
+                ..glyf.glyphs[1].components[0].clone()
+            }],
+            ..Glyph::empty()
+        });
+        let nested = glyf.glyphs[8].clone();
+        for (max_depth, allowed) in [(1, false), (2, true)] {
+            assert_eq!(
+                glyf.flat_components_with_max_depth(&nested, max_depth)
+                    .is_ok(),
+                allowed
+            );
+            assert_eq!(
+                glyf.resolve_match_points_with_max_depth(8, max_depth)
+                    .is_ok(),
+                allowed
+            );
+            assert_eq!(
+                !glyf.rendered_points_with_max_depth(8, max_depth).is_empty(),
+                allowed
+            );
+            assert_eq!(
+                glyf.nested_component_usage_with_max_depth(max_depth)
+                    .is_ok(),
+                allowed
+            );
+        }
+    }
+
+    #[test]
+    fn test_write_to() {
+        let glyf = sample_glyf();
+        let mut expected: Vec<u8> = vec![];
+        let mut expected_offsets = vec![];
+        for g in glyf.glyphs.iter() {
+            expected_offsets.push(expected.len() as u32);
+            expected.extend(otspec::ser::to_bytes(g).unwrap());
+            expected.resize((expected.len() + 3) & !3, 0);
+        }
+        expected_offsets.push(expected.len() as u32);
+        let mut streamed = vec![];
+        let offsets = glyf.write_to(&mut streamed).unwrap();
+        assert_eq!(streamed, expected);
+        assert_eq!(offsets, expected_offsets);
+        assert_eq!(streamed.len(), glyf.size_report().total);
+
+        // The streamed glyphs are read back with the returned offsets
+        let loca: Vec<Option<u32>> = offsets[..offsets.len() - 1]
+            .iter()
+            .zip(offsets[1..].iter())
+            .map(|(&a, &b)| if a == b { None } else { Some(a) })
+            .collect();
+        let reparsed = super::from_bytes(&streamed, &loca).unwrap();
+        assert_eq!(reparsed.glyphs, glyf.glyphs);
+
+        let mut streamed = vec![];
+        let offsets = super::glyf {
+            glyphs: vec![Glyph::empty()],
+        }
+        .write_to(&mut streamed)
+        .unwrap();
+        assert_eq!(streamed, vec![0]);
+        assert_eq!(offsets, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_size_report() {
+        let glyf = sample_glyf();
+        let mut output: Vec<u8> = vec![];
+        for g in glyf.glyphs.iter() {
+            let bytes = otspec::ser::to_bytes(g).unwrap();
+            assert_eq!(g.ot_binary_size(), bytes.len());
+            output.extend(bytes);
+            output.resize((output.len() + 3) & !3, 0);
+        }
+        let report = glyf.size_report();
+        assert_eq!(report.total, output.len());
+        assert_eq!(report.simple + report.composite, report.total);
+        assert_eq!(report.composite, 24);
+        assert_eq!(report.instructions, 0);
+
+        let scaled = Glyph {
+            components: vec![
+                Component {
+                    glyph_index: 0,
+                    transformation: kurbo::Affine::new([0.5, 0.25, 0.0, 0.5, 300.0, 0.0]),
+                    match_points: None,
+                    flags: ComponentFlags::empty(),
+                },
+                Component {
+                    glyph_index: 2,
+                    transformation: kurbo::Affine::scale(0.75),
+                    match_points: None,
+                    flags: ComponentFlags::empty(),
+                },
+            ],
+            instructions: vec![0xb0, 0x00, 0x2c],
+            ..Glyph::empty()
+        };
+        assert_eq!(
+            scaled.ot_binary_size(),
+            otspec::ser::to_bytes(&scaled).unwrap().len()
+        );
+        let report = glyf {
+            glyphs: vec![scaled],
+        }
+        .size_report();
+        assert_eq!(report.instructions, 3);
+        assert_eq!(report.total, report.composite);
+
+        assert_eq!(glyf { glyphs: vec![] }.size_report().total, 1);
+    }
+
     #[test]
     fn test_insert_implicit_oncurves() {
         #[rustfmt::skip]
@@ -510,6 +2131,8 @@ mod tests {
             components: vec![],
             instructions: vec![],
             overlap: false,
+            explicit_empty: false,
+            bounds_dirty: false,
             contours: vec![
                 vec![
                     Point {x: 634, y: 650, on_curve: true, },
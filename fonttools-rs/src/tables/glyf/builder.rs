@@ -0,0 +1,140 @@
+use super::glyph::Glyph;
+use super::point::Point;
+
+/// A convenience interface for constructing simple (non-composite) glyphs.
+///
+/// Contours are assembled in the TrueType point model: `line_to` adds an
+/// on-curve point, `quad_to` adds an off-curve control point followed by an
+/// on-curve end point. Contours are implicitly closed, so a final point which
+/// returns to the start of the contour is dropped when the contour is closed.
+/// Bounds are computed automatically when the glyph is built.
+///
+/// ```
+/// use fonttools::tables::glyf::GlyphBuilder;
+///
+/// let glyph = GlyphBuilder::new()
+///     .move_to(0, 0)
+///     .line_to(100, 0)
+///     .quad_to(150, 50, 100, 100)
+///     .close()
+///     .build();
+/// assert_eq!(glyph.contours[0].len(), 4);
+/// assert_eq!(glyph.xMax, 150);
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct GlyphBuilder {
+    contours: Vec<Vec<Point>>,
+    current: Vec<Point>,
+}
+
+impl GlyphBuilder {
+    /// Creates a new, empty glyph builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts a new contour at the given point, closing any open contour.
+    pub fn move_to(mut self, x: i16, y: i16) -> Self {
+        self.finish_contour();
+        self.current.push(Point {
+            x,
+            y,
+            on_curve: true,
+        });
+        self
+    }
+
+    /// Adds a straight line segment to the given point.
+    pub fn line_to(mut self, x: i16, y: i16) -> Self {
+        self.current.push(Point {
+            x,
+            y,
+            on_curve: true,
+        });
+        self
+    }
+
+    /// Adds a quadratic curve segment with the given control point and end point.
+    pub fn quad_to(mut self, cx: i16, cy: i16, x: i16, y: i16) -> Self {
+        self.current.push(Point {
+            x: cx,
+            y: cy,
+            on_curve: false,
+        });
+        self.current.push(Point {
+            x,
+            y,
+            on_curve: true,
+        });
+        self
+    }
+
+    /// Closes the current contour.
+    pub fn close(mut self) -> Self {
+        self.finish_contour();
+        self
+    }
+
+    /// Closes any open contour and returns the finished glyph, with its
+    /// bounds computed from the contour points.
+    pub fn build(mut self) -> Glyph {
+        self.finish_contour();
+        let mut glyph = Glyph {
+            contours: self.contours,
+            ..Glyph::empty()
+        };
+        glyph.set_bounds_rect(super::simple_bounds(&glyph));
+        glyph
+    }
+
+    fn finish_contour(&mut self) {
+        if self.current.len() > 1 {
+            let first = self.current[0];
+            if self.current.last() == Some(&first) {
+                self.current.pop();
+            }
+        }
+        if !self.current.is_empty() {
+            self.contours.push(std::mem::take(&mut self.current));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glyph_builder() {
+        let glyph = GlyphBuilder::new()
+            .move_to(20, 0)
+            .line_to(220, 0)
+            .line_to(100, 200)
+            .line_to(20, 0)
+            .move_to(386, 200)
+            .quad_to(386, 290, 477, 290)
+            .quad_to(567, 290, 567, 200)
+            .quad_to(567, 109, 477, 109)
+            .quad_to(386, 109, 386, 200)
+            .build();
+        assert_eq!(glyph.contours.len(), 2);
+        assert_eq!(glyph.contours[0].len(), 3);
+        assert_eq!(glyph.contours[1].len(), 8);
+        assert!(glyph.contours[1][0].on_curve);
+        assert!(!glyph.contours[1][7].on_curve);
+        assert_eq!(
+            (glyph.xMin, glyph.yMin, glyph.xMax, glyph.yMax),
+            (20, 0, 567, 290)
+        );
+
+        let serialized = otspec::ser::to_bytes(&glyph).unwrap();
+        let deserialized: Glyph = otspec::de::from_bytes(&serialized).unwrap();
+        assert_eq!(deserialized, glyph);
+    }
+
+    #[test]
+    fn test_glyph_builder_empty() {
+        let glyph = GlyphBuilder::new().build();
+        assert!(glyph.is_empty());
+    }
+}
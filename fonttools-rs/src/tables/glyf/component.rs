@@ -59,6 +59,13 @@ pub struct Component {
     /// An affine transformation applied to the component's contours.
     pub transformation: Affine,
     /// Alternate, and rarely used, method of positioning components using contour point numbers.
+    ///
+    /// The first number is a point in the glyph so far (the points of the
+    /// preceding components), and the second a point in this component; the
+    /// component is moved so that the two coincide. When this is set, the
+    /// component is written with `ARGS_ARE_XY_VALUES` cleared and the point
+    /// numbers in place of the offset, so the translation part of
+    /// `transformation` is not stored. The scale part is.
     pub match_points: Option<(uint16, uint16)>,
     /// Flags.
     /// Most of these are calculated automatically on serialization. Those which can be
@@ -84,17 +91,21 @@ impl Component {
         } else if instructions {
             flags |= ComponentFlags::WE_HAVE_INSTRUCTIONS;
         }
+        // Flags are chosen from the transform as it will be written, so a scale
+        // which rounds to 1.0 in F2DOT14 does not produce a scale record.
         let [x_scale, scale01, scale10, scale_y, translate_x, translate_y] =
-            self.transformation.as_coeffs();
-        if self.match_points.is_some() {
-            let (x, y) = self.match_points.unwrap();
-            if !(x <= 255 && y <= 255) {
+            self.quantized_transform().as_coeffs();
+        // The stored ARG_1_AND_2_ARE_WORDS flag is ignored; the argument size is
+        // always chosen from the values actually being written.
+        if let Some((x, y)) = self.match_points {
+            // Point numbers are unsigned
+            if x > u8::MAX as u16 || y > u8::MAX as u16 {
                 flags |= ComponentFlags::ARG_1_AND_2_ARE_WORDS;
             }
         } else {
             flags |= ComponentFlags::ARGS_ARE_XY_VALUES;
-            let (x, y) = (translate_x, translate_y);
-            if !((-128.0..=127.0).contains(&x) && (-128.0..=127.0).contains(&y)) {
+            let byte_range = (i8::MIN as f64)..=(i8::MAX as f64);
+            if !(byte_range.contains(&translate_x) && byte_range.contains(&translate_y)) {
                 flags |= ComponentFlags::ARG_1_AND_2_ARE_WORDS;
             }
         }
@@ -107,12 +118,71 @@ impl Component {
         }
         flags
     }
+
+    /// Returns the transformation as it will actually be stored in the font.
+    ///
+    /// The two-by-two part of a component's transform is stored as F2DOT14
+    /// values and the offset as integers, so each scale value is rounded to
+    /// the nearest multiple of 1/16384 (clamped to the F2DOT14 range) and each
+    /// offset is rounded to the nearest integer with [`otmath::ot_round`].
+    pub fn quantized_transform(&self) -> Affine {
+        let [x_scale, scale01, scale10, scale_y, translate_x, translate_y] =
+            self.transformation.as_coeffs();
+        let quantize = |v: f64| f2dot14_to_f32(f32_to_f2dot14(v as f32)) as f64;
+        Affine::new([
+            quantize(x_scale),
+            quantize(scale01),
+            quantize(scale10),
+            quantize(scale_y),
+            otmath::ot_round(translate_x) as f64,
+            otmath::ot_round(translate_y) as f64,
+        ])
+    }
+
+    /// Returns true if the component's transform is a reflection.
+    ///
+    /// A transform with a negative determinant (such as a scale of (-1, 1))
+    /// mirrors the component, which reverses the direction of its contours.
+    pub fn is_reflection(&self) -> bool {
+        self.transformation.determinant() < 0.0
+    }
+
+    /// Describes any parts of the component's transform which cannot be
+    /// stored in a font.
+    ///
+    /// The two-by-two part of the transform is stored as F2DOT14 values, so
+    /// must lie within [-2, 2); the offset is stored as 16-bit integers. Values
+    /// out of range are clamped when the component is written.
+    pub fn transform_problems(&self) -> Vec<String> {
+        let mut problems = vec![];
+        let [x_scale, scale01, scale10, scale_y, translate_x, translate_y] =
+            self.transformation.as_coeffs();
+        let max_f2dot14 = f2dot14_to_f32(i16::MAX) as f64;
+        for (name, value) in [
+            ("x scale", x_scale),
+            ("scale01", scale01),
+            ("scale10", scale10),
+            ("y scale", scale_y),
+        ] {
+            if !(-2.0..=max_f2dot14).contains(&value) {
+                problems.push(format!("{} of {} is out of the F2DOT14 range", name, value));
+            }
+        }
+        if self.match_points.is_none() {
+            let range = (i16::MIN as f64)..=(i16::MAX as f64);
+            for (name, value) in [("x offset", translate_x), ("y offset", translate_y)] {
+                if !range.contains(&(otmath::ot_round(value) as f64)) {
+                    problems.push(format!("{} of {} does not fit in 16 bits", name, value));
+                }
+            }
+        }
+        problems
+    }
 }
 
 fn read_f64_from_f2dot14(c: &mut ReaderContext) -> Result<f64, DeserializationError> {
-    let x: F2DOT14 = c.de()?;
-    let x_f32: f32 = x.into();
-    Ok(x_f32 as f64)
+    let packed: i16 = c.de()?;
+    Ok(f2dot14_to_f32(packed) as f64)
 }
 
 impl Deserialize for Component {
@@ -133,15 +203,6 @@ impl Deserialize for Component {
                 let p2: u8 = c.de()?;
                 match_points = Some((p1.into(), p2.into()));
             }
-            if flags.contains(
-                ComponentFlags::WE_HAVE_A_SCALE
-                    | ComponentFlags::WE_HAVE_AN_X_AND_Y_SCALE
-                    | ComponentFlags::WE_HAVE_A_TWO_BY_TWO,
-            ) {
-                return Err(DeserializationError(
-                    "Simon is a lazy programmer".to_string(),
-                ));
-            }
         } else {
             // signed xy values
             if flags.contains(ComponentFlags::ARG_1_AND_2_ARE_WORDS) {
@@ -214,4 +275,175 @@ mod tests {
         let serialized: Vec<u8> = otspec::ser::to_bytes(&deserialized).unwrap();
         assert_eq!(serialized, binary_glyph);
     }
+
+    fn offset_component(x: f64, y: f64) -> Component {
+        Component {
+            glyph_index: 1,
+            transformation: Affine::translate((x, y)),
+            match_points: None,
+            flags: ComponentFlags::empty(),
+        }
+    }
+
+    fn roundtrip_component(comp: Component) -> (Vec<u8>, Component) {
+        let glyph = Glyph {
+            xMin: 0,
+            xMax: 0,
+            yMin: 0,
+            yMax: 0,
+            contours: vec![],
+            instructions: vec![],
+            components: vec![comp],
+            overlap: false,
+            explicit_empty: false,
+            bounds_dirty: false,
+        };
+        let serialized: Vec<u8> = otspec::ser::to_bytes(&glyph).unwrap();
+        let deserialized: Glyph = otspec::de::from_bytes(&serialized).unwrap();
+        (serialized, deserialized.components[0].clone())
+    }
+
+    #[test]
+    fn test_component_arg_size() {
+        // 10 bytes of header, 4 bytes of flags and glyph index, then args
+        let (bytes, comp) = roundtrip_component(offset_component(127.0, -128.0));
+        assert_eq!(bytes.len(), 16);
+        assert!(!comp.flags.contains(ComponentFlags::ARG_1_AND_2_ARE_WORDS));
+        assert_eq!(comp.transformation, Affine::translate((127.0, -128.0)));
+
+        let (bytes, comp) = roundtrip_component(offset_component(128.0, 0.0));
+        assert_eq!(bytes.len(), 18);
+        assert!(comp.flags.contains(ComponentFlags::ARG_1_AND_2_ARE_WORDS));
+        assert_eq!(comp.transformation, Affine::translate((128.0, 0.0)));
+
+        let (bytes, comp) = roundtrip_component(offset_component(0.0, -129.0));
+        assert_eq!(bytes.len(), 18);
+        assert!(comp.flags.contains(ComponentFlags::ARG_1_AND_2_ARE_WORDS));
+        assert_eq!(comp.transformation, Affine::translate((0.0, -129.0)));
+
+        // A stale stored flag does not force the word form
+        let mut small = offset_component(5.0, 5.0);
+        small.flags = ComponentFlags::ARG_1_AND_2_ARE_WORDS;
+        let (bytes, _) = roundtrip_component(small);
+        assert_eq!(bytes.len(), 16);
+
+        // Half-unit offsets are rounded up, as fontTools does
+        let half = offset_component(-0.5, 2.5);
+        assert_eq!(half.quantized_transform(), Affine::translate((0.0, 3.0)));
+        let (_, comp) = roundtrip_component(half);
+        assert_eq!(comp.transformation, Affine::translate((0.0, 3.0)));
+    }
+
+    #[test]
+    fn test_component_scale_quantization() {
+        let mut comp = offset_component(10.0, 20.0);
+        comp.transformation = Affine::new([0.3333, 0.0, 0.0, 0.3333, 10.0, 20.0]);
+        let expected = 5461.0 / 16384.0;
+        assert_eq!(
+            comp.quantized_transform(),
+            Affine::new([expected, 0.0, 0.0, expected, 10.0, 20.0])
+        );
+        let (_, roundtripped) = roundtrip_component(comp.clone());
+        assert_eq!(roundtripped.transformation, comp.quantized_transform());
+        assert_eq!(
+            roundtripped.quantized_transform(),
+            roundtripped.transformation
+        );
+
+        // A scale which rounds to 1.0 is not written at all
+        comp.transformation = Affine::new([1.00001, 0.0, 0.0, 1.00001, 10.0, 20.0]);
+        let (bytes, roundtripped) = roundtrip_component(comp);
+        assert_eq!(bytes.len(), 16);
+        assert_eq!(roundtripped.transformation, Affine::translate((10.0, 20.0)));
+    }
+
+    #[test]
+    fn test_component_match_points_roundtrip() {
+        let mut comp = offset_component(0.0, 0.0);
+        comp.match_points = Some((3, 0));
+        let (bytes, roundtripped) = roundtrip_component(comp.clone());
+        let flags = u16::from_be_bytes([bytes[10], bytes[11]]);
+        assert_eq!(flags & ComponentFlags::ARGS_ARE_XY_VALUES.bits(), 0);
+        assert_eq!(bytes[14..], [3, 0]);
+        assert_eq!(roundtripped.match_points, Some((3, 0)));
+        assert_eq!(roundtripped.transformation, Affine::IDENTITY);
+        assert!(!roundtripped
+            .flags
+            .contains(ComponentFlags::ARGS_ARE_XY_VALUES));
+
+        // Point matching can be combined with a scale
+        comp.transformation = Affine::new([0.5, 0.0, 0.0, 0.75, 0.0, 0.0]);
+        let (bytes, roundtripped) = roundtrip_component(comp.clone());
+        assert_eq!(bytes.len(), 20);
+        assert_eq!(roundtripped.match_points, Some((3, 0)));
+        assert_eq!(roundtripped.transformation, comp.transformation);
+        let (rebytes, _) = roundtrip_component(roundtripped);
+        assert_eq!(rebytes, bytes);
+    }
+
+    #[test]
+    fn test_component_reflection() {
+        let mut comp = offset_component(10.0, 20.0);
+        assert!(!comp.is_reflection());
+        assert!(comp.transform_problems().is_empty());
+        comp.transformation = Affine::new([-1.0, 0.0, 0.0, 1.0, 10.0, 20.0]);
+        assert!(comp.is_reflection());
+        assert!(comp.transform_problems().is_empty());
+        // Reflecting in both axes is a rotation
+        comp.transformation = Affine::new([-1.0, 0.0, 0.0, -1.0, 10.0, 20.0]);
+        assert!(!comp.is_reflection());
+
+        comp.transformation = Affine::new([2.0, 0.0, 0.0, -2.5, 40000.0, 20.0]);
+        assert_eq!(comp.transform_problems().len(), 3);
+        comp.match_points = Some((1, 2));
+        assert_eq!(comp.transform_problems().len(), 2);
+    }
+
+    #[test]
+    fn test_component_transform_encoding() {
+        // 10 bytes of header, 4 bytes of flags and glyph index, 2 bytes of args
+        let cases = [
+            ([1.0, 0.0, 0.0, 1.0], ComponentFlags::empty(), 16),
+            ([0.5, 0.0, 0.0, 0.5], ComponentFlags::WE_HAVE_A_SCALE, 18),
+            (
+                [0.5, 0.0, 0.0, -1.0],
+                ComponentFlags::WE_HAVE_AN_X_AND_Y_SCALE,
+                20,
+            ),
+            (
+                [1.0, 0.25, -0.25, 1.0],
+                ComponentFlags::WE_HAVE_A_TWO_BY_TWO,
+                24,
+            ),
+        ];
+        let scale_flags = ComponentFlags::WE_HAVE_A_SCALE
+            | ComponentFlags::WE_HAVE_AN_X_AND_Y_SCALE
+            | ComponentFlags::WE_HAVE_A_TWO_BY_TWO;
+        for ([xx, yx, xy, yy], expected_flags, expected_len) in cases {
+            let mut comp = offset_component(10.0, 20.0);
+            comp.transformation = Affine::new([xx, yx, xy, yy, 10.0, 20.0]);
+            assert_eq!(
+                comp.recompute_flags(false, false) & scale_flags,
+                expected_flags
+            );
+            let (bytes, roundtripped) = roundtrip_component(comp.clone());
+            assert_eq!(bytes.len(), expected_len);
+            assert_eq!(roundtripped.flags & scale_flags, expected_flags);
+            assert_eq!(roundtripped.transformation, comp.transformation);
+        }
+    }
+
+    #[test]
+    fn test_component_match_point_arg_size() {
+        let mut comp = offset_component(0.0, 0.0);
+        comp.match_points = Some((255, 3));
+        let (bytes, roundtripped) = roundtrip_component(comp.clone());
+        assert_eq!(bytes.len(), 16);
+        assert_eq!(roundtripped.match_points, Some((255, 3)));
+
+        comp.match_points = Some((256, 3));
+        let (bytes, roundtripped) = roundtrip_component(comp);
+        assert_eq!(bytes.len(), 18);
+        assert_eq!(roundtripped.match_points, Some((256, 3)));
+    }
 }
@@ -1,5 +1,6 @@
 use super::Point;
 use kurbo::{PathEl, PathSeg};
+use otmath::RoundMode;
 
 /// Adds explicit oncurve points to a contour
 pub fn insert_explicit_oncurves(contour: &mut Vec<Point>) {
@@ -39,34 +40,129 @@ pub fn remove_implied_oncurves(contour: &mut Vec<Point>) {
     }
 }
 
+/// Removes redundant on-curve points from a contour
+///
+/// Consecutive on-curve points at the same position are merged, and an
+/// on-curve point is dropped if both of its neighbours are on-curve and it lies
+/// within `tolerance` units of the straight line between them. Points next to
+/// off-curve points are never removed, as this would change the curve.
+pub fn remove_collinear_points(contour: &mut Vec<Point>, tolerance: f64) {
+    let mut i: usize = 0;
+    while i < contour.len() && contour.len() > 2 {
+        let prev_ix = if i == 0 { contour.len() - 1 } else { i - 1 };
+        let next_ix = (i + 1) % contour.len();
+        let this = contour[i];
+        let prev = contour[prev_ix];
+        let next = contour[next_ix];
+        let redundant = this.on_curve
+            && prev.on_curve
+            && (this == prev || next.on_curve && is_between(prev, this, next, tolerance));
+        if !redundant {
+            i += 1;
+            continue;
+        }
+        contour.remove(i);
+        // The previous point has a new neighbour, so it may now be redundant
+        i = i.saturating_sub(1);
+    }
+}
+
+/// Whether `this` lies on the line segment from `prev` to `next`, within
+/// `tolerance` units.
+fn is_between(prev: Point, this: Point, next: Point, tolerance: f64) -> bool {
+    let p = kurbo::Point::new(prev.x.into(), prev.y.into());
+    let t = kurbo::Point::new(this.x.into(), this.y.into());
+    let n = kurbo::Point::new(next.x.into(), next.y.into());
+    let line = n - p;
+    let length_sq = line.hypot2();
+    if length_sq == 0.0 {
+        return (t - p).hypot() <= tolerance;
+    }
+    // Reject points beyond either end, which would make a spike
+    let along = (t - p).dot(line) / length_sq;
+    if !(0.0..=1.0).contains(&along) {
+        return false;
+    }
+    (t - p).cross(line).abs() / length_sq.sqrt() <= tolerance
+}
+
 /// Construct a vector of points from a `kurbo::BezPath` object
 ///
 /// Cubic paths will be converted to quadratic paths using the given error tolerance.
+/// Coordinates are truncated towards zero; see
+/// [`kurbo_contour_to_glyf_contour_with_rounding`] to round them instead.
 pub fn kurbo_contour_to_glyf_contour(kurbo_path: &kurbo::BezPath, error: f32) -> Vec<Point> {
+    bezpath_to_points(kurbo_path, error, |v| v as i16)
+}
+
+/// The default cubic-to-quadratic error tolerance for a font with the given
+/// units per em.
+///
+/// This is `units_per_em / 1000`, so one unit in a 1000-unit font and about
+/// two units in a 2048-unit font. Tighter tolerances produce many more points;
+/// looser ones visibly distort curves.
+pub fn default_curve_tolerance(units_per_em: u16) -> f32 {
+    units_per_em as f32 / 1000.0
+}
+
+/// Construct a vector of points from a `kurbo::BezPath` object, converting
+/// cubic curves to quadratics with a tolerance suited to the font's units
+/// per em.
+///
+/// If `error` is `None`, the tolerance is [`default_curve_tolerance`] for
+/// `units_per_em`; otherwise the given tolerance (in font units) is used.
+pub fn kurbo_contour_to_glyf_contour_for_upm(
+    kurbo_path: &kurbo::BezPath,
+    error: Option<f32>,
+    units_per_em: u16,
+) -> Vec<Point> {
+    kurbo_contour_to_glyf_contour(
+        kurbo_path,
+        error.unwrap_or_else(|| default_curve_tolerance(units_per_em)),
+    )
+}
+
+/// Construct a vector of points from a `kurbo::BezPath` object, rounding
+/// coordinates with the given strategy.
+///
+/// Cubic paths will be converted to quadratic paths using the given error tolerance.
+pub fn kurbo_contour_to_glyf_contour_with_rounding(
+    kurbo_path: &kurbo::BezPath,
+    error: f32,
+    rounding: RoundMode,
+) -> Vec<Point> {
+    bezpath_to_points(kurbo_path, error, |v| rounding.round(v) as i16)
+}
+
+fn bezpath_to_points(
+    kurbo_path: &kurbo::BezPath,
+    error: f32,
+    round: impl Fn(f64) -> i16,
+) -> Vec<Point> {
     let mut points: Vec<Point> = vec![];
     if let PathEl::MoveTo(pt) = kurbo_path.elements()[0] {
         points.push(Point {
-            x: pt.x as i16,
-            y: pt.y as i16,
+            x: round(pt.x),
+            y: round(pt.y),
             on_curve: true,
         });
     }
     for seg in kurbo_path.segments() {
         match seg {
             PathSeg::Line(l) => points.push(Point {
-                x: l.p1.x as i16,
-                y: l.p1.y as i16,
+                x: round(l.p1.x),
+                y: round(l.p1.y),
                 on_curve: true,
             }),
             PathSeg::Quad(q) => points.extend(vec![
                 Point {
-                    x: q.p1.x as i16,
-                    y: q.p1.y as i16,
+                    x: round(q.p1.x),
+                    y: round(q.p1.y),
                     on_curve: false,
                 },
                 Point {
-                    x: q.p2.x as i16,
-                    y: q.p2.y as i16,
+                    x: round(q.p2.x),
+                    y: round(q.p2.y),
                     on_curve: true,
                 },
             ]),
@@ -74,13 +170,13 @@ pub fn kurbo_contour_to_glyf_contour(kurbo_path: &kurbo::BezPath, error: f32) ->
                 for (_, _, q) in c.to_quads(error.into()) {
                     points.extend(vec![
                         Point {
-                            x: q.p1.x as i16,
-                            y: q.p1.y as i16,
+                            x: round(q.p1.x),
+                            y: round(q.p1.y),
                             on_curve: false,
                         },
                         Point {
-                            x: q.p2.x as i16,
-                            y: q.p2.y as i16,
+                            x: round(q.p2.x),
+                            y: round(q.p2.y),
                             on_curve: true,
                         },
                     ]);
@@ -130,3 +226,38 @@ pub fn glyf_contour_to_kurbo_contour(contour: &[Point]) -> kurbo::BezPath {
     path.close_path();
     path
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_curve_tolerance() {
+        assert_eq!(default_curve_tolerance(1000), 1.0);
+        assert_eq!(default_curve_tolerance(2048), 2.048);
+
+        let mut path = kurbo::BezPath::new();
+        path.move_to((0.0, 0.0));
+        path.curve_to((0.0, 600.0), (1000.0, 900.0), (1000.0, 0.0));
+        path.close_path();
+        let off_curves = |contour: Vec<Point>| contour.iter().filter(|p| !p.on_curve).count();
+
+        let tight = off_curves(kurbo_contour_to_glyf_contour_for_upm(
+            &path,
+            Some(0.1),
+            1000,
+        ));
+        let default = off_curves(kurbo_contour_to_glyf_contour_for_upm(&path, None, 1000));
+        let loose = off_curves(kurbo_contour_to_glyf_contour_for_upm(
+            &path,
+            Some(10.0),
+            1000,
+        ));
+        assert!(tight > default);
+        assert!(default > loose);
+        assert_eq!(
+            default,
+            off_curves(kurbo_contour_to_glyf_contour(&path, 1.0))
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use super::glyf;
+use super::glyph::Glyph;
+use super::point::Point;
+
+/// A single point which differs between two versions of a contour.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PointChange {
+    /// The index of the contour.
+    pub contour: usize,
+    /// The index of the point within the contour.
+    pub point: usize,
+    /// The point in the first table.
+    pub old: Point,
+    /// The point in the second table.
+    pub new: Point,
+}
+
+/// A way in which a glyph differs between two tables.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GlyphChange {
+    /// The glyph is only present in the second table.
+    Added,
+    /// The glyph is only present in the first table.
+    Removed,
+    /// The number of contours, or the number of points in a contour, differs.
+    ContourStructure,
+    /// The contours have the same structure, but some points differ.
+    Points(Vec<PointChange>),
+    /// The components differ.
+    Components,
+    /// The instructions differ.
+    Instructions,
+    /// The stored bounding box differs.
+    Bounds,
+}
+
+/// The differences found in a single glyph.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlyphDiff {
+    /// The glyph ID.
+    pub glyph_id: u16,
+    /// What has changed.
+    pub changes: Vec<GlyphChange>,
+}
+
+/// Compares two glyf tables glyph by glyph.
+///
+/// Only glyphs which differ are reported, in glyph ID order. If one table
+/// has more glyphs than the other, the extra glyphs are reported as added or
+/// removed.
+pub fn diff(a: &glyf, b: &glyf) -> Vec<GlyphDiff> {
+    let count = a.glyphs.len().max(b.glyphs.len());
+    (0..count)
+        .filter_map(|gid| {
+            let changes = match (a.glyphs.get(gid), b.glyphs.get(gid)) {
+                (Some(old), Some(new)) => glyph_changes(old, new),
+                (Some(_), None) => vec![GlyphChange::Removed],
+                (None, Some(_)) => vec![GlyphChange::Added],
+                (None, None) => unreachable!(),
+            };
+            if changes.is_empty() {
+                None
+            } else {
+                Some(GlyphDiff {
+                    glyph_id: gid as u16,
+                    changes,
+                })
+            }
+        })
+        .collect()
+}
+
+fn glyph_changes(old: &Glyph, new: &Glyph) -> Vec<GlyphChange> {
+    let mut changes = vec![];
+    let same_structure = old.contours.len() == new.contours.len()
+        && old
+            .contours
+            .iter()
+            .zip(new.contours.iter())
+            .all(|(o, n)| o.len() == n.len());
+    if !same_structure {
+        changes.push(GlyphChange::ContourStructure);
+    } else {
+        let points: Vec<PointChange> = old
+            .contours
+            .iter()
+            .zip(new.contours.iter())
+            .enumerate()
+            .flat_map(|(contour, (o, n))| {
+                o.iter()
+                    .zip(n.iter())
+                    .enumerate()
+                    .filter(|(_, (op, np))| op != np)
+                    .map(move |(point, (op, np))| PointChange {
+                        contour,
+                        point,
+                        old: *op,
+                        new: *np,
+                    })
+            })
+            .collect();
+        if !points.is_empty() {
+            changes.push(GlyphChange::Points(points));
+        }
+    }
+    if old.components != new.components {
+        changes.push(GlyphChange::Components);
+    }
+    if old.instructions != new.instructions {
+        changes.push(GlyphChange::Instructions);
+    }
+    if old.bounds_rect() != new.bounds_rect() {
+        changes.push(GlyphChange::Bounds);
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tables::glyf::tests::sample_glyf;
+
+    #[test]
+    fn test_diff() {
+        let a = sample_glyf();
+        assert!(diff(&a, &a).is_empty());
+
+        let mut b = a.clone();
+        let old = b.glyphs[2].contours[1][3];
+        b.glyphs[2].contours[1][3].x += 1;
+        let new = b.glyphs[2].contours[1][3];
+        assert_eq!(
+            diff(&a, &b),
+            vec![GlyphDiff {
+                glyph_id: 2,
+                changes: vec![GlyphChange::Points(vec![PointChange {
+                    contour: 1,
+                    point: 3,
+                    old,
+                    new
+                }])]
+            }]
+        );
+
+        b.glyphs[3].instructions = vec![0xb0, 0x00];
+        b.glyphs.pop();
+        let diffs = diff(&a, &b);
+        assert_eq!(diffs.len(), 3);
+        assert_eq!(diffs[1].glyph_id, 3);
+        assert_eq!(diffs[1].changes, vec![GlyphChange::Instructions]);
+        assert_eq!(
+            diffs[2],
+            GlyphDiff {
+                glyph_id: 7,
+                changes: vec![GlyphChange::Removed]
+            }
+        );
+        assert_eq!(diff(&b, &a)[2].changes, vec![GlyphChange::Added]);
+    }
+}
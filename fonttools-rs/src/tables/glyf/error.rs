@@ -0,0 +1,34 @@
+use otspec::DeserializationError;
+
+/// An error encountered while parsing a glyph in the glyf table.
+#[derive(Debug, Clone)]
+pub struct GlyfError {
+    /// The ID of the glyph which failed to parse.
+    pub glyph_id: u16,
+    /// The offset of the glyph's data from the start of the glyf table.
+    pub offset: u32,
+    /// The underlying parse error.
+    pub source: DeserializationError,
+}
+
+impl std::fmt::Display for GlyfError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "could not parse glyph {} at offset {}: {}",
+            self.glyph_id, self.offset, self.source.0
+        )
+    }
+}
+
+impl std::error::Error for GlyfError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+impl From<GlyfError> for DeserializationError {
+    fn from(e: GlyfError) -> Self {
+        DeserializationError(e.to_string())
+    }
+}
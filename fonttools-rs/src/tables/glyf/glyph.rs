@@ -1,15 +1,18 @@
 use super::component::{Component, ComponentFlags};
 use super::contourutils;
+use super::interpolatable::check_interpolatable;
 use super::point::Point;
 use bitflags::bitflags;
 use itertools::izip;
+use kurbo::{ParamCurve, ParamCurveArclen, ParamCurveNearest, PathEl, PathSeg, QuadBez, Shape};
+use otmath::RoundMode;
 use otspec::types::*;
 use otspec::{
     DeserializationError, Deserialize, Deserializer, ReaderContext, SerializationError, Serialize,
     Serializer,
 };
 use otspec_macros::{tables, Deserialize, Serialize};
-use std::cmp::max;
+use std::cmp::{max, Reverse};
 tables!(
     GlyphCore {
         int16	xMin
@@ -40,7 +43,97 @@ pub struct CompositeMaxpValues {
     pub max_depth: u16,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+/// The direction in which a contour winds.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Orientation {
+    /// The direction of outer contours in TrueType outlines.
+    Clockwise,
+    /// The direction of holes in TrueType outlines.
+    CounterClockwise,
+    /// The contour encloses no area, so has no direction.
+    Degenerate,
+}
+
+/// The kind of description used for a glyph in the glyf table.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum GlyphKind {
+    /// A glyph with contours and no components.
+    Simple,
+    /// A glyph made up of components.
+    Composite,
+    /// A glyph with no outline, which usually takes up no space in the glyf table.
+    Empty,
+}
+
+/// The accuracy, in font units, to which curve lengths are computed.
+const ARCLEN_ACCURACY: f64 = 1e-3;
+
+/// A single segment of a glyph outline.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Segment {
+    /// A straight line between two points.
+    Line(kurbo::Point, kurbo::Point),
+    /// A quadratic curve from the first point to the last, with the middle
+    /// point as its control point.
+    Quad(kurbo::Point, kurbo::Point, kurbo::Point),
+}
+
+/// Splits a single closed contour into segments, materializing implied
+/// on-curve points.
+fn contour_segments(contour: &[Point]) -> Vec<Segment> {
+    let mut segments = vec![];
+    if contour.is_empty() {
+        return segments;
+    }
+    let as_kurbo = |pt: &Point| kurbo::Point::new(pt.x.into(), pt.y.into());
+    // Start at an on-curve point, or at the implied point between the last
+    // and first points if there are none.
+    let (start, rest): (kurbo::Point, Vec<&Point>) = match contour.iter().position(|pt| pt.on_curve)
+    {
+        Some(ix) => (
+            as_kurbo(&contour[ix]),
+            contour[ix + 1..]
+                .iter()
+                .chain(contour[..ix].iter())
+                .collect(),
+        ),
+        None => (
+            as_kurbo(&contour[contour.len() - 1]).midpoint(as_kurbo(&contour[0])),
+            contour.iter().collect(),
+        ),
+    };
+    let mut current = start;
+    let mut control: Option<kurbo::Point> = None;
+    for pt in rest {
+        let p = as_kurbo(pt);
+        match (pt.on_curve, control) {
+            (true, None) => {
+                segments.push(Segment::Line(current, p));
+                current = p;
+            }
+            (true, Some(c)) => {
+                segments.push(Segment::Quad(current, c, p));
+                current = p;
+                control = None;
+            }
+            (false, None) => control = Some(p),
+            (false, Some(c)) => {
+                let implied = c.midpoint(p);
+                segments.push(Segment::Quad(current, c, implied));
+                current = implied;
+                control = Some(p);
+            }
+        }
+    }
+    match control {
+        Some(c) => segments.push(Segment::Quad(current, c, start)),
+        None if current != start => segments.push(Segment::Line(current, start)),
+        None => {}
+    }
+    segments
+}
+
+#[derive(Debug, Clone)]
 #[allow(non_snake_case)]
 /// A higher-level representation of a TrueType outline glyph.
 pub struct Glyph {
@@ -61,6 +154,46 @@ pub struct Glyph {
     /// A flag used in the low-level glyph representation to determine if this
     /// glyph has overlaps. This *appears* to be unused in OpenType implementations.
     pub overlap: bool,
+    /// Whether a glyph with no contours or components is stored as a header
+    /// declaring zero contours (which may carry instructions), rather than
+    /// as no data at all. Ignored for glyphs with an outline.
+    pub explicit_empty: bool,
+    /// Whether the stored bounds may be out of date because the contours
+    /// have been edited since they were calculated. Methods which edit the
+    /// contours set this rather than recalculating the bounds each time;
+    /// [`Glyph::bounds`] recalculates them when needed, and serialization
+    /// always writes fresh bounds for a glyph with it set. It is only
+    /// meaningful for glyphs without components. A glyph built from scratch
+    /// with a struct literal should set it to `false` if the bounds fields
+    /// are correct, or to `true` to have them calculated. This is not taken
+    /// into account when comparing glyphs.
+    pub bounds_dirty: bool,
+}
+
+impl PartialEq for Glyph {
+    fn eq(&self, other: &Self) -> bool {
+        let Glyph {
+            xMin,
+            xMax,
+            yMin,
+            yMax,
+            contours,
+            instructions,
+            components,
+            overlap,
+            explicit_empty,
+            bounds_dirty: _,
+        } = self;
+        *xMin == other.xMin
+            && *xMax == other.xMax
+            && *yMin == other.yMin
+            && *yMax == other.yMax
+            && *contours == other.contours
+            && *instructions == other.instructions
+            && *components == other.components
+            && *overlap == other.overlap
+            && *explicit_empty == other.explicit_empty
+    }
 }
 
 impl Deserialize for Glyph {
@@ -78,7 +211,17 @@ impl Deserialize for Glyph {
         });
         let mut overlap = false;
         let mut has_instructions = false;
-        if num_contours < 1 {
+        if num_contours == 0 {
+            // An empty glyph with a header; there are no end points, and no
+            // flags or coordinates after the instructions.
+            let instructions_count: uint16 = c.de()?;
+            instructions = c.de_counted(instructions_count as usize)?;
+        } else if num_contours < 0 && c.input.len() < c.ptr + 6 {
+            // A composite header with no room for a component (which takes at
+            // least six bytes) after it, other than padding
+            log::warn!("Composite glyph has no components; treating it as empty");
+            return Ok(Glyph::empty());
+        } else if num_contours < 0 {
             loop {
                 let comp: Component = c.de()?;
                 let has_more = comp.flags.contains(ComponentFlags::MORE_COMPONENTS);
@@ -93,24 +236,26 @@ impl Deserialize for Glyph {
                     break;
                 }
             }
+            // Instructions for the composite as a whole follow the last component
             if has_instructions {
-                let instructions_count: i16 = c.de()?;
-                if instructions_count > 0 {
-                    instructions = c.de_counted(instructions_count as usize)?;
-                }
+                let instructions_count: uint16 = c.de()?;
+                instructions = c.de_counted(instructions_count as usize)?;
             }
         } else {
             // println!("Reading {:?} contours", num_contours);
-            let mut end_pts_of_contour: Vec<usize> = (0..num_contours as usize)
-                .map(|_| {
-                    let x: Result<uint16, DeserializationError> = c.de();
-                    1 + (x.unwrap() as usize)
-                })
-                .collect();
-            let instructions_count: i16 = c.de()?;
-            if instructions_count > 0 {
-                instructions = c.de_counted(instructions_count as usize)?;
+            let end_pts: Vec<uint16> = c.de_counted(num_contours as usize)?;
+            // Each contour must have at least one point, so the end points
+            // must be strictly increasing.
+            if let Some(pair) = end_pts.windows(2).find(|pair| pair[1] <= pair[0]) {
+                return Err(DeserializationError(format!(
+                    "Contour end points are not increasing ({} then {})",
+                    pair[0], pair[1]
+                )));
             }
+            let mut end_pts_of_contour: Vec<usize> =
+                end_pts.iter().map(|&x| 1 + x as usize).collect();
+            let instructions_count: uint16 = c.de()?;
+            instructions = c.de_counted(instructions_count as usize)?;
             // println!("Instructions: {:?}", instructions);
             let num_points = *(end_pts_of_contour
                 .last()
@@ -202,6 +347,8 @@ impl Deserialize for Glyph {
             components,
             instructions,
             overlap,
+            explicit_empty: num_contours == 0,
+            bounds_dirty: false,
             xMax: core.xMax,
             yMax: core.yMax,
             xMin: core.xMin,
@@ -211,18 +358,336 @@ impl Deserialize for Glyph {
 }
 
 impl Glyph {
+    /// Creates an empty glyph, with no contours, components or instructions.
+    ///
+    /// Empty glyphs (such as `space`) have no data in the `glyf` table; they
+    /// serialize to zero bytes, and so are represented in the `loca` table by
+    /// two consecutive equal offsets.
+    pub fn empty() -> Self {
+        Glyph {
+            xMin: 0,
+            xMax: 0,
+            yMin: 0,
+            yMax: 0,
+            contours: vec![],
+            instructions: vec![],
+            components: vec![],
+            overlap: false,
+            explicit_empty: false,
+            bounds_dirty: false,
+        }
+    }
+
     /// Returns true if this glyph has any components
     pub fn has_components(&self) -> bool {
         !self.components.is_empty()
     }
 
     /// Returns true if this glyph has neither components nor contours
+    ///
+    /// Unless `explicit_empty` is set, an empty glyph serializes to zero
+    /// bytes, so any instructions or bounds it has are not preserved.
     pub fn is_empty(&self) -> bool {
         self.components.is_empty() && self.contours.is_empty()
     }
 
+    /// Returns true if this glyph serializes to zero bytes, and so is
+    /// represented in the `loca` table by two consecutive equal offsets.
+    pub fn has_no_data(&self) -> bool {
+        self.is_empty() && !self.explicit_empty
+    }
+
+    /// Returns true if this glyph is a composite glyph, i.e. it has at least
+    /// one component.
+    ///
+    /// A glyph with components is written as a composite glyph, and any
+    /// contours it also has are not serialized.
+    pub fn is_composite(&self) -> bool {
+        !self.components.is_empty()
+    }
+
+    /// Returns true if this glyph is a simple glyph, i.e. it has contours and
+    /// no components.
+    pub fn is_simple(&self) -> bool {
+        !self.contours.is_empty() && self.components.is_empty()
+    }
+
+    /// Returns which kind of glyph description this glyph will be written as.
+    pub fn kind(&self) -> GlyphKind {
+        if self.is_composite() {
+            GlyphKind::Composite
+        } else if self.is_simple() {
+            GlyphKind::Simple
+        } else {
+            GlyphKind::Empty
+        }
+    }
+
+    /// Compares the outlines of two glyphs, ignoring their stored bounds.
+    ///
+    /// Contours, components (in order) and instructions are compared; the
+    /// `xMin`/`xMax`/`yMin`/`yMax` fields and the overlap flag are not.
+    pub fn outline_eq(&self, other: &Glyph) -> bool {
+        self.contours == other.contours
+            && self.components == other.components
+            && self.instructions == other.instructions
+    }
+
+    /// Compares the outlines of two glyphs, allowing points to have moved by
+    /// up to `tolerance` font units.
+    ///
+    /// The glyphs must have the same structure: the same number of contours,
+    /// each with the same number of points, with matching on-curve flags.
+    /// Corresponding points must be no further than `tolerance` apart.
+    /// Components must refer to the same glyphs with the same match points
+    /// and the same two-by-two transform as it would be stored in the font
+    /// (see [`Component::quantized_transform`]); their offsets must be within
+    /// `tolerance` of each other. Stored bounds, flags and instructions are
+    /// ignored. This is useful for checking conversions which are not exact,
+    /// such as round trips through curve formats.
+    pub fn approx_eq(&self, other: &Glyph, tolerance: f64) -> bool {
+        let close = |x0: f64, y0: f64, x1: f64, y1: f64| (x1 - x0).hypot(y1 - y0) <= tolerance;
+        self.contours.len() == other.contours.len()
+            && self
+                .contours
+                .iter()
+                .zip(other.contours.iter())
+                .all(|(a, b)| {
+                    a.len() == b.len()
+                        && a.iter().zip(b.iter()).all(|(p, q)| {
+                            p.on_curve == q.on_curve
+                                && close(p.x.into(), p.y.into(), q.x.into(), q.y.into())
+                        })
+                })
+            && self.components.len() == other.components.len()
+            && self
+                .components
+                .iter()
+                .zip(other.components.iter())
+                .all(|(a, b)| {
+                    let linear = |c: &Component| {
+                        let [xx, yx, xy, yy, _, _] = c.quantized_transform().as_coeffs();
+                        [xx, yx, xy, yy]
+                    };
+                    let [.., a_dx, a_dy] = a.transformation.as_coeffs();
+                    let [.., b_dx, b_dy] = b.transformation.as_coeffs();
+                    a.glyph_index == b.glyph_index
+                        && a.match_points == b.match_points
+                        && linear(a) == linear(b)
+                        && close(a_dx, a_dy, b_dx, b_dy)
+                })
+    }
+
+    /// Returns a hash of this glyph's outline, ignoring its stored bounds.
+    ///
+    /// Contours, components (glyph ID, transformation, match points and flags)
+    /// and instructions are hashed, so glyphs which are [`Glyph::outline_eq`]
+    /// hash identically. The hash function is fixed, so the value is stable
+    /// across runs and platforms and may be used as a persistent cache key.
+    pub fn outline_hash(&self) -> u64 {
+        let mut hasher = StableHasher::default();
+        hasher.write(&(self.contours.len() as u64).to_le_bytes());
+        for contour in &self.contours {
+            hasher.write(&(contour.len() as u64).to_le_bytes());
+            for pt in contour {
+                hasher.write(&pt.x.to_le_bytes());
+                hasher.write(&pt.y.to_le_bytes());
+                hasher.write(&[pt.on_curve as u8]);
+            }
+        }
+        hasher.write(&(self.components.len() as u64).to_le_bytes());
+        for comp in &self.components {
+            hasher.write(&comp.glyph_index.to_le_bytes());
+            for coeff in comp.transformation.as_coeffs() {
+                // Adding zero turns -0.0 into 0.0, which compare equal
+                hasher.write(&(coeff + 0.0).to_bits().to_le_bytes());
+            }
+            match comp.match_points {
+                Some((a, b)) => {
+                    hasher.write(&[1]);
+                    hasher.write(&a.to_le_bytes());
+                    hasher.write(&b.to_le_bytes());
+                }
+                None => hasher.write(&[0]),
+            }
+            hasher.write(&comp.flags.bits().to_le_bytes());
+        }
+        hasher.write(&(self.instructions.len() as u64).to_le_bytes());
+        hasher.write(&self.instructions);
+        hasher.0
+    }
+
+    /// Checks that the glyph's components can be written to a font.
+    ///
+    /// At most one component may set the `USE_MY_METRICS` flag, as the
+    /// glyph's metrics are otherwise undefined. This is checked when the
+    /// glyph is serialized.
+    pub fn validate_components(&self) -> Result<(), String> {
+        let metrics_components: Vec<usize> = self
+            .components
+            .iter()
+            .enumerate()
+            .filter(|(_, comp)| comp.flags.contains(ComponentFlags::USE_MY_METRICS))
+            .map(|(ix, _)| ix)
+            .collect();
+        if metrics_components.len() > 1 {
+            return Err(format!(
+                "Components {:?} all set USE_MY_METRICS, but only one may",
+                metrics_components
+            ));
+        }
+        Ok(())
+    }
+
+    /// Describes any inconsistencies between the stored flags of the glyph's
+    /// components and their positions in the glyph.
+    ///
+    /// Every component but the last must set `MORE_COMPONENTS`, and the last
+    /// must not; only the last may set `WE_HAVE_INSTRUCTIONS`, and only if
+    /// the glyph has instructions. These flags are always recomputed when the
+    /// glyph is serialized, so inconsistent flags are not written to a font;
+    /// this is for checking glyphs which have been built or edited by hand.
+    pub fn component_flag_problems(&self) -> Vec<String> {
+        let mut problems = vec![];
+        let last = self.components.len().saturating_sub(1);
+        for (ix, comp) in self.components.iter().enumerate() {
+            let more = comp.flags.contains(ComponentFlags::MORE_COMPONENTS);
+            if ix < last && !more {
+                problems.push(format!(
+                    "Component {} does not set MORE_COMPONENTS, but is not the last",
+                    ix
+                ));
+            } else if ix == last && more {
+                problems.push(format!(
+                    "Component {} sets MORE_COMPONENTS, but is the last",
+                    ix
+                ));
+            }
+            if comp.flags.contains(ComponentFlags::WE_HAVE_INSTRUCTIONS) {
+                if ix < last {
+                    problems.push(format!(
+                        "Component {} sets WE_HAVE_INSTRUCTIONS, but is not the last",
+                        ix
+                    ));
+                } else if self.instructions.is_empty() {
+                    problems.push(format!(
+                        "Component {} sets WE_HAVE_INSTRUCTIONS, but the glyph has no instructions",
+                        ix
+                    ));
+                }
+            }
+        }
+        problems
+    }
+
+    /// Describes any problems with the transforms of the glyph's components.
+    ///
+    /// This reports transforms which cannot be stored in a font (see
+    /// [`Component::transform_problems`]), and components which reflect a
+    /// base glyph (looked up in `glyphs`) whose direction has not been
+    /// corrected for it. Decomposing a reflected component copies its
+    /// contours without correcting their direction, so unless the base glyph
+    /// was drawn to allow for the reflection, they wind the opposite way to
+    /// the rest of the glyph, which can invert the fill where they meet other
+    /// contours.
+    pub fn component_transform_problems(&self, glyphs: &[Glyph]) -> Vec<String> {
+        let mut problems = vec![];
+        for (ix, comp) in self.components.iter().enumerate() {
+            for problem in comp.transform_problems() {
+                problems.push(format!("Component {}: {}", ix, problem));
+            }
+            let base = glyphs.get(comp.glyph_index as usize);
+            if base.is_some_and(|base| reflection_reverses_direction(comp, base)) {
+                problems.push(format!(
+                    "Component {} reflects glyph {}, reversing the direction of its contours",
+                    ix, comp.glyph_index
+                ));
+            }
+        }
+        problems
+    }
+
+    /// Returns true if the stored flags of the glyph's components are
+    /// consistent with their positions (see [`Glyph::component_flag_problems`]).
+    pub fn is_flag_consistent(&self) -> bool {
+        self.component_flag_problems().is_empty()
+    }
+
+    /// Returns a one-line description of this glyph, for use in log messages.
+    ///
+    /// This gives the number of contours, points and components and the
+    /// bounds, rather than every point as the `Debug` output does.
+    pub fn summary(&self) -> String {
+        let bounds = self.bounds_rect();
+        format!(
+            "{} contours, {} points, {} components, bounds ({}, {}, {}, {})",
+            self.contours.len(),
+            self.contours.iter().map(|c| c.len()).sum::<usize>(),
+            self.components.len(),
+            bounds.min_x(),
+            bounds.min_y(),
+            bounds.max_x(),
+            bounds.max_y()
+        )
+    }
+
+    /// Removes any TrueType instructions from this glyph.
+    ///
+    /// The outline is left unchanged; for composite glyphs, the
+    /// `WE_HAVE_INSTRUCTIONS` component flag is also cleared.
+    pub fn strip_instructions(&mut self) {
+        self.instructions.clear();
+        for comp in self.components.iter_mut() {
+            comp.flags.remove(ComponentFlags::WE_HAVE_INSTRUCTIONS);
+        }
+    }
+
+    /// Rotates each contour to begin at a canonical point.
+    ///
+    /// The new start point is the topmost on-curve point of the contour, or
+    /// the leftmost of these if there are several. Since contours are closed,
+    /// starting at an on-curve point leaves the shape unchanged. This lets
+    /// glyphs which differ only in their start points be compared and
+    /// interpolated. Contours without on-curve points are left alone.
+    ///
+    /// Any instructions which refer to point numbers will no longer be valid.
+    pub fn set_start_points(&mut self) {
+        for contour in self.contours.iter_mut() {
+            let start = contour
+                .iter()
+                .enumerate()
+                .filter(|(_, pt)| pt.on_curve)
+                .min_by_key(|(_, pt)| (Reverse(pt.y), pt.x))
+                .map(|(ix, _)| ix);
+            if let Some(start) = start {
+                contour.rotate_left(start);
+            }
+        }
+    }
+
+    /// Returns the bounding box of the glyph's ink, computed from its points.
+    ///
+    /// Unlike [`Glyph::bounds_rect`], this does not rely on the stored bounds
+    /// being up to date. A composite glyph's ink depends on the glyphs it
+    /// uses, so its stored bounds are returned instead; see
+    /// [`glyf::glyph_bounds`](super::glyf::glyph_bounds) to compute them.
+    pub fn ink_bounds(&self) -> kurbo::Rect {
+        if self.has_components() {
+            self.bounds_rect()
+        } else {
+            super::simple_bounds(self)
+        }
+    }
+
     /// Returns a bounding box rectangle for this glyph as a `kurbo::Rect`.
+    ///
+    /// If the contours have been edited since the bounds were last
+    /// calculated, the bounds are calculated from the contours without being
+    /// stored; use [`Glyph::bounds`] to store them as well.
     pub fn bounds_rect(&self) -> kurbo::Rect {
+        if self.bounds_dirty {
+            return super::simple_bounds(self);
+        }
         kurbo::Rect::new(
             self.xMin.into(),
             self.yMin.into(),
@@ -230,12 +695,36 @@ impl Glyph {
             self.yMax.into(),
         )
     }
+
+    /// Returns the bounding box of this glyph, first recalculating and
+    /// storing it if the contours have been edited since it was last
+    /// calculated.
+    ///
+    /// This means that several edits in a row cost only one recalculation.
+    /// As with [`Glyph::set_bounds_rect`], the bounds are clamped to the
+    /// range of an `i16`.
+    pub fn bounds(&mut self) -> kurbo::Rect {
+        if self.bounds_dirty {
+            #[cfg(test)]
+            tests::BOUNDS_RECALCULATIONS.with(|n| n.set(n.get() + 1));
+            self.set_bounds_rect(super::simple_bounds(self));
+        }
+        self.bounds_rect()
+    }
+
     /// Sets the bounding box rectangle for this glyph from a `kurbo::Rect`.
+    ///
+    /// The bounds are stored as 16-bit integers, so any edge beyond the
+    /// range of an `i16` (for example, a component positioned far from the
+    /// origin) is clamped to it, and a warning is logged. The bounds are
+    /// then no longer considered out of date.
     pub fn set_bounds_rect(&mut self, r: kurbo::Rect) {
-        self.xMin = r.min_x() as i16;
-        self.xMax = r.max_x() as i16;
-        self.yMin = r.min_y() as i16;
-        self.yMax = r.max_y() as i16;
+        let [x_min, y_min, x_max, y_max] = clamp_bounds(r);
+        self.xMin = x_min;
+        self.xMax = x_max;
+        self.yMin = y_min;
+        self.yMax = y_max;
+        self.bounds_dirty = false;
     }
 
     /// Assuming that the contour list has been expanded into a flat list of
@@ -265,154 +754,822 @@ impl Glyph {
             contourutils::insert_explicit_oncurves(contour);
         }
     }
-    fn _compile_deltas_greedy(&self) -> (Vec<u8>, Vec<u8>, Vec<u8>) {
-        assert!(!self.has_components());
-        let mut last_x = 0;
-        let mut last_y = 0;
-        let mut compressed_flags: Vec<u8> = vec![];
-        let mut compressed_xs: Vec<u8> = vec![];
-        let mut compressed_ys: Vec<u8> = vec![];
-        for point in self.contours.iter().flatten() {
-            let mut x = point.x - last_x;
-            let mut y = point.y - last_y;
-            let mut flag = if point.on_curve {
-                SimpleGlyphFlags::ON_CURVE_POINT
-            } else {
-                SimpleGlyphFlags::empty()
-            };
-            if x == 0 {
-                flag |= SimpleGlyphFlags::X_IS_SAME_OR_POSITIVE_X_SHORT_VECTOR
-            } else if -255 <= x && x <= 255 {
-                flag |= SimpleGlyphFlags::X_SHORT_VECTOR;
-                if x > 0 {
-                    flag |= SimpleGlyphFlags::X_IS_SAME_OR_POSITIVE_X_SHORT_VECTOR
-                } else {
-                    x = -x;
-                }
-                compressed_xs.push(x as u8);
-            } else {
-                compressed_xs.extend(&i16::to_be_bytes(x as i16));
-            }
-            if y == 0 {
-                flag |= SimpleGlyphFlags::Y_IS_SAME_OR_POSITIVE_Y_SHORT_VECTOR
-            } else if -255 <= y && y <= 255 {
-                flag |= SimpleGlyphFlags::Y_SHORT_VECTOR;
-                if y > 0 {
-                    flag |= SimpleGlyphFlags::Y_IS_SAME_OR_POSITIVE_Y_SHORT_VECTOR
-                } else {
-                    y = -y;
-                }
-                compressed_ys.push(y as u8);
-            } else {
-                compressed_ys.extend(&i16::to_be_bytes(y as i16));
-            }
-            /* Not gonna do repeating flags today */
-            compressed_flags.push(flag.bits());
-
-            last_x = point.x;
-            last_y = point.y;
+    /// Removes redundant on-curve points from the glyph's contours.
+    ///
+    /// Consecutive duplicate on-curve points are merged, and on-curve points
+    /// lying within `tolerance` units of the straight line between their two
+    /// on-curve neighbours are dropped. Curves are left unchanged.
+    pub fn remove_collinear_points(&mut self, tolerance: f64) {
+        for contour in self.contours.iter_mut() {
+            contourutils::remove_collinear_points(contour, tolerance);
         }
-        (compressed_flags, compressed_xs, compressed_ys)
     }
-
-    /// Decomposes components in this glyph (but not recursively)
-    pub fn decompose(&self, glyphs: &[Glyph]) -> Glyph {
-        let mut newglyph = Glyph {
-            xMin: 0,
-            xMax: 0,
-            yMin: 0,
-            yMax: 0,
-            instructions: vec![],
-            overlap: self.overlap,
-            contours: vec![],
-            components: vec![],
-        };
-        let mut new_contours = vec![];
-        new_contours.extend(self.contours.clone());
-        for comp in &self.components {
-            let ix = comp.glyph_index;
-            match glyphs.get(ix as usize) {
-                None => {
-                    log::error!("Component not found for ID={:?}", ix);
-                }
-                Some(other_glyph) => {
-                    for c in &other_glyph.contours {
-                        new_contours.push(
-                            c.iter()
-                                .map(|pt| pt.transform(comp.transformation))
-                                .collect(),
-                        );
-                    }
-                    if other_glyph.has_components() {
-                        log::warn!("Found nested components while decomposing");
-                    }
-                }
+    /// Removes a final point which repeats the first point of its contour.
+    ///
+    /// Formats with open paths often close a contour by repeating its start
+    /// point, but TrueType contours are implicitly closed, so the repeated
+    /// point only adds a zero-length segment. Only exact duplicates (same
+    /// position and on-curve flag) are removed.
+    pub fn dedupe_contour_endpoints(&mut self) {
+        for contour in self.contours.iter_mut() {
+            while contour.len() > 1 && contour.first() == contour.last() {
+                contour.pop();
             }
         }
-        if !new_contours.is_empty() {
-            newglyph.contours = new_contours;
+    }
+    /// Appends a contour to the glyph.
+    ///
+    /// Contours must have at least two points; shorter contours are rejected
+    /// and the glyph is left unchanged. For glyphs without components, the
+    /// bounds are marked as out of date (see [`Glyph::bounds`]).
+    pub fn push_contour(&mut self, contour: Vec<Point>) -> Result<(), String> {
+        if contour.len() < 2 {
+            return Err(format!(
+                "A contour needs at least two points, but this one has {}",
+                contour.len()
+            ));
         }
-        newglyph
+        self.contours.push(contour);
+        self.update_contour_bounds();
+        Ok(())
     }
 
-    /// Produces a tuple made up of a list of X/Y coordinates and a list
-    /// of ends-of-contour indices, suitable for use when constructing a
-    /// `gvar` table.
-    pub fn gvar_coords_and_ends(&self) -> (Vec<(int16, int16)>, Vec<usize>) {
-        let mut ends: Vec<usize> = self
-            .contours
-            .iter()
-            .map(|c| c.len())
-            .scan(0, |acc, x| {
-                *acc += x;
-                Some(*acc - 1)
-            })
-            .collect();
+    /// Removes the contour at `index` from the glyph and returns it.
+    ///
+    /// For glyphs without components, the bounds are marked as out of date.
+    /// Panics if `index` is out of range.
+    pub fn remove_contour(&mut self, index: usize) -> Vec<Point> {
+        let contour = self.contours.remove(index);
+        self.update_contour_bounds();
+        contour
+    }
+
+    /// Keeps only the contours for which `f` returns true, like
+    /// [`Vec::retain`].
+    ///
+    /// This is useful for removing degenerate contours, such as those with
+    /// no area. For glyphs without components, the bounds are marked as out
+    /// of date.
+    pub fn retain_contours<F: Fn(&[Point]) -> bool>(&mut self, f: F) {
+        self.contours.retain(|contour| f(contour));
+        self.update_contour_bounds();
+    }
+
+    /// Swaps the contours at indices `a` and `b`.
+    ///
+    /// The outline is unchanged, but point numbers are, so any instructions
+    /// or variation data which refer to them will no longer be valid.
+    /// Panics if either index is out of range.
+    pub fn swap_contours(&mut self, a: usize, b: usize) {
+        self.contours.swap(a, b);
+    }
 
-        let mut coords: Vec<(i16, i16)> = self
+    /// Inserts a point into the contour at index `contour`, directly after
+    /// the point at index `after`.
+    ///
+    /// For glyphs without components, the bounds are marked as out of date.
+    /// Any instructions or variation data which refer to point numbers will no
+    /// longer be valid.
+    pub fn insert_point(&mut self, contour: usize, after: usize, pt: Point) -> Result<(), String> {
+        let points = self
             .contours
-            .iter()
-            .flatten()
-            .map(|pt| (pt.x, pt.y))
-            .collect();
-        for comp in &self.components {
-            let [_, _, _, _, translate_x, translate_y] = comp.transformation.as_coeffs();
-            coords.push((translate_x as i16, translate_y as i16));
-            ends.push(ends.iter().max().map(|x| x + 1).unwrap_or(0));
+            .get_mut(contour)
+            .ok_or_else(|| format!("Contour {} not found", contour))?;
+        if after >= points.len() {
+            return Err(format!(
+                "Point {} not found in contour {} of {} points",
+                after,
+                contour,
+                points.len()
+            ));
         }
+        points.insert(after + 1, pt);
+        self.update_contour_bounds();
+        Ok(())
+    }
 
-        // Phantom points
-        let left_side_x = 0; // XXX WRONG
-        let right_side_x = 0;
-        let top_side_y = 0;
-        let bottom_side_y = 0;
-        coords.push((left_side_x, 0));
-        ends.push(ends.iter().max().map(|x| x + 1).unwrap_or(0));
-        coords.push((right_side_x, 0));
-        ends.push(ends.iter().max().unwrap() + 1);
-        coords.push((0, top_side_y));
-        ends.push(ends.iter().max().unwrap() + 1);
-        coords.push((0, bottom_side_y));
-        ends.push(ends.iter().max().unwrap() + 1);
-        assert_eq!(
-            *ends.last().unwrap(),
-            coords.len() - 1,
-            "Coords: {:?}\nEnds: {:?}\nGlyf: {:#?}",
-            coords,
-            ends,
-            self
-        );
-        (coords, ends)
+    /// Deletes the point at `index` from the contour at index `contour`,
+    /// returning it.
+    ///
+    /// If the point is on-curve and both its neighbours are off-curve, it is
+    /// replaced by an explicit on-curve point at their midpoint, which is
+    /// where the curve would otherwise join them implicitly. A contour must
+    /// keep at least two points; use [`Glyph::remove_contour`] to delete it
+    /// altogether. For glyphs without components, the bounds are marked as
+    /// out of date.
+    pub fn delete_point(&mut self, contour: usize, index: usize) -> Result<Point, String> {
+        let points = self
+            .contours
+            .get_mut(contour)
+            .ok_or_else(|| format!("Contour {} not found", contour))?;
+        if index >= points.len() {
+            return Err(format!(
+                "Point {} not found in contour {} of {} points",
+                index,
+                contour,
+                points.len()
+            ));
+        }
+        if points.len() <= 2 {
+            return Err(format!(
+                "Contour {} would have fewer than two points",
+                contour
+            ));
+        }
+        let len = points.len();
+        let prev = points[(index + len - 1) % len];
+        let next = points[(index + 1) % len];
+        let removed = points[index];
+        if removed.on_curve && !prev.on_curve && !next.on_curve {
+            points[index] = Point {
+                x: otmath::ot_round((f64::from(prev.x) + f64::from(next.x)) / 2.0) as i16,
+                y: otmath::ot_round((f64::from(prev.y) + f64::from(next.y)) / 2.0) as i16,
+                on_curve: true,
+            };
+        } else {
+            points.remove(index);
+        }
+        self.update_contour_bounds();
+        Ok(removed)
     }
 
-    /// Number of points in this glyph (without counting components)
-    pub fn num_points(&self) -> usize {
-        self.contours.iter().map(|x| x.len()).sum()
+    /// Appends the contours of a `kurbo::BezPath` to the glyph.
+    ///
+    /// Each subpath becomes a new contour after any existing ones. Cubic
+    /// curves are converted to quadratics with a tolerance of one font unit;
+    /// use [`contourutils::kurbo_contour_to_glyf_contour`] and
+    /// [`Glyph::push_contour`] directly for a different tolerance. Every
+    /// subpath must be closed, or an error is returned and the glyph is left
+    /// unchanged.
+    pub fn add_contour_from_bezpath(&mut self, path: &kurbo::BezPath) -> Result<(), String> {
+        let mut subpaths: Vec<kurbo::BezPath> = vec![];
+        for el in path.elements() {
+            if let PathEl::MoveTo(_) = el {
+                subpaths.push(kurbo::BezPath::new());
+            }
+            subpaths
+                .last_mut()
+                .ok_or_else(|| "Path does not begin with a move".to_string())?
+                .push(*el);
+        }
+        let mut contours = vec![];
+        for (ix, subpath) in subpaths.iter().enumerate() {
+            if subpath.elements().last() != Some(&PathEl::ClosePath) {
+                return Err(format!("Subpath {} is not closed", ix));
+            }
+            let mut contour = contourutils::kurbo_contour_to_glyf_contour(subpath, 1.0);
+            // The closing segment repeats the start point
+            while contour.len() > 1 && contour.first() == contour.last() {
+                contour.pop();
+            }
+            if contour.len() < 2 {
+                return Err(format!("Subpath {} has fewer than two points", ix));
+            }
+            contours.push(contour);
+        }
+        self.contours.extend(contours);
+        self.update_contour_bounds();
+        Ok(())
     }
 
-    /// Number of contours in this glyph (without counting components)
-    pub fn num_contours(&self) -> usize {
-        self.contours.len()
+    /// Marks the bounds of a glyph without components as out of date, after
+    /// its contours have been edited.
+    fn update_contour_bounds(&mut self) {
+        if !self.has_components() {
+            self.bounds_dirty = true;
+        }
+    }
+
+    /// Mirrors the glyph horizontally around the vertical line `x = pivot`.
+    ///
+    /// Mirroring reverses the direction of every contour, so each contour is
+    /// reversed afterwards (keeping its start point) to preserve the winding
+    /// direction. Components are mirrored by adjusting their transformations.
+    /// The bounds are mirrored to match for a composite glyph, and otherwise
+    /// marked as out of date (see [`Glyph::bounds`]).
+    pub fn flip_x(&mut self, pivot: i16) {
+        let pivot = f64::from(pivot);
+        self.mirror(kurbo::Affine::new([-1.0, 0.0, 0.0, 1.0, 2.0 * pivot, 0.0]));
+    }
+
+    /// Mirrors the glyph vertically around the horizontal line `y = pivot`.
+    ///
+    /// As with [`Glyph::flip_x`], contour directions are corrected and the
+    /// bounds are updated or marked as out of date.
+    pub fn flip_y(&mut self, pivot: i16) {
+        let pivot = f64::from(pivot);
+        self.mirror(kurbo::Affine::new([1.0, 0.0, 0.0, -1.0, 0.0, 2.0 * pivot]));
+    }
+
+    fn mirror(&mut self, t: kurbo::Affine) {
+        for contour in self.contours.iter_mut() {
+            for pt in contour.iter_mut() {
+                *pt = pt.transform(t);
+            }
+            if contour.len() > 1 {
+                contour[1..].reverse();
+            }
+        }
+        for comp in self.components.iter_mut() {
+            comp.transformation = t * comp.transformation;
+        }
+        self.transform_bounds(t);
+    }
+
+    /// Updates the bounds after the whole glyph has been transformed by `t`.
+    ///
+    /// The bounds of a glyph without components are marked as out of date,
+    /// to be recalculated from its contours when next needed; those of a
+    /// composite, which cannot be recalculated from the glyph alone, are
+    /// transformed.
+    fn transform_bounds(&mut self, t: kurbo::Affine) {
+        if self.has_components() {
+            let bounds = t.transform_rect_bbox(self.bounds_rect());
+            self.set_bounds_rect(bounds);
+        } else {
+            self.bounds_dirty = true;
+        }
+    }
+
+    /// Scales the offsets of the glyph's components by `factor`.
+    ///
+    /// Only the translation part of each component's transformation is
+    /// scaled; the 2x2 linear part is left alone. This is what is needed
+    /// when the glyphs a composite refers to are scaled themselves, as with
+    /// a change of `unitsPerEm`, since scaling the linear part would scale
+    /// the components twice. Offsets are rounded to whole units. Components
+    /// positioned by matching points are unchanged.
+    pub fn scale_component_offsets(&mut self, factor: f64) {
+        for comp in self.components.iter_mut() {
+            if comp.match_points.is_some() {
+                continue;
+            }
+            let mut coeffs = comp.transformation.as_coeffs();
+            coeffs[4] = f64::from(otmath::ot_round(coeffs[4] * factor));
+            coeffs[5] = f64::from(otmath::ot_round(coeffs[5] * factor));
+            comp.transformation = kurbo::Affine::new(coeffs);
+        }
+    }
+
+    /// Scales the glyph from a font with `from` units per em to one with `to`.
+    ///
+    /// The points of contours are scaled and rounded, and component offsets
+    /// are scaled with [`Glyph::scale_component_offsets`], leaving their
+    /// scale factors unchanged. The bounds of a composite glyph are scaled to
+    /// match, and those of other glyphs are marked as out of date.
+    /// Instructions are kept as they are.
+    pub fn scale_to_upm(&mut self, from: u16, to: u16) {
+        let factor = f64::from(to) / f64::from(from);
+        let t = kurbo::Affine::scale(factor);
+        for pt in self.contours.iter_mut().flatten() {
+            *pt = pt.transform(t);
+        }
+        self.scale_component_offsets(factor);
+        self.transform_bounds(t);
+    }
+
+    /// Moves the whole glyph by `dx` and `dy` units.
+    ///
+    /// Every contour point and the offset of every component (other than
+    /// those positioned by matching points) is moved. The bounds of a
+    /// composite glyph are shifted by the same amount, and those of other
+    /// glyphs are marked as out of date. Coordinates which would overflow are
+    /// clamped to the `i16` range.
+    pub fn translate(&mut self, dx: i16, dy: i16) {
+        for pt in self.contours.iter_mut().flatten() {
+            pt.x = pt.x.saturating_add(dx);
+            pt.y = pt.y.saturating_add(dy);
+        }
+        let shift = kurbo::Affine::translate((f64::from(dx), f64::from(dy)));
+        for comp in self.components.iter_mut() {
+            if comp.match_points.is_none() {
+                comp.transformation = shift * comp.transformation;
+            }
+        }
+        if self.has_components() {
+            self.xMin = self.xMin.saturating_add(dx);
+            self.xMax = self.xMax.saturating_add(dx);
+            self.yMin = self.yMin.saturating_add(dy);
+            self.yMax = self.yMax.saturating_add(dy);
+        } else {
+            self.bounds_dirty = true;
+        }
+    }
+
+    /// Iterates over the segments of the glyph's contours, contour by contour.
+    ///
+    /// Implied on-curve points between consecutive off-curve points are
+    /// materialized, and each contour is closed with a final segment back to
+    /// its start. Components are not included; decompose the glyph first if
+    /// you need them.
+    pub fn segments(&self) -> impl Iterator<Item = Segment> + '_ {
+        self.contours
+            .iter()
+            .flat_map(|contour| contour_segments(contour))
+    }
+
+    /// Approximates each contour by a closed polygon, for tessellation.
+    ///
+    /// Curves are subdivided into line segments which stay within
+    /// `tolerance` units of the true outline, so larger tolerances give fewer
+    /// vertices. Each polygon starts at an on-curve point and ends by
+    /// repeating its first vertex. Components are not included; use
+    /// [`glyf::flatten_to_polygons`](super::glyf::flatten_to_polygons) to
+    /// include them.
+    pub fn flatten_to_polygons(&self, tolerance: f64) -> Vec<Vec<(f64, f64)>> {
+        self.contours
+            .iter()
+            .filter_map(|contour| {
+                let segments = contour_segments(contour);
+                let start = match segments.first()? {
+                    Segment::Line(p0, _) | Segment::Quad(p0, _, _) => *p0,
+                };
+                let mut elements = vec![PathEl::MoveTo(start)];
+                elements.extend(segments.iter().map(|segment| match *segment {
+                    Segment::Line(_, p1) => PathEl::LineTo(p1),
+                    Segment::Quad(_, p1, p2) => PathEl::QuadTo(p1, p2),
+                }));
+                let mut polygon = vec![];
+                kurbo::flatten(elements, tolerance, |el| {
+                    if let PathEl::MoveTo(p) | PathEl::LineTo(p) = el {
+                        polygon.push((p.x, p.y));
+                    }
+                });
+                if polygon.last() != polygon.first() {
+                    polygon.push(polygon[0]);
+                }
+                Some(polygon)
+            })
+            .collect()
+    }
+
+    /// Returns the total length of the outline of the contour at `index`.
+    ///
+    /// Implied on-curve points are materialized and the closing segment is
+    /// included; curve lengths are accurate to within 0.001 units. Panics if
+    /// `index` is out of range.
+    pub fn contour_length(&self, index: usize) -> f64 {
+        contour_segments(&self.contours[index])
+            .iter()
+            .map(|segment| match *segment {
+                Segment::Line(p0, p1) => p0.distance(p1),
+                Segment::Quad(p0, p1, p2) => QuadBez::new(p0, p1, p2).arclen(ARCLEN_ACCURACY),
+            })
+            .sum()
+    }
+
+    /// Returns the total length of the outlines of all the glyph's contours.
+    ///
+    /// Components are not included; decompose the glyph first if you need
+    /// them.
+    pub fn perimeter(&self) -> f64 {
+        (0..self.contours.len())
+            .map(|ix| self.contour_length(ix))
+            .sum()
+    }
+
+    /// Determines whether a point lies within the filled area of the glyph,
+    /// using the non-zero winding rule.
+    ///
+    /// Points lying exactly on the outline are considered to be inside.
+    /// Components are not included; decompose the glyph first if you need
+    /// them.
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        let pt = kurbo::Point::new(x, y);
+        let mut path = kurbo::BezPath::new();
+        let mut last: Option<kurbo::Point> = None;
+        for segment in self.segments() {
+            let (start, el) = match segment {
+                Segment::Line(p0, p1) => (p0, PathEl::LineTo(p1)),
+                Segment::Quad(p0, p1, p2) => (p0, PathEl::QuadTo(p1, p2)),
+            };
+            if last != Some(start) {
+                path.move_to(start);
+            }
+            path.push(el);
+            last = match el {
+                PathEl::LineTo(p) | PathEl::QuadTo(_, p) => Some(p),
+                _ => None,
+            };
+        }
+        if path
+            .segments()
+            .any(|seg| seg.nearest(pt, 1e-9).distance_sq < 1e-12)
+        {
+            return true;
+        }
+        path.winding(pt) != 0
+    }
+
+    /// Determines whether the glyph's contours overlap each other or
+    /// themselves.
+    ///
+    /// Curves are approximated by short line segments, and the glyph is
+    /// reported as overlapping if any two non-adjacent segments intersect, or
+    /// if one contour lies inside another contour which winds in the same
+    /// direction. This can be used to set the `overlap` flag for rasterizers.
+    /// Components are ignored; use [`super::glyf::detect_overlap`] to check
+    /// composite glyphs.
+    pub fn detect_overlap(&self) -> bool {
+        let polygons: Vec<Vec<kurbo::Line>> = self
+            .contours
+            .iter()
+            .map(|c| flatten_contour(c))
+            .filter(|lines| !lines.is_empty())
+            .collect();
+        for (i, a) in polygons.iter().enumerate() {
+            for (j, b) in polygons.iter().enumerate().skip(i) {
+                for (ia, la) in a.iter().enumerate() {
+                    for (ib, lb) in b.iter().enumerate() {
+                        // Neighbouring segments of a contour always meet
+                        if i == j && (ib <= ia + 1 || (ia == 0 && ib == b.len() - 1)) {
+                            continue;
+                        }
+                        if !PathSeg::Line(*la).intersect_line(*lb).is_empty() {
+                            return true;
+                        }
+                    }
+                }
+            }
+        }
+        // Without any crossings, contours can still be nested
+        let paths: Vec<kurbo::BezPath> = polygons.iter().map(|p| polygon_path(p)).collect();
+        for (i, outer) in paths.iter().enumerate() {
+            for (j, inner) in paths.iter().enumerate() {
+                if i != j
+                    && outer.winding(polygons[j][0].p0) != 0
+                    && outer.area().signum() == inner.area().signum()
+                {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// Works out how the glyph's contours are nested inside each other.
+    ///
+    /// For each contour, returns the index of its parent: the smallest
+    /// contour (by area) which contains it, or `None` if it is not inside any
+    /// other contour. A contour is taken to be inside another if its starting
+    /// point is within the other (by the non-zero winding rule) and its
+    /// bounding box lies within the other's, so overlapping strokes which
+    /// merely cross each other are not nested. Winding direction is not taken
+    /// into account, so this can be used to find holes which are drawn the
+    /// wrong way round. Curves are approximated by line segments, and empty
+    /// contours are always top-level.
+    pub fn containment(&self) -> Vec<Option<usize>> {
+        let paths: Vec<Option<kurbo::BezPath>> = self
+            .contours
+            .iter()
+            .map(|c| {
+                let lines = flatten_contour(c);
+                (!lines.is_empty()).then(|| polygon_path(&lines))
+            })
+            .collect();
+        let areas: Vec<f64> = paths
+            .iter()
+            .map(|p| p.as_ref().map_or(0.0, |p| p.area().abs()))
+            .collect();
+        paths
+            .iter()
+            .enumerate()
+            .map(|(inner, path)| {
+                let path = path.as_ref()?;
+                let start = match path.elements().first()? {
+                    PathEl::MoveTo(p) => *p,
+                    _ => return None,
+                };
+                let bbox = path.bounding_box();
+                paths
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(outer, p)| Some((outer, p.as_ref()?)))
+                    .filter(|&(outer, p)| {
+                        // Requiring a larger area rules out identical contours
+                        // containing each other
+                        areas[outer] > areas[inner]
+                            && p.bounding_box().union(bbox) == p.bounding_box()
+                            && p.winding(start) != 0
+                    })
+                    .min_by(|a, b| areas[a.0].total_cmp(&areas[b.0]))
+                    .map(|(outer, _)| outer)
+            })
+            .collect()
+    }
+
+    /// Reports the direction in which the contour at `index` winds.
+    ///
+    /// This is found from the sign of the area the contour encloses, with
+    /// curves approximated by line segments. Contours enclosing no area,
+    /// including those of fewer than three points, are
+    /// [`Orientation::Degenerate`]. Panics if `index` is out of range.
+    pub fn contour_orientation(&self, index: usize) -> Orientation {
+        let area = contour_area(&self.contours[index]);
+        if area < 0.0 {
+            Orientation::Clockwise
+        } else if area > 0.0 {
+            Orientation::CounterClockwise
+        } else {
+            Orientation::Degenerate
+        }
+    }
+
+    /// Makes the glyph's contours wind in the TrueType direction.
+    ///
+    /// Top-level contours are made clockwise, the contours directly inside
+    /// them (holes) counter-clockwise, and so on, alternating with each level
+    /// of nesting found by [`Glyph::containment`]. Reversed contours keep
+    /// their start point. Contours with no area are left as they are, and
+    /// components are not affected.
+    pub fn correct_direction(&mut self) {
+        let parents = self.containment();
+        for ix in 0..self.contours.len() {
+            let mut depth = 0;
+            let mut ancestor = parents[ix];
+            while let Some(parent) = ancestor {
+                depth += 1;
+                ancestor = parents[parent];
+            }
+            let wanted = if depth % 2 == 0 {
+                Orientation::Clockwise
+            } else {
+                Orientation::CounterClockwise
+            };
+            let orientation = self.contour_orientation(ix);
+            if orientation != Orientation::Degenerate && orientation != wanted {
+                self.contours[ix][1..].reverse();
+            }
+        }
+    }
+
+    /// Cleans up the glyph's outline, as when importing it from elsewhere.
+    ///
+    /// In order, this removes repeated endpoints
+    /// ([`Glyph::dedupe_contour_endpoints`]), removes on-curve points within
+    /// half a unit of the line through their neighbours
+    /// ([`Glyph::remove_collinear_points`]), drops contours which enclose no
+    /// area (including contours of fewer than three points), corrects the
+    /// direction of the remaining contours ([`Glyph::correct_direction`]).
+    /// Finally, the bounds of glyphs without components are recalculated.
+    /// Running it again on its output changes nothing.
+    pub fn sanitize(&mut self) {
+        self.dedupe_contour_endpoints();
+        self.remove_collinear_points(0.5);
+        self.retain_contours(|contour| contour_area(contour).abs() >= 0.5);
+        self.correct_direction();
+        if !self.has_components() {
+            self.bounds();
+        }
+    }
+
+    /// Returns the glyph's contours as a `kurbo::BezPath` made up only of
+    /// lines and cubic curves.
+    ///
+    /// Each quadratic segment is converted to the exactly equivalent cubic
+    /// segment, after implied on-curve points have been made explicit. This
+    /// is useful for feeding TrueType outlines to consumers which only handle
+    /// cubic curves, such as CFF-based pipelines. Components are not included;
+    /// decompose the glyph first if you need them.
+    pub fn to_cubic_bezpath(&self) -> kurbo::BezPath {
+        let mut path = kurbo::BezPath::new();
+        for contour in self.contours.iter().filter(|c| !c.is_empty()) {
+            let quadratic = contourutils::glyf_contour_to_kurbo_contour(contour);
+            let mut current = kurbo::Point::ZERO;
+            for el in quadratic.elements() {
+                match *el {
+                    PathEl::MoveTo(p) => {
+                        path.move_to(p);
+                        current = p;
+                    }
+                    PathEl::LineTo(p) => {
+                        path.line_to(p);
+                        current = p;
+                    }
+                    PathEl::QuadTo(p1, p2) => {
+                        let cubic = QuadBez::new(current, p1, p2).raise();
+                        path.curve_to(cubic.p1, cubic.p2, cubic.p3);
+                        current = p2;
+                    }
+                    PathEl::CurveTo(p1, p2, p3) => {
+                        path.curve_to(p1, p2, p3);
+                        current = p3;
+                    }
+                    PathEl::ClosePath => path.close_path(),
+                }
+            }
+        }
+        path
+    }
+
+    fn _compile_deltas(&self) -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+        assert!(!self.has_components());
+        let mut last_x = 0;
+        let mut last_y = 0;
+        let mut compressed_flags: Vec<u8> = vec![];
+        let mut compressed_xs: Vec<u8> = vec![];
+        let mut compressed_ys: Vec<u8> = vec![];
+        let mut run: Option<(SimpleGlyphFlags, usize)> = None;
+        for point in self.contours.iter().flatten() {
+            let x = point.x - last_x;
+            let y = point.y - last_y;
+            let flag = point_flag(point.on_curve, x, y);
+            match coordinate_size(x) {
+                0 => {}
+                1 => compressed_xs.push(x.unsigned_abs() as u8),
+                _ => compressed_xs.extend(&i16::to_be_bytes(x)),
+            }
+            match coordinate_size(y) {
+                0 => {}
+                1 => compressed_ys.push(y.unsigned_abs() as u8),
+                _ => compressed_ys.extend(&i16::to_be_bytes(y)),
+            }
+            run = match run {
+                Some((last_flag, count)) if last_flag == flag => Some((flag, count + 1)),
+                Some((last_flag, count)) => {
+                    push_flag_run(&mut compressed_flags, last_flag, count);
+                    Some((flag, 1))
+                }
+                None => Some((flag, 1)),
+            };
+            last_x = point.x;
+            last_y = point.y;
+        }
+        if let Some((flag, count)) = run {
+            push_flag_run(&mut compressed_flags, flag, count);
+        }
+        (compressed_flags, compressed_xs, compressed_ys)
+    }
+
+    /// Decomposes components in this glyph (but not recursively)
+    ///
+    /// Transformed coordinates are truncated towards zero; see
+    /// [`Glyph::decompose_with_rounding`] to round them.
+    pub fn decompose(&self, glyphs: &[Glyph]) -> Glyph {
+        self.decompose_by(glyphs, |pt, t| pt.transform(t))
+    }
+
+    /// Decomposes components in this glyph (but not recursively), rounding
+    /// transformed coordinates with the given strategy.
+    pub fn decompose_with_rounding(&self, glyphs: &[Glyph], rounding: RoundMode) -> Glyph {
+        self.decompose_by(glyphs, |pt, t| pt.transform_with_rounding(t, rounding))
+    }
+
+    fn decompose_by(
+        &self,
+        glyphs: &[Glyph],
+        transform: impl Fn(&Point, kurbo::Affine) -> Point,
+    ) -> Glyph {
+        let mut newglyph = Glyph {
+            overlap: self.overlap,
+            ..Glyph::empty()
+        };
+        let mut new_contours = vec![];
+        new_contours.extend(self.contours.clone());
+        for comp in &self.components {
+            let ix = comp.glyph_index;
+            match glyphs.get(ix as usize) {
+                None => {
+                    log::error!("Component not found for ID={:?}", ix);
+                }
+                Some(other_glyph) => {
+                    for c in &other_glyph.contours {
+                        new_contours.push(
+                            c.iter()
+                                .map(|pt| transform(pt, comp.transformation))
+                                .collect(),
+                        );
+                    }
+                    if other_glyph.has_components() {
+                        log::warn!("Found nested components while decomposing");
+                    }
+                    if comp.is_reflection() && !other_glyph.contours.is_empty() {
+                        log::warn!(
+                            "Component {:?} is reflected, so its decomposed contours may wind backwards",
+                            ix
+                        );
+                    }
+                }
+            }
+        }
+        if !new_contours.is_empty() {
+            newglyph.contours = new_contours;
+        }
+        newglyph
+    }
+
+    /// Produces a tuple made up of a list of X/Y coordinates and a list
+    /// of ends-of-contour indices, suitable for use when constructing a
+    /// `gvar` table.
+    ///
+    /// A glyph with both contours and components is written to the font as a
+    /// composite, so as in [`Glyph::num_points`] only its components are
+    /// used.
+    pub fn gvar_coords_and_ends(&self) -> (Vec<(int16, int16)>, Vec<usize>) {
+        let contours: &[Vec<Point>] = if self.has_components() {
+            &[]
+        } else {
+            &self.contours
+        };
+        let mut ends: Vec<usize> = contours
+            .iter()
+            .map(|c| c.len())
+            .scan(0, |acc, x| {
+                *acc += x;
+                Some(*acc - 1)
+            })
+            .collect();
+
+        let mut coords: Vec<(i16, i16)> =
+            contours.iter().flatten().map(|pt| (pt.x, pt.y)).collect();
+        for comp in &self.components {
+            let [_, _, _, _, translate_x, translate_y] = comp.transformation.as_coeffs();
+            coords.push((translate_x as i16, translate_y as i16));
+            ends.push(ends.iter().max().map(|x| x + 1).unwrap_or(0));
+        }
+
+        // Phantom points
+        let left_side_x = 0; // XXX WRONG
+        let right_side_x = 0;
+        let top_side_y = 0;
+        let bottom_side_y = 0;
+        coords.push((left_side_x, 0));
+        ends.push(ends.iter().max().map(|x| x + 1).unwrap_or(0));
+        coords.push((right_side_x, 0));
+        ends.push(ends.iter().max().unwrap() + 1);
+        coords.push((0, top_side_y));
+        ends.push(ends.iter().max().unwrap() + 1);
+        coords.push((0, bottom_side_y));
+        ends.push(ends.iter().max().unwrap() + 1);
+        debug_assert_eq!(coords.len(), self.num_points(true));
+        assert_eq!(
+            *ends.last().unwrap(),
+            coords.len() - 1,
+            "Coords: {:?}\nEnds: {:?}\nGlyf: {:#?}",
+            coords,
+            ends,
+            self
+        );
+        (coords, ends)
+    }
+
+    /// Returns the difference between each of this glyph's coordinates and
+    /// the corresponding coordinate of `base`.
+    ///
+    /// The coordinates are those of [`Glyph::gvar_coords_and_ends`], so for
+    /// composites the deltas of the component offsets are included, and the
+    /// phantom points are at the end. Returns `None` if the glyphs are not
+    /// compatible for interpolation (see [`check_interpolatable`]), or if a
+    /// difference does not fit in 16 bits.
+    ///
+    /// [`check_interpolatable`]: super::check_interpolatable
+    pub fn coord_deltas(&self, base: &Glyph) -> Option<Vec<(i16, i16)>> {
+        check_interpolatable(base, self).ok()?;
+        let (coords, _) = self.gvar_coords_and_ends();
+        let (base_coords, _) = base.gvar_coords_and_ends();
+        coords
+            .iter()
+            .zip(base_coords.iter())
+            .map(|((x, y), (bx, by))| Some((x.checked_sub(*bx)?, y.checked_sub(*by)?)))
+            .collect()
+    }
+
+    /// Number of points in this glyph.
+    ///
+    /// For a simple glyph, this is the number of points in all its contours.
+    /// For a composite glyph, each component counts as a single point (its
+    /// offset), as in the `gvar` table; the points of the glyphs it refers to
+    /// are not counted. If `include_phantom` is true, the four phantom points
+    /// used by `gvar` to vary the glyph's metrics are added.
+    pub fn num_points(&self, include_phantom: bool) -> usize {
+        let points = if self.has_components() {
+            self.components.len()
+        } else {
+            self.contours.iter().map(|x| x.len()).sum()
+        };
+        if include_phantom {
+            points + 4
+        } else {
+            points
+        }
+    }
+
+    /// Returns the on-curve flags of each contour's points, contour by contour.
+    ///
+    /// Two glyphs' contours are point-compatible for interpolation exactly
+    /// when their signatures are equal, so comparing signatures shows where
+    /// the point structure of two masters diverges. Components are not
+    /// included; see [`check_interpolatable`] for a full comparison.
+    pub fn contour_signatures(&self) -> Vec<Vec<bool>> {
+        self.contours
+            .iter()
+            .map(|contour| contour.iter().map(|pt| pt.on_curve).collect())
+            .collect()
+    }
+
+    /// Number of contours in this glyph (without counting components)
+    pub fn num_contours(&self) -> usize {
+        self.contours.len()
     }
 
     /// Get information about composite depth and contour points
@@ -421,7 +1578,7 @@ impl Glyph {
         self._composite_maxp_values(glyphs, 1)
     }
     fn _composite_maxp_values(&self, glyphs: &[Glyph], depth: u16) -> Option<CompositeMaxpValues> {
-        if !self.has_components() {
+        if !self.has_components() || u32::from(depth) > super::MAX_COMPONENT_DEPTH {
             return None;
         }
         let mut info = CompositeMaxpValues {
@@ -435,11 +1592,16 @@ impl Glyph {
             .flat_map(|c| glyphs.get(c.glyph_index as usize))
         {
             if !base_glyph.has_components() {
-                info.num_points += base_glyph.num_points() as u16;
-                info.num_contours += base_glyph.num_contours() as u16;
+                let clamp = |x: usize| x.min(u16::MAX as usize) as u16;
+                info.num_points = info
+                    .num_points
+                    .saturating_add(clamp(base_glyph.num_points(false)));
+                info.num_contours = info
+                    .num_contours
+                    .saturating_add(clamp(base_glyph.num_contours()));
             } else if let Some(other_info) = base_glyph._composite_maxp_values(glyphs, depth + 1) {
-                info.num_points += other_info.num_points;
-                info.num_contours += other_info.num_contours;
+                info.num_points = info.num_points.saturating_add(other_info.num_points);
+                info.num_contours = info.num_contours.saturating_add(other_info.num_contours);
                 info.max_depth = max(info.max_depth, other_info.max_depth);
             }
         }
@@ -447,47 +1609,219 @@ impl Glyph {
     }
 }
 
-impl Serialize for Glyph {
-    fn to_bytes(&self, data: &mut Vec<u8>) -> Result<(), SerializationError> {
-        if self.is_empty() {
-            return Ok(());
-        }
-        data.put(if self.has_components() {
-            -1
-        } else {
-            self.contours.len() as i16
-        })?;
-        // recalc bounds?
-        data.put(GlyphCore {
-            xMin: self.xMin,
-            xMax: self.xMax,
-            yMin: self.yMin,
-            yMax: self.yMax,
-        })?;
-        if self.has_components() {
-            for (i, comp) in self.components.iter().enumerate() {
-                let flags = comp
-                    .recompute_flags(i < self.components.len() - 1, !self.instructions.is_empty());
+/// Computes the left and right sidebearings of a glyph from its ink bounds
+/// (see [`Glyph::ink_bounds`]) and its advance width.
+///
+/// The left sidebearing is the distance from the origin to the left edge of
+/// the ink, and the right sidebearing the distance from the right edge of the
+/// ink to the advance width. Either is negative when the ink extends outside
+/// the advance box.
+pub fn sidebearings(ink: kurbo::Rect, advance_width: u16) -> (f64, f64) {
+    (ink.min_x(), f64::from(advance_width) - ink.max_x())
+}
+
+/// Returns true if `comp` reflects `base` so that the contours it places
+/// wind against the TrueType direction (see [`Glyph::correct_direction`]).
+///
+/// A base glyph whose contours were reversed to allow for the reflection is
+/// not a problem, nor is one with no contours.
+fn reflection_reverses_direction(comp: &Component, base: &Glyph) -> bool {
+    if !comp.is_reflection() || base.contours.is_empty() {
+        return false;
+    }
+    let placed = Glyph {
+        contours: base
+            .contours
+            .iter()
+            .map(|c| {
+                c.iter()
+                    .map(|pt| pt.transform(comp.transformation))
+                    .collect()
+            })
+            .collect(),
+        ..Glyph::empty()
+    };
+    let mut corrected = placed.clone();
+    corrected.correct_direction();
+    corrected.contours != placed.contours
+}
+
+/// Converts a bounding box to `[xMin, yMin, xMax, yMax]`, clamping each edge
+/// to the range of an `i16` and warning if any had to be clamped.
+fn clamp_bounds(r: kurbo::Rect) -> [i16; 4] {
+    let range = f64::from(i16::MIN)..=f64::from(i16::MAX);
+    if ![r.min_x(), r.min_y(), r.max_x(), r.max_y()]
+        .iter()
+        .all(|v| range.contains(v))
+    {
+        log::warn!(
+            "Glyph bounds {:?} exceed the 16-bit coordinate range; clamping",
+            r
+        );
+    }
+    let clamp = |v: f64| v.clamp(*range.start(), *range.end()) as i16;
+    [
+        clamp(r.min_x()),
+        clamp(r.min_y()),
+        clamp(r.max_x()),
+        clamp(r.max_y()),
+    ]
+}
+
+/// The signed area enclosed by a contour, with curves approximated by line
+/// segments. Counter-clockwise contours have a positive area.
+fn contour_area(contour: &[Point]) -> f64 {
+    let lines = flatten_contour(contour);
+    if lines.is_empty() {
+        return 0.0;
+    }
+    polygon_path(&lines).area()
+}
+
+/// Approximates a contour by line segments, splitting each curve into
+/// several lines. Zero-length segments are dropped.
+fn flatten_contour(contour: &[Point]) -> Vec<kurbo::Line> {
+    const CURVE_STEPS: usize = 8;
+    let mut lines = vec![];
+    for segment in contour_segments(contour) {
+        match segment {
+            Segment::Line(p0, p1) => lines.push(kurbo::Line::new(p0, p1)),
+            Segment::Quad(p0, p1, p2) => {
+                let quad = QuadBez::new(p0, p1, p2);
+                let mut last = p0;
+                for step in 1..=CURVE_STEPS {
+                    let pt = quad.eval(step as f64 / CURVE_STEPS as f64);
+                    lines.push(kurbo::Line::new(last, pt));
+                    last = pt;
+                }
+            }
+        }
+    }
+    lines.retain(|l| l.p0 != l.p1);
+    lines
+}
+
+/// Builds a closed path from a polygon's edges.
+fn polygon_path(lines: &[kurbo::Line]) -> kurbo::BezPath {
+    let mut path = kurbo::BezPath::new();
+    path.move_to(lines[0].p0);
+    for line in lines {
+        path.line_to(line.p1);
+    }
+    path.close_path();
+    path
+}
+
+/// A 64-bit FNV-1a hasher, whose output does not vary between runs.
+struct StableHasher(u64);
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        StableHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl StableHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+impl Glyph {
+    /// Checks that the instructions' length fits in the glyph's `u16` field.
+    fn check_instructions_length(&self) -> Result<u16, SerializationError> {
+        u16::try_from(self.instructions.len()).map_err(|_| {
+            SerializationError(format!(
+                "Too many instructions in glyph ({})",
+                self.instructions.len()
+            ))
+        })
+    }
+
+    /// Checks that the difference between each point and the one before it
+    /// (which is how coordinates are stored) fits in an `i16`.
+    fn check_coordinate_deltas(&self) -> Result<(), SerializationError> {
+        let (mut last_x, mut last_y) = (0, 0);
+        for (ix, point) in self.contours.iter().flatten().enumerate() {
+            let (x, y) = (i32::from(point.x), i32::from(point.y));
+            for delta in [x - last_x, y - last_y] {
+                if i16::try_from(delta).is_err() {
+                    return Err(SerializationError(format!(
+                        "Point {} is too far ({} units) from the point before it",
+                        ix, delta
+                    )));
+                }
+            }
+            last_x = x;
+            last_y = y;
+        }
+        Ok(())
+    }
+
+    fn put_instructions(&self, data: &mut Vec<u8>) -> Result<(), SerializationError> {
+        data.put(self.check_instructions_length()?)?;
+        data.put(self.instructions.clone())
+    }
+}
+
+/// Glyphs are serialized deterministically: the same `Glyph` always produces
+/// the same bytes. Where the format allows a choice of encoding (repeated
+/// flags, short or long coordinates, byte or word component arguments, scale
+/// records), the smallest is always chosen by a fixed rule which depends only
+/// on the glyph's contents. Deserializing the output and serializing it again
+/// therefore gives identical bytes.
+impl Serialize for Glyph {
+    fn to_bytes(&self, data: &mut Vec<u8>) -> Result<(), SerializationError> {
+        if self.has_no_data() {
+            return Ok(());
+        }
+        self.validate_components().map_err(SerializationError)?;
+        // Check up front, so that nothing is written for an invalid glyph
+        self.check_instructions_length()?;
+        self.check_coordinate_deltas()?;
+        data.put(if self.has_components() {
+            -1
+        } else {
+            self.contours.len() as i16
+        })?;
+        let [x_min, y_min, x_max, y_max] = if self.bounds_dirty {
+            clamp_bounds(super::simple_bounds(self))
+        } else {
+            [self.xMin, self.yMin, self.xMax, self.yMax]
+        };
+        data.put(GlyphCore {
+            xMin: x_min,
+            xMax: x_max,
+            yMin: y_min,
+            yMax: y_max,
+        })?;
+        if self.has_components() {
+            for (i, comp) in self.components.iter().enumerate() {
+                let flags = comp
+                    .recompute_flags(i < self.components.len() - 1, !self.instructions.is_empty());
                 data.put(flags.bits())?;
                 data.put(comp.glyph_index)?;
                 let [x_scale, scale01, scale10, scale_y, translate_x, translate_y] =
                     comp.transformation.as_coeffs();
                 if flags.contains(ComponentFlags::ARGS_ARE_XY_VALUES) {
                     if flags.contains(ComponentFlags::ARG_1_AND_2_ARE_WORDS) {
-                        data.put(translate_x.round() as i16)?;
-                        data.put(translate_y as i16)?;
+                        data.put(otmath::ot_round(translate_x) as i16)?;
+                        data.put(otmath::ot_round(translate_y) as i16)?;
                     } else {
-                        data.put(translate_x.round() as i8)?;
-                        data.put(translate_y as i8)?;
+                        data.put(otmath::ot_round(translate_x) as i8)?;
+                        data.put(otmath::ot_round(translate_y) as i8)?;
                     }
                 } else {
                     let (x, y) = comp.match_points.unwrap();
                     if flags.contains(ComponentFlags::ARG_1_AND_2_ARE_WORDS) {
-                        data.put(x as i16)?;
-                        data.put(y as i16)?;
+                        data.put(x)?;
+                        data.put(y)?;
                     } else {
-                        data.put(x as i8)?;
-                        data.put(y as i8)?;
+                        data.put(x as u8)?;
+                        data.put(y as u8)?;
                     }
                 }
                 if flags.contains(ComponentFlags::WE_HAVE_A_TWO_BY_TWO) {
@@ -502,24 +1836,1554 @@ impl Serialize for Glyph {
                     data.put(F2DOT14(x_scale as f32))?;
                 }
                 if flags.contains(ComponentFlags::WE_HAVE_INSTRUCTIONS) {
-                    data.put(self.instructions.len() as u16)?;
-                    data.put(self.instructions.clone())?;
+                    self.put_instructions(data)?;
                 }
             }
         } else {
             let end_pts_of_contour = self.end_points();
             data.put(end_pts_of_contour)?;
-            if !self.instructions.is_empty() {
-                data.put(self.instructions.len() as u16)?;
-                data.put(self.instructions.clone())?;
-            } else {
-                data.put(0_u16)?;
-            }
-            let (compressed_flags, compressed_xs, compressed_ys) = self._compile_deltas_greedy();
+            self.put_instructions(data)?;
+            let (compressed_flags, compressed_xs, compressed_ys) = self._compile_deltas();
             data.put(compressed_flags)?;
             data.put(compressed_xs)?;
             data.put(compressed_ys)?;
         }
         Ok(())
     }
+
+    fn ot_binary_size(&self) -> usize {
+        if self.has_no_data() {
+            return 0;
+        }
+        // numberOfContours and bounds
+        let mut size = 10;
+        if self.has_components() {
+            for (i, comp) in self.components.iter().enumerate() {
+                let flags = comp
+                    .recompute_flags(i < self.components.len() - 1, !self.instructions.is_empty());
+                size += 4;
+                size += if flags.contains(ComponentFlags::ARG_1_AND_2_ARE_WORDS) {
+                    4
+                } else {
+                    2
+                };
+                if flags.contains(ComponentFlags::WE_HAVE_A_TWO_BY_TWO) {
+                    size += 8;
+                } else if flags.contains(ComponentFlags::WE_HAVE_AN_X_AND_Y_SCALE) {
+                    size += 4;
+                } else if flags.contains(ComponentFlags::WE_HAVE_A_SCALE) {
+                    size += 2;
+                }
+                if flags.contains(ComponentFlags::WE_HAVE_INSTRUCTIONS) {
+                    size += 2 + self.instructions.len();
+                }
+            }
+        } else {
+            size += 2 * self.contours.len() + 2 + self.instructions.len();
+            let (mut last_x, mut last_y) = (0, 0);
+            let mut run: Option<(SimpleGlyphFlags, usize)> = None;
+            for point in self.contours.iter().flatten() {
+                // Deltas which overflow are rejected when serializing
+                let (x, y) = (point.x.wrapping_sub(last_x), point.y.wrapping_sub(last_y));
+                size += coordinate_size(x) + coordinate_size(y);
+                let flag = point_flag(point.on_curve, x, y);
+                run = match run {
+                    Some((last_flag, count)) if last_flag == flag => Some((flag, count + 1)),
+                    Some((_, count)) => {
+                        size += flag_run_size(count);
+                        Some((flag, 1))
+                    }
+                    None => Some((flag, 1)),
+                };
+                last_x = point.x;
+                last_y = point.y;
+            }
+            if let Some((_, count)) = run {
+                size += flag_run_size(count);
+            }
+        }
+        size
+    }
+}
+
+/// The number of bytes used to store a coordinate delta in a simple glyph.
+fn coordinate_size(delta: i16) -> usize {
+    match delta {
+        0 => 0,
+        -255..=255 => 1,
+        _ => 2,
+    }
+}
+
+/// Chooses the flag for a point in a simple glyph, given its coordinate deltas.
+///
+/// Each delta uses the shortest available encoding: zero deltas are stored
+/// as "same" and take no space, deltas within 255 take a single byte with
+/// the sign in the flag, and anything else takes a word.
+fn point_flag(on_curve: bool, x: i16, y: i16) -> SimpleGlyphFlags {
+    let mut flag = if on_curve {
+        SimpleGlyphFlags::ON_CURVE_POINT
+    } else {
+        SimpleGlyphFlags::empty()
+    };
+    match coordinate_size(x) {
+        0 => flag |= SimpleGlyphFlags::X_IS_SAME_OR_POSITIVE_X_SHORT_VECTOR,
+        1 if x > 0 => {
+            flag |= SimpleGlyphFlags::X_SHORT_VECTOR
+                | SimpleGlyphFlags::X_IS_SAME_OR_POSITIVE_X_SHORT_VECTOR
+        }
+        1 => flag |= SimpleGlyphFlags::X_SHORT_VECTOR,
+        _ => {}
+    }
+    match coordinate_size(y) {
+        0 => flag |= SimpleGlyphFlags::Y_IS_SAME_OR_POSITIVE_Y_SHORT_VECTOR,
+        1 if y > 0 => {
+            flag |= SimpleGlyphFlags::Y_SHORT_VECTOR
+                | SimpleGlyphFlags::Y_IS_SAME_OR_POSITIVE_Y_SHORT_VECTOR
+        }
+        1 => flag |= SimpleGlyphFlags::Y_SHORT_VECTOR,
+        _ => {}
+    }
+    flag
+}
+
+/// Writes `count` copies of a flag, using the REPEAT flag where it saves space.
+///
+/// A repeated flag takes two bytes and covers up to 256 points, so runs of
+/// three or more are repeated and shorter runs are written out in full.
+fn push_flag_run(data: &mut Vec<u8>, flag: SimpleGlyphFlags, mut count: usize) {
+    while count > 0 {
+        let chunk = count.min(256);
+        if chunk > 2 {
+            data.push((flag | SimpleGlyphFlags::REPEAT_FLAG).bits());
+            data.push((chunk - 1) as u8);
+        } else {
+            for _ in 0..chunk {
+                data.push(flag.bits());
+            }
+        }
+        count -= chunk;
+    }
+}
+
+/// The number of bytes [`push_flag_run`] uses to write a run of `count` flags.
+fn flag_run_size(count: usize) -> usize {
+    2 * (count / 256) + (count % 256).min(2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tables::glyf::tests::{component, sample_glyf, square_glyph};
+    use crate::tables::glyf::GlyphBuilder;
+    use kurbo::Point as KPoint;
+
+    thread_local! {
+        /// The number of times [`Glyph::bounds`] has recalculated out of
+        /// date bounds on this thread.
+        pub(super) static BOUNDS_RECALCULATIONS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+    }
+
+    #[test]
+    fn test_approx_eq() {
+        let glyph = GlyphBuilder::new()
+            .move_to(0, 0)
+            .line_to(100, 0)
+            .quad_to(150, 50, 100, 100)
+            .build();
+        assert!(glyph.approx_eq(&glyph, 0.0));
+        let mut moved = glyph.clone();
+        moved.contours[0][1].x += 1;
+        assert!(moved.approx_eq(&glyph, 2.0));
+        assert!(!moved.approx_eq(&glyph, 0.5));
+
+        // Structural differences are never close
+        let mut flipped = glyph.clone();
+        flipped.contours[0][2].on_curve = true;
+        assert!(!flipped.approx_eq(&glyph, 1000.0));
+        let mut shorter = glyph.clone();
+        shorter.contours[0].pop();
+        assert!(!shorter.approx_eq(&glyph, 1000.0));
+
+        let composite = |glyph_index, dx| Glyph {
+            components: vec![Component {
+                glyph_index,
+                transformation: kurbo::Affine::translate((dx, 0.0)),
+                match_points: None,
+                flags: ComponentFlags::empty(),
+            }],
+            ..Glyph::empty()
+        };
+        assert!(composite(1, 10.0).approx_eq(&composite(1, 11.0), 2.0));
+        assert!(!composite(1, 10.0).approx_eq(&composite(1, 11.0), 0.5));
+        assert!(!composite(1, 10.0).approx_eq(&composite(2, 10.0), 2.0));
+        let mut scaled = composite(1, 10.0);
+        scaled.components[0].transformation = kurbo::Affine::new([0.5, 0.0, 0.0, 0.5, 10.0, 0.0]);
+        assert!(!scaled.approx_eq(&composite(1, 10.0), 2.0));
+    }
+
+    #[test]
+    fn test_outline_eq() {
+        let glyph = GlyphBuilder::new()
+            .move_to(0, 0)
+            .line_to(100, 0)
+            .line_to(100, 100)
+            .build();
+        let mut stale = glyph.clone();
+        stale.xMax = 50;
+        stale.yMin = -10;
+        assert_ne!(glyph, stale);
+        assert!(glyph.outline_eq(&stale));
+
+        let mut moved = glyph.clone();
+        moved.contours[0][1].x = 101;
+        assert!(!glyph.outline_eq(&moved));
+
+        let mut hinted = glyph.clone();
+        hinted.instructions = vec![0xb0, 0x00];
+        assert!(!glyph.outline_eq(&hinted));
+
+        let composite = Glyph {
+            components: vec![
+                component(1, ComponentFlags::empty()),
+                component(2, ComponentFlags::empty()),
+            ],
+            ..Glyph::empty()
+        };
+        let reordered = Glyph {
+            components: vec![
+                component(2, ComponentFlags::empty()),
+                component(1, ComponentFlags::empty()),
+            ],
+            ..Glyph::empty()
+        };
+        assert!(!composite.outline_eq(&reordered));
+    }
+
+    #[test]
+    fn test_remove_collinear_points() {
+        let square = square_glyph();
+        let mut glyph = GlyphBuilder::new()
+            .move_to(0, 0)
+            .line_to(50, 1)
+            .line_to(100, 0)
+            .line_to(100, 100)
+            .line_to(100, 100)
+            .line_to(0, 100)
+            .line_to(0, 50)
+            .build();
+        glyph.remove_collinear_points(0.0);
+        assert_eq!(glyph.contours[0].len(), 5);
+        glyph.remove_collinear_points(1.0);
+        assert_eq!(glyph.contours, square.contours);
+
+        // Points next to a curve, or making a spike, are kept
+        let mut curved = GlyphBuilder::new()
+            .move_to(0, 0)
+            .line_to(50, 0)
+            .quad_to(100, 0, 100, 50)
+            .line_to(100, 200)
+            .line_to(100, 150)
+            .line_to(0, 100)
+            .build();
+        let expected = curved.contours.clone();
+        curved.remove_collinear_points(1.0);
+        assert_eq!(curved.contours, expected);
+    }
+
+    #[test]
+    fn test_outline_hash() {
+        let glyph = GlyphBuilder::new()
+            .move_to(0, 0)
+            .line_to(100, 0)
+            .line_to(100, 100)
+            .build();
+        let hash = glyph.outline_hash();
+        // The value is fixed, not seeded per run
+        assert_eq!(hash, 0x24c3_01a7_2e27_347c);
+
+        let mut stale = glyph.clone();
+        stale.xMax = 50;
+        stale.yMin = -10;
+        assert_eq!(stale.outline_hash(), hash);
+
+        let mut moved = glyph.clone();
+        moved.contours[0][1].x = 101;
+        assert_ne!(moved.outline_hash(), hash);
+
+        // Moving a point between contours changes the hash
+        let mut split = glyph.clone();
+        let last = split.contours[0].pop().unwrap();
+        split.contours.push(vec![last]);
+        assert_ne!(split.outline_hash(), hash);
+
+        let mut hinted = glyph;
+        hinted.instructions = vec![0xb0, 0x00];
+        assert_ne!(hinted.outline_hash(), hash);
+    }
+
+    #[test]
+    fn test_dedupe_contour_endpoints() {
+        let square = square_glyph();
+        let mut glyph = square.clone();
+        let first = glyph.contours[0][0];
+        glyph.contours[0].push(first);
+        assert_eq!(glyph.contours[0].len(), 5);
+        glyph.dedupe_contour_endpoints();
+        assert_eq!(glyph.contours, square.contours);
+
+        // An off-curve point at the start position is not a duplicate
+        let mut curved = square.clone();
+        curved.contours[0].push(Point {
+            on_curve: false,
+            ..first
+        });
+        let expected = curved.contours.clone();
+        curved.dedupe_contour_endpoints();
+        assert_eq!(curved.contours, expected);
+    }
+
+    #[test]
+    fn test_detect_overlap() {
+        let glyf = sample_glyf();
+        assert!(glyf.glyphs[0].detect_overlap());
+        // O is an outer contour and a counter
+        assert!(!glyf.glyphs[2].detect_overlap());
+
+        let square = |x: i16, y: i16| {
+            GlyphBuilder::new()
+                .move_to(x, y)
+                .line_to(x, y + 100)
+                .line_to(x + 100, y + 100)
+                .line_to(x + 100, y)
+                .build()
+        };
+        assert!(!square(0, 0).detect_overlap());
+        let mut two = square(0, 0);
+        two.contours.extend(square(50, 50).contours);
+        assert!(two.detect_overlap());
+        let mut apart = square(0, 0);
+        apart.contours.extend(square(200, 0).contours);
+        assert!(!apart.detect_overlap());
+
+        // A smaller square inside, in the same direction
+        let mut nested = GlyphBuilder::new()
+            .move_to(0, 0)
+            .line_to(0, 300)
+            .line_to(300, 300)
+            .line_to(300, 0)
+            .build();
+        nested.contours.extend(square(100, 100).contours);
+        assert!(nested.detect_overlap());
+        nested.contours[1].reverse();
+        assert!(!nested.detect_overlap());
+    }
+
+    #[test]
+    fn test_flip() {
+        let glyf = sample_glyf();
+        let dollar = glyf.glyphs[5].clone();
+        let area = |g: &Glyph| -> f64 {
+            use kurbo::ParamCurveArea;
+            g.segments()
+                .map(|s| match s {
+                    Segment::Line(p0, p1) => kurbo::Line::new(p0, p1).signed_area(),
+                    Segment::Quad(p0, p1, p2) => QuadBez::new(p0, p1, p2).signed_area(),
+                })
+                .sum()
+        };
+
+        let mut flipped = dollar.clone();
+        flipped.flip_x(300);
+        flipped.bounds();
+        assert_eq!(flipped.xMin, 600 - dollar.xMax);
+        assert_eq!(flipped.xMax, 600 - dollar.xMin);
+        assert_eq!(flipped.yMin, dollar.yMin);
+        assert_eq!(flipped.contours[0][0].x, 600 - dollar.contours[0][0].x);
+        // Contour directions, and so the filled area, are preserved
+        assert!((area(&flipped) - area(&dollar)).abs() < 1e-6);
+        flipped.flip_x(300);
+        flipped.bounds();
+        assert_eq!(flipped, dollar);
+
+        flipped.flip_y(-10);
+        flipped.bounds();
+        assert_eq!(flipped.yMax, -20 - dollar.yMin);
+        assert!((area(&flipped) - area(&dollar)).abs() < 1e-6);
+        flipped.flip_y(-10);
+        flipped.bounds();
+        assert_eq!(flipped, dollar);
+
+        let mut aacute = glyf.glyphs[1].clone();
+        aacute.flip_x(0);
+        assert_eq!(
+            aacute.components[1].transformation,
+            kurbo::Affine::new([-1.0, 0.0, 0.0, 1.0, -402.0, 130.0])
+        );
+        aacute.flip_x(0);
+        assert_eq!(aacute, glyf.glyphs[1]);
+    }
+
+    #[test]
+    fn test_scale_to_upm() {
+        let glyf = sample_glyf();
+
+        let mut aacute = glyf.glyphs[1].clone();
+        let mut scaled_acute = glyf.glyphs[1].components[1].clone();
+        scaled_acute.transformation = kurbo::Affine::new([0.5, 0.0, 0.0, 0.75, 201.0, 130.0]);
+        aacute.components[1] = scaled_acute;
+        aacute.scale_to_upm(1000, 2000);
+        assert_eq!(
+            aacute.components[1].transformation,
+            kurbo::Affine::new([0.5, 0.0, 0.0, 0.75, 402.0, 260.0])
+        );
+        assert_eq!(aacute.components[0].transformation, kurbo::Affine::IDENTITY);
+        assert_eq!(aacute.yMax, glyf.glyphs[1].yMax * 2);
+
+        let mut a = glyf.glyphs[0].clone();
+        a.scale_to_upm(1000, 2000);
+        assert_eq!(a.contours[0][0].x, glyf.glyphs[0].contours[0][0].x * 2);
+        a.bounds();
+        assert_eq!(a.xMax, glyf.glyphs[0].xMax * 2);
+        a.scale_to_upm(2000, 1000);
+        a.bounds();
+        assert_eq!(a, glyf.glyphs[0]);
+    }
+
+    #[test]
+    fn test_translate() {
+        let glyf = sample_glyf();
+
+        let mut dollar = glyf.glyphs[5].clone();
+        dollar.translate(30, -20);
+        dollar.bounds();
+        assert_eq!(
+            (dollar.xMin, dollar.yMin, dollar.xMax, dollar.yMax),
+            (
+                glyf.glyphs[5].xMin + 30,
+                glyf.glyphs[5].yMin - 20,
+                glyf.glyphs[5].xMax + 30,
+                glyf.glyphs[5].yMax - 20
+            )
+        );
+        assert_eq!(
+            dollar.contours[0][0].x,
+            glyf.glyphs[5].contours[0][0].x + 30
+        );
+        let mut recalculated = dollar.clone();
+        recalculated.set_bounds_rect(super::super::simple_bounds(&dollar));
+        assert_eq!(recalculated, dollar);
+
+        let mut aacute = glyf.glyphs[1].clone();
+        aacute.translate(10, 5);
+        assert_eq!(
+            aacute.components[1].transformation,
+            kurbo::Affine::translate((412.0, 135.0))
+        );
+        assert_eq!(aacute.xMax, glyf.glyphs[1].xMax + 10);
+        aacute.translate(-10, -5);
+        assert_eq!(aacute, glyf.glyphs[1]);
+    }
+
+    #[test]
+    fn test_sidebearings() {
+        let glyf = sample_glyf();
+        let mut cap_a = glyf.glyphs[0].clone();
+        let ink = cap_a.ink_bounds();
+        assert_eq!(ink, kurbo::Rect::new(5.0, 0.0, 751.0, 700.0));
+        assert_eq!(sidebearings(ink, 751), (5.0, 0.0));
+        assert_eq!(sidebearings(ink, 800), (5.0, 49.0));
+        assert_eq!(sidebearings(ink, 700), (5.0, -51.0));
+
+        // Stale stored bounds do not affect the ink bounds of a simple glyph
+        cap_a.xMin = 0;
+        assert_eq!(cap_a.ink_bounds(), ink);
+        cap_a.translate(-10, 0);
+        assert_eq!(sidebearings(cap_a.ink_bounds(), 751).0, -5.0);
+
+        let aacute = &glyf.glyphs[1];
+        assert_eq!(aacute.ink_bounds(), aacute.bounds_rect());
+    }
+
+    #[test]
+    fn test_serialization_fixed_point() {
+        let glyf = sample_glyf();
+        let mut glyphs = glyf.glyphs.clone();
+        // Variants exercising other encodings
+        for g in glyf.glyphs.iter().filter(|g| !g.is_empty()) {
+            let mut variant = g.clone();
+            variant.overlap = true;
+            variant.instructions = vec![0xb0, 0x01, 0x2f];
+            variant.translate(-300, 200);
+            for comp in variant.components.iter_mut() {
+                comp.transformation *= kurbo::Affine::scale_non_uniform(0.3, 1.7);
+            }
+            glyphs.push(variant);
+        }
+        for g in glyphs {
+            let first = otspec::ser::to_bytes(&g).unwrap();
+            assert_eq!(otspec::ser::to_bytes(&g).unwrap(), first);
+            if g.has_no_data() {
+                // Nothing is written, and there is nothing to read back
+                assert!(first.is_empty());
+                continue;
+            }
+            let reparsed: Glyph = otspec::de::from_bytes(&first).unwrap();
+            let second = otspec::ser::to_bytes(&reparsed).unwrap();
+            assert_eq!(first, second, "{}", g.summary());
+            let again: Glyph = otspec::de::from_bytes(&second).unwrap();
+            assert_eq!(again, reparsed);
+        }
+    }
+
+    #[test]
+    fn test_glyph_kind() {
+        let simple = GlyphBuilder::new()
+            .move_to(0, 0)
+            .line_to(100, 0)
+            .line_to(100, 100)
+            .build();
+        assert!(simple.is_simple() && !simple.is_composite());
+        assert_eq!(simple.kind(), GlyphKind::Simple);
+
+        let composite = Glyph {
+            components: vec![component(1, ComponentFlags::empty())],
+            ..Glyph::empty()
+        };
+        assert!(composite.is_composite() && !composite.is_simple());
+        assert_eq!(composite.kind(), GlyphKind::Composite);
+
+        let empty = Glyph::empty();
+        assert!(!empty.is_simple() && !empty.is_composite());
+        assert_eq!(empty.kind(), GlyphKind::Empty);
+    }
+
+    #[test]
+    fn test_repeated_flag_encoding() {
+        // 300 on-curve points along the x axis, one unit apart
+        let contour: Vec<Point> = (0..300)
+            .map(|x| Point {
+                x,
+                y: 0,
+                on_curve: true,
+            })
+            .collect();
+        let glyph = Glyph {
+            xMax: 299,
+            contours: vec![contour],
+            ..Glyph::empty()
+        };
+        let serialized = otspec::ser::to_bytes(&glyph).unwrap();
+        // 10 bytes of header, 2 of endpoints, 2 of instruction length; one
+        // flag for the first point, then a run of 299 identical flags which
+        // takes two repeats (256 + 43) of two bytes each; 299 x deltas of
+        // one byte each and no y deltas.
+        assert_eq!(serialized.len(), 14 + 1 + 4 + 299);
+        assert_eq!(glyph.ot_binary_size(), serialized.len());
+        let flags = &serialized[14..19];
+        assert_eq!(flags[1] & 0x08, 0x08);
+        assert_eq!(flags[2], 255);
+        assert_eq!(flags[4], 42);
+        let deserialized: Glyph = otspec::de::from_bytes(&serialized).unwrap();
+        assert_eq!(deserialized, glyph);
+
+        // Runs of one or two flags are not worth repeating
+        let mut buf = vec![];
+        push_flag_run(&mut buf, SimpleGlyphFlags::ON_CURVE_POINT, 2);
+        assert_eq!(buf, vec![0x01, 0x01]);
+        buf.clear();
+        push_flag_run(&mut buf, SimpleGlyphFlags::ON_CURVE_POINT, 258);
+        assert_eq!(buf, vec![0x09, 255, 0x01, 0x01]);
+        assert_eq!(flag_run_size(258), 4);
+    }
+
+    #[test]
+    fn test_add_contour_from_bezpath() {
+        let mut glyph = Glyph::empty();
+        let mut path = kurbo::Rect::new(0.0, 0.0, 100.0, 50.0).to_path(0.1);
+        path.extend(kurbo::Rect::new(200.0, 0.0, 300.0, 80.0).to_path(0.1));
+        glyph.add_contour_from_bezpath(&path).unwrap();
+        assert_eq!(glyph.num_contours(), 2);
+        assert!(glyph.contours.iter().all(|c| c.len() == 4));
+        assert_eq!(glyph.bounds_rect(), kurbo::Rect::new(0.0, 0.0, 300.0, 80.0));
+
+        let mut open = kurbo::BezPath::new();
+        open.move_to((0.0, 0.0));
+        open.line_to((100.0, 0.0));
+        open.line_to((100.0, 100.0));
+        assert!(glyph.add_contour_from_bezpath(&open).is_err());
+        assert_eq!(glyph.num_contours(), 2);
+    }
+
+    #[test]
+    fn test_perimeter() {
+        let glyph = GlyphBuilder::new()
+            .move_to(0, 0)
+            .line_to(300, 0)
+            .line_to(300, 200)
+            .line_to(0, 200)
+            .move_to(500, 0)
+            .line_to(600, 0)
+            .line_to(600, 100)
+            .line_to(500, 100)
+            .build();
+        assert_eq!(glyph.contour_length(0), 1000.0);
+        assert_eq!(glyph.contour_length(1), 400.0);
+        assert_eq!(glyph.perimeter(), 1400.0);
+
+        // A straight "curve" has the length of its chord, and the implied
+        // on-curve point between the two off-curve points is materialized
+        let curved = Glyph {
+            contours: vec![vec![
+                Point {
+                    x: 0,
+                    y: 0,
+                    on_curve: true,
+                },
+                Point {
+                    x: 100,
+                    y: 0,
+                    on_curve: false,
+                },
+                Point {
+                    x: 300,
+                    y: 0,
+                    on_curve: false,
+                },
+                Point {
+                    x: 400,
+                    y: 0,
+                    on_curve: true,
+                },
+            ]],
+            ..Glyph::empty()
+        };
+        assert!((curved.perimeter() - 800.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_insert_and_delete_point() {
+        let mut glyph = square_glyph();
+        let original = glyph.clone();
+        let on = |x, y| Point {
+            x,
+            y,
+            on_curve: true,
+        };
+        let off = |x, y| Point {
+            x,
+            y,
+            on_curve: false,
+        };
+
+        // Find the edge from (100, 0) to (100, 100), and add a point on it
+        let after = glyph.contours[0]
+            .iter()
+            .position(|pt| (pt.x, pt.y) == (100, 0))
+            .unwrap();
+        let next = glyph.contours[0][(after + 1) % 4];
+        glyph.insert_point(0, after, on(100, next.y / 2)).unwrap();
+        assert_eq!(glyph.contours[0].len(), 5);
+        assert_eq!(glyph.bounds_rect(), original.bounds_rect());
+        for (x, y) in [(50.0, 50.0), (99.0, 50.0), (101.0, 50.0), (50.0, -1.0)] {
+            assert_eq!(glyph.contains(x, y), original.contains(x, y));
+        }
+        assert_eq!(glyph.perimeter(), original.perimeter());
+        assert!(glyph.insert_point(0, 5, on(0, 0)).is_err());
+        assert!(glyph.insert_point(1, 0, on(0, 0)).is_err());
+
+        // Deleting it restores the original
+        assert_eq!(glyph.delete_point(0, after + 1), Ok(on(100, 50)));
+        assert_eq!(glyph, original);
+
+        // An on-curve point between two off-curve points becomes implied
+        let mut curved = Glyph {
+            contours: vec![vec![on(0, 0), off(100, 0), on(150, 60), off(100, 100)]],
+            ..Glyph::empty()
+        };
+        assert_eq!(curved.delete_point(0, 2), Ok(on(150, 60)));
+        assert_eq!(curved.contours[0][2], on(100, 50));
+        // Otherwise the point is simply removed
+        assert_eq!(curved.delete_point(0, 1), Ok(off(100, 0)));
+        assert_eq!(
+            curved.contours[0],
+            vec![on(0, 0), on(100, 50), off(100, 100)]
+        );
+        curved.delete_point(0, 2).unwrap();
+        assert!(curved.delete_point(0, 0).is_err());
+    }
+
+    #[test]
+    fn test_flatten_to_polygons() {
+        let glyph = GlyphBuilder::new()
+            .move_to(0, 0)
+            .line_to(100, 0)
+            .line_to(100, 100)
+            .move_to(200, 0)
+            .quad_to(300, 200, 400, 0)
+            .build();
+        let fine = glyph.flatten_to_polygons(0.1);
+        let coarse = glyph.flatten_to_polygons(10.0);
+        assert_eq!(fine.len(), 2);
+        assert_eq!(coarse.len(), 2);
+        // Straight contours are unaffected by the tolerance
+        assert_eq!(fine[0].len(), 4);
+        assert_eq!(coarse[0].len(), 4);
+        assert!(coarse[1].len() < fine[1].len());
+        for polygon in fine.iter().chain(coarse.iter()) {
+            assert_eq!(polygon.first(), polygon.last());
+        }
+        // The curve's midpoint is within tolerance of the fine polygon
+        assert!(fine[1]
+            .iter()
+            .any(|&(x, y)| (x - 300.0).abs() < 1.0 && (y - 100.0).abs() < 1.0));
+    }
+
+    #[test]
+    fn test_use_my_metrics_validation() {
+        let mut glyph = Glyph {
+            components: vec![
+                component(1, ComponentFlags::USE_MY_METRICS),
+                component(2, ComponentFlags::empty()),
+            ],
+            ..Glyph::empty()
+        };
+        assert!(glyph.validate_components().is_ok());
+        assert!(otspec::ser::to_bytes(&glyph).is_ok());
+
+        glyph.components[1].flags = ComponentFlags::USE_MY_METRICS;
+        assert!(glyph.validate_components().is_err());
+        assert!(otspec::ser::to_bytes(&glyph).is_err());
+    }
+
+    #[test]
+    fn test_component_flag_consistency() {
+        // Both flags are the wrong way around
+        let glyph = Glyph {
+            components: vec![
+                component(1, ComponentFlags::empty()),
+                component(
+                    2,
+                    ComponentFlags::MORE_COMPONENTS | ComponentFlags::WE_HAVE_INSTRUCTIONS,
+                ),
+            ],
+            ..Glyph::empty()
+        };
+        assert!(!glyph.is_flag_consistent());
+        assert_eq!(glyph.component_flag_problems().len(), 3);
+
+        let serialized = otspec::ser::to_bytes(&glyph).unwrap();
+        let reparsed: Glyph = otspec::de::from_bytes(&serialized).unwrap();
+        assert!(reparsed.components[0]
+            .flags
+            .contains(ComponentFlags::MORE_COMPONENTS));
+        assert!(!reparsed.components[1]
+            .flags
+            .intersects(ComponentFlags::MORE_COMPONENTS | ComponentFlags::WE_HAVE_INSTRUCTIONS));
+        assert!(reparsed.is_flag_consistent());
+
+        let mut hinted = reparsed;
+        hinted.instructions = vec![0xb0, 0x00];
+        let reparsed: Glyph =
+            otspec::de::from_bytes(&otspec::ser::to_bytes(&hinted).unwrap()).unwrap();
+        assert!(reparsed.components[1]
+            .flags
+            .contains(ComponentFlags::WE_HAVE_INSTRUCTIONS));
+        assert!(reparsed.is_flag_consistent());
+        assert!(Glyph::empty().is_flag_consistent());
+    }
+
+    #[test]
+    fn test_coord_deltas() {
+        let base = GlyphBuilder::new()
+            .move_to(0, 0)
+            .line_to(100, 0)
+            .line_to(100, 100)
+            .build();
+        let mut moved = base.clone();
+        moved.contours[0][1].x += 10;
+        moved.contours[0][2].y -= 5;
+        let deltas = moved.coord_deltas(&base).unwrap();
+        // Three points, then four phantom points
+        assert_eq!(deltas.len(), 7);
+        assert_eq!(deltas[1], (10, 0));
+        assert_eq!(deltas[2], (0, -5));
+        assert_eq!(deltas.iter().filter(|&&d| d != (0, 0)).count(), 2);
+
+        let component = |x: f64| Component {
+            glyph_index: 1,
+            transformation: kurbo::Affine::translate((x, 20.0)),
+            match_points: None,
+            flags: ComponentFlags::empty(),
+        };
+        let composite = Glyph {
+            components: vec![component(50.0)],
+            ..Glyph::empty()
+        };
+        let shifted = Glyph {
+            components: vec![component(80.0)],
+            ..Glyph::empty()
+        };
+        assert_eq!(
+            shifted.coord_deltas(&composite).unwrap(),
+            vec![(30, 0), (0, 0), (0, 0), (0, 0), (0, 0)]
+        );
+
+        let mut incompatible = base.clone();
+        incompatible.contours[0].pop();
+        assert_eq!(incompatible.coord_deltas(&base), None);
+        assert_eq!(composite.coord_deltas(&base), None);
+
+        let mut far = base.clone();
+        far.contours[0][0].x = i16::MIN;
+        let mut far_base = base.clone();
+        far_base.contours[0][0].x = i16::MAX;
+        assert_eq!(far.coord_deltas(&far_base), None);
+    }
+
+    #[test]
+    fn test_contour_editing() {
+        let mut glyph = GlyphBuilder::new()
+            .move_to(0, 0)
+            .line_to(100, 0)
+            .line_to(100, 100)
+            .move_to(200, 0)
+            .line_to(300, 0)
+            .line_to(300, 50)
+            .build();
+        let second = glyph.contours[1].clone();
+
+        let removed = glyph.remove_contour(0);
+        assert_eq!(removed.len(), 3);
+        assert_eq!(glyph.num_contours(), 1);
+        assert_eq!(glyph.contours[0], second);
+        assert_eq!(
+            glyph.bounds_rect(),
+            kurbo::Rect::new(200.0, 0.0, 300.0, 50.0)
+        );
+
+        glyph.push_contour(removed).unwrap();
+        assert_eq!(glyph.num_contours(), 2);
+        assert_eq!(
+            glyph.bounds_rect(),
+            kurbo::Rect::new(0.0, 0.0, 300.0, 100.0)
+        );
+
+        glyph.swap_contours(0, 1);
+        assert_eq!(glyph.contours[1], second);
+
+        let lonely = vec![Point {
+            x: 10,
+            y: 10,
+            on_curve: true,
+        }];
+        assert!(glyph.push_contour(lonely).is_err());
+        assert!(glyph.push_contour(vec![]).is_err());
+        assert_eq!(glyph.num_contours(), 2);
+    }
+
+    #[test]
+    fn test_retain_contours() {
+        let mut glyph = GlyphBuilder::new()
+            .move_to(0, 0)
+            .line_to(100, 0)
+            .line_to(100, 100)
+            .move_to(200, 0)
+            .line_to(300, 0)
+            .line_to(400, 0)
+            .build();
+        let first = glyph.contours[0].clone();
+        let area = |contour: &[Point]| {
+            contour
+                .iter()
+                .zip(contour.iter().cycle().skip(1))
+                .map(|(a, b)| a.x as i32 * b.y as i32 - b.x as i32 * a.y as i32)
+                .sum::<i32>()
+        };
+        glyph.retain_contours(|contour| area(contour) != 0);
+        assert_eq!(glyph.contours, vec![first]);
+        assert_eq!(
+            glyph.bounds_rect(),
+            kurbo::Rect::new(0.0, 0.0, 100.0, 100.0)
+        );
+
+        glyph.retain_contours(|_| true);
+        assert_eq!(glyph.num_contours(), 1);
+        glyph.retain_contours(|contour| contour.len() > 3);
+        assert!(glyph.contours.is_empty());
+    }
+
+    #[test]
+    fn test_lazy_bounds() {
+        let mut glyph = GlyphBuilder::new()
+            .move_to(0, 0)
+            .line_to(100, 0)
+            .line_to(100, 100)
+            .build();
+        assert!(!glyph.bounds_dirty);
+        let original = glyph.clone();
+
+        // Edits mark the bounds as out of date without recalculating them
+        glyph
+            .push_contour(vec![
+                Point {
+                    x: 200,
+                    y: 0,
+                    on_curve: true,
+                },
+                Point {
+                    x: 300,
+                    y: 0,
+                    on_curve: true,
+                },
+                Point {
+                    x: 300,
+                    y: 150,
+                    on_curve: true,
+                },
+            ])
+            .unwrap();
+        glyph
+            .insert_point(
+                1,
+                1,
+                Point {
+                    x: 350,
+                    y: 50,
+                    on_curve: true,
+                },
+            )
+            .unwrap();
+        assert!(glyph.bounds_dirty);
+        assert_eq!(
+            (glyph.xMin, glyph.yMin, glyph.xMax, glyph.yMax),
+            (0, 0, 100, 100)
+        );
+        // Reading the bounds without storing them still gives fresh ones
+        let expected = kurbo::Rect::new(0.0, 0.0, 350.0, 150.0);
+        assert_eq!(glyph.bounds_rect(), expected);
+
+        // One call recalculates and stores them...
+        assert_eq!(glyph.bounds(), expected);
+        assert!(!glyph.bounds_dirty);
+        assert_eq!(
+            (glyph.xMin, glyph.yMin, glyph.xMax, glyph.yMax),
+            (0, 0, 350, 150)
+        );
+        // ...and later calls use the stored bounds rather than recalculating
+        glyph.xMax = 999;
+        assert_eq!(glyph.bounds().max_x(), 999.0);
+        glyph.xMax = 350;
+
+        // Any number of edits costs a single recalculation
+        BOUNDS_RECALCULATIONS.with(|n| n.set(0));
+        let mut edited = glyph.clone();
+        edited.translate(10, 20);
+        edited.flip_x(200);
+        edited.flip_y(100);
+        edited.scale_to_upm(1000, 2000);
+        edited.remove_contour(0);
+        assert!(edited.bounds_dirty);
+        assert_eq!(BOUNDS_RECALCULATIONS.with(|n| n.get()), 0);
+        let bounds = edited.bounds();
+        assert_eq!(bounds, super::super::simple_bounds(&edited));
+        edited.bounds();
+        assert_eq!(BOUNDS_RECALCULATIONS.with(|n| n.get()), 1);
+
+        // Serialization writes up-to-date bounds even if they are stale
+        let mut edited = original.clone();
+        edited.push_contour(glyph.contours[1].clone()).unwrap();
+        assert_eq!(
+            otspec::ser::to_bytes(&edited).unwrap(),
+            otspec::ser::to_bytes(&glyph).unwrap()
+        );
+
+        // The flag is not compared
+        let mut dirty = original.clone();
+        dirty.bounds_dirty = true;
+        assert_eq!(dirty, original);
+    }
+
+    #[test]
+    fn test_summary() {
+        let glyph = GlyphBuilder::new()
+            .move_to(0, 0)
+            .line_to(100, 0)
+            .line_to(100, 100)
+            .move_to(200, 0)
+            .quad_to(250, 50, 300, 0)
+            .build();
+        assert_eq!(
+            glyph.summary(),
+            "2 contours, 6 points, 0 components, bounds (0, 0, 300, 100)"
+        );
+        let composite = Glyph {
+            components: vec![component(1, ComponentFlags::empty())],
+            ..Glyph::empty()
+        };
+        assert!(composite.summary().contains("1 components"));
+    }
+
+    #[test]
+    fn test_strip_instructions() {
+        let mut glyph = GlyphBuilder::new()
+            .move_to(0, 0)
+            .line_to(100, 0)
+            .line_to(100, 100)
+            .build();
+        glyph.instructions = vec![0xb0, 0x01, 0x2f];
+        let mut stripped = glyph.clone();
+        stripped.strip_instructions();
+        assert!(stripped.instructions.is_empty());
+        assert_eq!(stripped.contours, glyph.contours);
+        assert_eq!(stripped.bounds_rect(), glyph.bounds_rect());
+
+        let binary_composite = vec![
+            0xff, 0xff, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x02, 0x00, 0x01,
+            0x0a, 0x14, 0x00, 0x01, 0xb0,
+        ];
+        let mut composite: Glyph = otspec::de::from_bytes(&binary_composite).unwrap();
+        assert_eq!(composite.instructions, vec![0xb0]);
+        let components = composite.components.clone();
+        composite.strip_instructions();
+        assert!(composite.instructions.is_empty());
+        assert!(!composite.components[0]
+            .flags
+            .contains(ComponentFlags::WE_HAVE_INSTRUCTIONS));
+        assert_eq!(
+            composite.components[0].transformation,
+            components[0].transformation
+        );
+        let serialized = otspec::ser::to_bytes(&composite).unwrap();
+        assert_eq!(
+            serialized,
+            vec![
+                0xff, 0xff, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x00, 0x01,
+                0x0a, 0x14,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_composite_instructions() {
+        let binary_composite = vec![
+            0xff, 0xff, 0x00, 0x00, 0x00, 0x00, 0x00, 0x64, 0x00, 0x64, 0x00, 0x22, 0x00, 0x01,
+            0x00, 0x00, 0x01, 0x02, 0x00, 0x02, 0x32, 0x00, 0x00, 0x03, 0xb0, 0x01, 0x2f,
+        ];
+        let composite: Glyph = otspec::de::from_bytes(&binary_composite).unwrap();
+        assert_eq!(composite.components.len(), 2);
+        assert_eq!(composite.instructions, vec![0xb0, 0x01, 0x2f]);
+        let serialized = otspec::ser::to_bytes(&composite).unwrap();
+        assert_eq!(serialized, binary_composite);
+
+        // Adding instructions to an unhinted composite sets the flag on the
+        // last component
+        let mut unhinted = composite.clone();
+        unhinted.strip_instructions();
+        let serialized = otspec::ser::to_bytes(&unhinted).unwrap();
+        assert_eq!(serialized.len(), binary_composite.len() - 5);
+        unhinted.instructions = vec![0xb0, 0x01, 0x2f];
+        assert_eq!(otspec::ser::to_bytes(&unhinted).unwrap(), binary_composite);
+    }
+
+    #[test]
+    fn test_bad_end_points() {
+        // Two contours ending at points 2 and 2; the second would be empty
+        let binary_glyph = vec![
+            0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x64, 0x00, 0x64, 0x00, 0x02, 0x00, 0x02,
+            0x00, 0x00, 0x31, 0x33, 0x31, 0x64, 0x64,
+        ];
+        let err = otspec::de::from_bytes::<Glyph>(&binary_glyph).unwrap_err();
+        assert_eq!(err.0, "Contour end points are not increasing (2 then 2)");
+
+        // Decreasing end points
+        let mut decreasing = binary_glyph.clone();
+        decreasing[13] = 0x01;
+        assert!(otspec::de::from_bytes::<Glyph>(&decreasing).is_err());
+
+        // A truncated end point array is an error rather than a panic
+        assert!(otspec::de::from_bytes::<Glyph>(&binary_glyph[..13]).is_err());
+
+        // Increasing end points are fine
+        let mut fixed = binary_glyph;
+        fixed[11] = 0x00;
+        let glyph: Glyph = otspec::de::from_bytes(&fixed).unwrap();
+        assert_eq!(glyph.contours.len(), 2);
+        assert_eq!(glyph.contours[0].len(), 1);
+        assert_eq!(glyph.contours[1].len(), 2);
+    }
+
+    #[test]
+    fn test_composite_without_components() {
+        let binary_glyph = vec![0xff, 0xff, 0x00, 0x00, 0x00, 0x00, 0x00, 0x64, 0x00, 0x64];
+        let glyph: Glyph = otspec::de::from_bytes(&binary_glyph).unwrap();
+        assert_eq!(glyph, Glyph::empty());
+        assert!(otspec::ser::to_bytes(&glyph).unwrap().is_empty());
+
+        // Followed by another glyph in the table, the header is still empty
+        let square = GlyphBuilder::new()
+            .move_to(0, 0)
+            .line_to(100, 0)
+            .line_to(100, 100)
+            .build();
+        let mut table = binary_glyph.clone();
+        table.extend([0, 0]);
+        table.extend(otspec::ser::to_bytes(&square).unwrap());
+        let glyf = crate::tables::glyf::from_bytes(&table, &[Some(0), Some(12)]).unwrap();
+        assert_eq!(glyf.glyphs[0], Glyph::empty());
+        assert_eq!(glyf.glyphs[1], square);
+    }
+
+    #[test]
+    fn test_coordinate_delta_overflow() {
+        let mut glyph = GlyphBuilder::new()
+            .move_to(-20000, 0)
+            .line_to(20000, 0)
+            .line_to(20000, 100)
+            .build();
+        let mut data = vec![];
+        let err = glyph.to_bytes(&mut data).unwrap_err();
+        assert_eq!(
+            err.0,
+            "Point 1 is too far (40000 units) from the point before it"
+        );
+        assert!(data.is_empty());
+
+        // The same distance in two steps is fine
+        glyph.contours[0].insert(
+            1,
+            Point {
+                x: 0,
+                y: 0,
+                on_curve: true,
+            },
+        );
+        let bytes = otspec::ser::to_bytes(&glyph).unwrap();
+        let roundtripped: Glyph = otspec::de::from_bytes(&bytes).unwrap();
+        assert_eq!(roundtripped.contours, glyph.contours);
+    }
+
+    #[test]
+    fn test_oversized_instructions() {
+        let mut glyph = GlyphBuilder::new()
+            .move_to(0, 0)
+            .line_to(100, 0)
+            .line_to(100, 100)
+            .build();
+        glyph.instructions = vec![0; u16::MAX as usize];
+        assert!(otspec::ser::to_bytes(&glyph).is_ok());
+
+        glyph.instructions.push(0);
+        let mut data = vec![];
+        let err = glyph.to_bytes(&mut data).unwrap_err();
+        assert_eq!(err.0, "Too many instructions in glyph (65536)");
+        assert!(data.is_empty());
+
+        let composite = Glyph {
+            components: vec![component(0, ComponentFlags::empty())],
+            instructions: glyph.instructions.clone(),
+            ..Glyph::empty()
+        };
+        assert!(otspec::ser::to_bytes(&composite).is_err());
+    }
+
+    #[test]
+    fn test_set_start_points() {
+        let glyph = GlyphBuilder::new()
+            .move_to(0, 0)
+            .line_to(100, 0)
+            .quad_to(100, 100, 50, 100)
+            .line_to(0, 100)
+            .quad_to(-20, 50, 0, 0)
+            .move_to(200, 0)
+            .line_to(300, 0)
+            .line_to(250, 50)
+            .build();
+        let mut rotated = glyph.clone();
+        rotated.contours[0].rotate_left(3);
+        rotated.contours[1].rotate_left(1);
+        assert!(!glyph.outline_eq(&rotated));
+
+        let mut normalized = glyph.clone();
+        normalized.set_start_points();
+        rotated.set_start_points();
+        assert_eq!(normalized, rotated);
+        assert_eq!(
+            normalized.contours[0][0],
+            Point {
+                x: 0,
+                y: 100,
+                on_curve: true
+            }
+        );
+        assert_eq!(normalized.contours[1][0].y, 50);
+
+        // The same segments are drawn, just starting from different points
+        let before = glyph.to_bezpath();
+        let after = normalized.to_bezpath();
+        assert!((before.area() - after.area()).abs() < 1e-9);
+        assert_eq!(before.bounding_box(), after.bounding_box());
+        let segments = |path: &kurbo::BezPath| {
+            let mut segments: Vec<String> =
+                path.segments().map(|seg| format!("{:?}", seg)).collect();
+            segments.sort();
+            segments
+        };
+        assert_eq!(segments(&before), segments(&after));
+        assert_ne!(before, after);
+
+        // Only off-curve points: nothing to rotate to
+        let mut offcurve = glyph.clone();
+        for pt in offcurve.contours[1].iter_mut() {
+            pt.on_curve = false;
+        }
+        let expected = offcurve.contours[1].clone();
+        offcurve.set_start_points();
+        assert_eq!(offcurve.contours[1], expected);
+    }
+
+    #[test]
+    fn test_num_points() {
+        let simple = GlyphBuilder::new()
+            .move_to(0, 0)
+            .line_to(100, 0)
+            .line_to(100, 100)
+            .move_to(200, 0)
+            .quad_to(250, 50, 300, 0)
+            .build();
+        assert_eq!(simple.num_points(false), 6);
+        assert_eq!(simple.num_points(true), 10);
+        assert_eq!(
+            simple.num_points(true),
+            simple.gvar_coords_and_ends().0.len()
+        );
+
+        let composite = Glyph {
+            components: vec![
+                component(1, ComponentFlags::empty()),
+                component(2, ComponentFlags::empty()),
+            ],
+            ..Glyph::empty()
+        };
+        assert_eq!(composite.num_points(false), 2);
+        assert_eq!(composite.num_points(true), 6);
+        assert_eq!(
+            composite.num_points(true),
+            composite.gvar_coords_and_ends().0.len()
+        );
+
+        // Contours alongside components are not written, so not counted
+        let mixed = Glyph {
+            components: composite.components.clone(),
+            ..simple.clone()
+        };
+        assert_eq!(mixed.num_points(true), 6);
+        let (coords, ends) = mixed.gvar_coords_and_ends();
+        assert_eq!(coords.len(), mixed.num_points(true));
+        assert_eq!(ends, vec![0, 1, 2, 3, 4, 5]);
+
+        assert_eq!(Glyph::empty().num_points(false), 0);
+        assert_eq!(Glyph::empty().num_points(true), 4);
+    }
+
+    #[test]
+    fn test_segments() {
+        let triangle = GlyphBuilder::new()
+            .move_to(0, 0)
+            .line_to(100, 0)
+            .line_to(50, 100)
+            .build();
+        assert_eq!(
+            triangle.segments().collect::<Vec<_>>(),
+            vec![
+                Segment::Line(KPoint::new(0.0, 0.0), KPoint::new(100.0, 0.0)),
+                Segment::Line(KPoint::new(100.0, 0.0), KPoint::new(50.0, 100.0)),
+                Segment::Line(KPoint::new(50.0, 100.0), KPoint::new(0.0, 0.0)),
+            ]
+        );
+
+        // A contour starting off-curve, with an implied on-curve point
+        let mut curved = GlyphBuilder::new()
+            .move_to(0, 0)
+            .quad_to(0, 100, 50, 100)
+            .build();
+        curved.contours[0][2].on_curve = false;
+        curved.contours[0].rotate_left(1);
+        assert_eq!(
+            curved.segments().collect::<Vec<_>>(),
+            vec![
+                Segment::Quad(
+                    KPoint::new(0.0, 0.0),
+                    KPoint::new(0.0, 100.0),
+                    KPoint::new(25.0, 100.0)
+                ),
+                Segment::Quad(
+                    KPoint::new(25.0, 100.0),
+                    KPoint::new(50.0, 100.0),
+                    KPoint::new(0.0, 0.0)
+                ),
+            ]
+        );
+
+        // Only off-curve points
+        let mut circle = square_glyph();
+        for pt in circle.contours[0].iter_mut() {
+            pt.on_curve = false;
+        }
+        let segments: Vec<Segment> = circle.segments().collect();
+        assert_eq!(segments.len(), 4);
+        assert_eq!(
+            segments[0],
+            Segment::Quad(
+                KPoint::new(0.0, 50.0),
+                KPoint::new(0.0, 0.0),
+                KPoint::new(50.0, 0.0)
+            )
+        );
+        assert!(Glyph::empty().segments().next().is_none());
+    }
+
+    #[test]
+    fn test_contains() {
+        let glyf = sample_glyf();
+        let cap_a = &glyf.glyphs[0];
+        // The counter between the two strokes, above the crossbar
+        assert!(!cap_a.contains(378.0, 400.0));
+        // Below the crossbar, between the strokes
+        assert!(!cap_a.contains(378.0, 100.0));
+        assert!(cap_a.contains(250.0, 400.0));
+        assert!(cap_a.contains(378.0, 250.0));
+        // The apex, where the strokes overlap
+        assert!(cap_a.contains(378.0, 650.0));
+        assert!(!cap_a.contains(-10.0, 0.0));
+        // On an edge
+        assert!(cap_a.contains(60.0, 0.0));
+
+        // A hole drawn in the opposite direction
+        let mut ring = GlyphBuilder::new()
+            .move_to(0, 0)
+            .line_to(300, 0)
+            .line_to(300, 300)
+            .line_to(0, 300)
+            .move_to(100, 100)
+            .line_to(100, 200)
+            .line_to(200, 200)
+            .line_to(200, 100)
+            .build();
+        assert!(ring.contains(50.0, 150.0));
+        assert!(!ring.contains(150.0, 150.0));
+        // Drawn in the same direction, the hole is filled
+        ring.contours[1].reverse();
+        assert!(ring.contains(150.0, 150.0));
+
+        let curve = GlyphBuilder::new()
+            .move_to(0, 0)
+            .quad_to(100, 200, 200, 0)
+            .build();
+        assert!(curve.contains(100.0, 90.0));
+        assert!(!curve.contains(100.0, 110.0));
+    }
+
+    #[test]
+    fn test_component_transform_problems() {
+        let triangle = GlyphBuilder::new()
+            .move_to(0, 0)
+            .line_to(0, 100)
+            .line_to(100, 0)
+            .build();
+        let mut reversed = triangle.clone();
+        reversed.contours[0][1..].reverse();
+        let mut glyphs = vec![Glyph::empty(); 5];
+        glyphs[3] = triangle;
+        let mut glyph = Glyph::empty();
+        glyph.components.push(Component {
+            glyph_index: 3,
+            transformation: kurbo::Affine::new([-1.0, 0.0, 0.0, 1.0, 500.0, 0.0]),
+            match_points: None,
+            flags: ComponentFlags::empty(),
+        });
+        glyph.components.push(Component {
+            glyph_index: 4,
+            transformation: kurbo::Affine::new([3.0, 0.0, 0.0, 1.0, 0.0, 0.0]),
+            match_points: None,
+            flags: ComponentFlags::empty(),
+        });
+        assert_eq!(
+            glyph.component_transform_problems(&glyphs),
+            vec![
+                "Component 0 reflects glyph 3, reversing the direction of its contours",
+                "Component 1: x scale of 3 is out of the F2DOT14 range",
+            ]
+        );
+        glyph.components.truncate(1);
+        // A base glyph drawn the other way round is correct once reflected
+        glyphs[3] = reversed;
+        assert!(glyph.component_transform_problems(&glyphs).is_empty());
+        // A reflected glyph with no contours is not a problem either
+        glyph.components[0].glyph_index = 4;
+        assert!(glyph.component_transform_problems(&glyphs).is_empty());
+        glyph.components[0].glyph_index = 3;
+        glyph.components[0].transformation = kurbo::Affine::translate((500.0, 0.0));
+        assert!(glyph.component_transform_problems(&glyphs).is_empty());
+    }
+
+    #[test]
+    fn test_containment() {
+        let glyf = sample_glyf();
+        // The strokes of the A cross, but neither is inside the other
+        let cap_a = &glyf.glyphs[0];
+        assert_eq!(cap_a.containment(), vec![None; cap_a.contours.len()]);
+
+        let square = |x: i16, y: i16, size: i16| {
+            GlyphBuilder::new()
+                .move_to(x, y)
+                .line_to(x, y + size)
+                .line_to(x + size, y + size)
+                .line_to(x + size, y)
+                .build()
+                .contours
+                .remove(0)
+        };
+        // A ring with an island in its hole, and a separate square
+        let mut glyph = Glyph::empty();
+        glyph.contours.push(square(100, 100, 100));
+        glyph.contours.push(square(0, 0, 300));
+        glyph.contours.push(square(400, 0, 100));
+        glyph.contours.push(square(125, 125, 50));
+        assert_eq!(glyph.containment(), vec![Some(1), None, None, Some(0)]);
+        // Direction does not matter
+        glyph.contours[0].reverse();
+        assert_eq!(glyph.containment(), vec![Some(1), None, None, Some(0)]);
+    }
+
+    #[test]
+    fn test_contour_orientation() {
+        let mut glyph = GlyphBuilder::new()
+            .move_to(0, 0)
+            .line_to(0, 100)
+            .line_to(100, 100)
+            .line_to(100, 0)
+            .move_to(200, 0)
+            .line_to(300, 0)
+            .line_to(300, 100)
+            .move_to(400, 0)
+            .line_to(500, 0)
+            .line_to(600, 0)
+            .build();
+        assert_eq!(glyph.contour_orientation(0), Orientation::Clockwise);
+        assert_eq!(glyph.contour_orientation(1), Orientation::CounterClockwise);
+        assert_eq!(glyph.contour_orientation(2), Orientation::Degenerate);
+
+        // Degenerate contours are left alone by direction correction
+        let degenerate = glyph.contours[2].clone();
+        glyph.correct_direction();
+        assert_eq!(glyph.contour_orientation(1), Orientation::Clockwise);
+        assert_eq!(glyph.contours[2], degenerate);
+        glyph.correct_direction();
+        assert_eq!(glyph.contours[2], degenerate);
+    }
+
+    #[test]
+    fn test_sanitize() {
+        let pt = |x, y| Point {
+            x,
+            y,
+            on_curve: true,
+        };
+        let mut glyph = Glyph::empty();
+        // A counter-clockwise square with a repeated endpoint and a point in
+        // the middle of one side
+        glyph.contours.push(vec![
+            pt(0, 0),
+            pt(300, 0),
+            pt(300, 150),
+            pt(300, 300),
+            pt(0, 300),
+            pt(0, 0),
+        ]);
+        // A clockwise hole
+        glyph
+            .contours
+            .push(vec![pt(100, 100), pt(100, 200), pt(200, 200), pt(200, 100)]);
+        // Degenerate contours
+        glyph
+            .contours
+            .push(vec![pt(400, 0), pt(500, 0), pt(600, 0)]);
+        glyph.contours.push(vec![pt(700, 700)]);
+        glyph.contours.push(vec![]);
+        glyph.xMax = 1000;
+
+        glyph.sanitize();
+        assert!(!glyph.bounds_dirty);
+        assert_eq!(
+            glyph.contours,
+            vec![
+                vec![pt(0, 0), pt(0, 300), pt(300, 300), pt(300, 0)],
+                vec![pt(100, 100), pt(200, 100), pt(200, 200), pt(100, 200)],
+            ]
+        );
+        assert_eq!(
+            (glyph.xMin, glyph.yMin, glyph.xMax, glyph.yMax),
+            (0, 0, 300, 300)
+        );
+        let clean = glyph.clone();
+        glyph.sanitize();
+        assert_eq!(glyph, clean);
+
+        // The sample glyphs are already clean
+        let glyf = sample_glyf();
+        let mut o = glyf.glyphs[2].clone();
+        o.sanitize();
+        assert!(o.outline_eq(&glyf.glyphs[2]));
+    }
+
+    #[test]
+    fn test_to_cubic_bezpath() {
+        let glyph = GlyphBuilder::new()
+            .move_to(0, 0)
+            .quad_to(150, 300, 300, 0)
+            .build();
+        let path = glyph.to_cubic_bezpath();
+        assert_eq!(
+            path.elements(),
+            &[
+                PathEl::MoveTo(KPoint::new(0.0, 0.0)),
+                PathEl::CurveTo(
+                    KPoint::new(100.0, 200.0),
+                    KPoint::new(200.0, 200.0),
+                    KPoint::new(300.0, 0.0)
+                ),
+                PathEl::LineTo(KPoint::new(0.0, 0.0)),
+                PathEl::ClosePath,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_to_cubic_bezpath_implied_oncurve() {
+        // Two consecutive off-curve points imply an on-curve point between them
+        let glyph = GlyphBuilder::new()
+            .move_to(0, 0)
+            .quad_to(0, 300, 150, 300)
+            .build();
+        let mut implied = glyph;
+        implied.contours[0][2] = Point {
+            x: 300,
+            y: 300,
+            on_curve: false,
+        };
+        let path = implied.to_cubic_bezpath();
+        assert!(path
+            .elements()
+            .iter()
+            .all(|el| !matches!(el, PathEl::QuadTo(_, _))));
+        assert_eq!(
+            path.elements()[1],
+            PathEl::CurveTo(
+                KPoint::new(0.0, 200.0),
+                KPoint::new(50.0, 300.0),
+                KPoint::new(150.0, 300.0)
+            )
+        );
+    }
 }
@@ -0,0 +1,248 @@
+use super::component::ComponentFlags;
+use super::glyph::Glyph;
+
+/// Component flags which must agree between masters. The others are
+/// recomputed from the component's transformation when it is serialized.
+const INTERPOLATABLE_FLAGS: ComponentFlags = ComponentFlags::from_bits_truncate(
+    ComponentFlags::ROUND_XY_TO_GRID.bits()
+        | ComponentFlags::USE_MY_METRICS.bits()
+        | ComponentFlags::SCALED_COMPONENT_OFFSET.bits()
+        | ComponentFlags::UNSCALED_COMPONENT_OFFSET.bits()
+        | ComponentFlags::OVERLAP_COMPOUND.bits(),
+);
+
+/// The first difference found between two glyphs which prevents them from
+/// being interpolated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IncompatibilityReport {
+    /// The glyphs have different numbers of contours.
+    ContourCount(usize, usize),
+    /// A contour has a different number of points in each glyph.
+    PointCount {
+        /// The index of the contour.
+        contour: usize,
+        /// The number of points in the first glyph's contour.
+        a: usize,
+        /// The number of points in the second glyph's contour.
+        b: usize,
+    },
+    /// A point is on-curve in one glyph but off-curve in the other.
+    OnCurve {
+        /// The index of the contour.
+        contour: usize,
+        /// The index of the point within the contour.
+        point: usize,
+    },
+    /// The glyphs have different numbers of components.
+    ComponentCount(usize, usize),
+    /// A component refers to a different glyph in each glyph.
+    ComponentGlyph {
+        /// The index of the component.
+        component: usize,
+        /// The glyph ID used by the first glyph's component.
+        a: u16,
+        /// The glyph ID used by the second glyph's component.
+        b: u16,
+    },
+    /// A component has different flags in each glyph, or is positioned by
+    /// offset in one glyph and by matching points in the other.
+    ComponentFlags {
+        /// The index of the component.
+        component: usize,
+    },
+    /// A coordinate differs by more than a 16-bit delta can hold.
+    DeltaOverflow,
+}
+
+impl std::fmt::Display for IncompatibilityReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ContourCount(a, b) => write!(f, "contour count differs ({} != {})", a, b),
+            Self::PointCount { contour, a, b } => write!(
+                f,
+                "point count of contour {} differs ({} != {})",
+                contour, a, b
+            ),
+            Self::OnCurve { contour, point } => write!(
+                f,
+                "on-curve flag of point {} in contour {} differs",
+                point, contour
+            ),
+            Self::ComponentCount(a, b) => write!(f, "component count differs ({} != {})", a, b),
+            Self::ComponentGlyph { component, a, b } => write!(
+                f,
+                "component {} refers to different glyphs ({} != {})",
+                component, a, b
+            ),
+            Self::ComponentFlags { component } => {
+                write!(f, "flags of component {} differ", component)
+            }
+            Self::DeltaOverflow => write!(f, "coordinate difference does not fit in 16 bits"),
+        }
+    }
+}
+
+impl std::error::Error for IncompatibilityReport {}
+
+/// Checks whether two masters of a glyph can be interpolated.
+///
+/// The glyphs must have the same number of contours, the same number of
+/// points in each contour, and the same on-curve pattern. Composite glyphs
+/// must have the same components, in the same order, with the same flags.
+/// Coordinates and transformations are free to differ. The first mismatch
+/// found is returned.
+pub fn check_interpolatable(a: &Glyph, b: &Glyph) -> Result<(), IncompatibilityReport> {
+    if a.contours.len() != b.contours.len() {
+        return Err(IncompatibilityReport::ContourCount(
+            a.contours.len(),
+            b.contours.len(),
+        ));
+    }
+    for (contour, (ca, cb)) in a.contours.iter().zip(b.contours.iter()).enumerate() {
+        if ca.len() != cb.len() {
+            return Err(IncompatibilityReport::PointCount {
+                contour,
+                a: ca.len(),
+                b: cb.len(),
+            });
+        }
+        if let Some(point) = ca
+            .iter()
+            .zip(cb.iter())
+            .position(|(pa, pb)| pa.on_curve != pb.on_curve)
+        {
+            return Err(IncompatibilityReport::OnCurve { contour, point });
+        }
+    }
+    if a.components.len() != b.components.len() {
+        return Err(IncompatibilityReport::ComponentCount(
+            a.components.len(),
+            b.components.len(),
+        ));
+    }
+    for (component, (ca, cb)) in a.components.iter().zip(b.components.iter()).enumerate() {
+        if ca.glyph_index != cb.glyph_index {
+            return Err(IncompatibilityReport::ComponentGlyph {
+                component,
+                a: ca.glyph_index,
+                b: cb.glyph_index,
+            });
+        }
+        if ca.flags & INTERPOLATABLE_FLAGS != cb.flags & INTERPOLATABLE_FLAGS
+            || ca.match_points.is_some() != cb.match_points.is_some()
+        {
+            return Err(IncompatibilityReport::ComponentFlags { component });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tables::glyf::{Component, GlyphBuilder, Point};
+
+    fn triangle(x: i16) -> Glyph {
+        GlyphBuilder::new()
+            .move_to(0, 0)
+            .line_to(x, 0)
+            .quad_to(x, 100, 0, 100)
+            .build()
+    }
+
+    #[test]
+    fn test_check_interpolatable() {
+        assert_eq!(check_interpolatable(&triangle(100), &triangle(300)), Ok(()));
+
+        let mut extra_point = triangle(300);
+        extra_point.contours[0].insert(
+            1,
+            Point {
+                x: 50,
+                y: 0,
+                on_curve: true,
+            },
+        );
+        assert_eq!(
+            check_interpolatable(&triangle(100), &extra_point),
+            Err(IncompatibilityReport::PointCount {
+                contour: 0,
+                a: 4,
+                b: 5
+            })
+        );
+
+        let mut straight = triangle(300);
+        straight.contours[0][2].on_curve = true;
+        assert_eq!(
+            check_interpolatable(&triangle(100), &straight),
+            Err(IncompatibilityReport::OnCurve {
+                contour: 0,
+                point: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_contour_signatures() {
+        assert_eq!(
+            triangle(100).contour_signatures(),
+            vec![vec![true, true, false, true]]
+        );
+        assert_eq!(
+            triangle(100).contour_signatures(),
+            triangle(300).contour_signatures()
+        );
+
+        let mut straight = triangle(300);
+        straight.contours[0][2].on_curve = true;
+        assert!(check_interpolatable(&triangle(100), &straight).is_err());
+        assert_ne!(
+            triangle(100).contour_signatures(),
+            straight.contour_signatures()
+        );
+    }
+
+    #[test]
+    fn test_check_interpolatable_components() {
+        let composite = |glyph_index, x, flags| Glyph {
+            components: vec![Component {
+                glyph_index,
+                transformation: kurbo::Affine::translate((x, 0.0)),
+                match_points: None,
+                flags,
+            }],
+            ..Glyph::empty()
+        };
+        // Flags derived from the transformation may differ
+        assert_eq!(
+            check_interpolatable(
+                &composite(1, 10.0, ComponentFlags::ARGS_ARE_XY_VALUES),
+                &composite(
+                    1,
+                    1000.0,
+                    ComponentFlags::ARGS_ARE_XY_VALUES | ComponentFlags::ARG_1_AND_2_ARE_WORDS
+                ),
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            check_interpolatable(
+                &composite(1, 10.0, ComponentFlags::empty()),
+                &composite(2, 10.0, ComponentFlags::empty())
+            ),
+            Err(IncompatibilityReport::ComponentGlyph {
+                component: 0,
+                a: 1,
+                b: 2
+            })
+        );
+        assert_eq!(
+            check_interpolatable(
+                &composite(1, 10.0, ComponentFlags::empty()),
+                &composite(1, 10.0, ComponentFlags::USE_MY_METRICS)
+            ),
+            Err(IncompatibilityReport::ComponentFlags { component: 0 })
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use super::glyph::Glyph;
+use crate::tables::maxp::maxp10;
+
+/// A way in which a glyph exceeds the limits declared in the `maxp` table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LimitViolation {
+    /// A simple glyph has more points than `maxPoints`, or a composite glyph
+    /// has more points than `maxCompositePoints`.
+    TooManyPoints {
+        /// The number of points in the glyph.
+        points: usize,
+        /// The declared limit.
+        limit: u16,
+    },
+    /// A simple glyph has more contours than `maxContours`, or a composite
+    /// glyph has more contours than `maxCompositeContours`.
+    TooManyContours {
+        /// The number of contours in the glyph.
+        contours: usize,
+        /// The declared limit.
+        limit: u16,
+    },
+    /// The glyph has more components than `maxComponentElements`.
+    TooManyComponents {
+        /// The number of components in the glyph.
+        components: usize,
+        /// The declared limit.
+        limit: u16,
+    },
+    /// The glyph's components are nested more deeply than `maxComponentDepth`.
+    TooDeep {
+        /// The nesting depth of the glyph's components.
+        depth: u16,
+        /// The declared limit.
+        limit: u16,
+    },
+}
+
+impl std::fmt::Display for LimitViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooManyPoints { points, limit } => {
+                write!(f, "{} points exceeds the limit of {}", points, limit)
+            }
+            Self::TooManyContours { contours, limit } => {
+                write!(f, "{} contours exceeds the limit of {}", contours, limit)
+            }
+            Self::TooManyComponents { components, limit } => {
+                write!(
+                    f,
+                    "{} components exceeds the limit of {}",
+                    components, limit
+                )
+            }
+            Self::TooDeep { depth, limit } => write!(
+                f,
+                "component depth {} exceeds the limit of {}",
+                depth, limit
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LimitViolation {}
+
+impl Glyph {
+    /// Checks this glyph against the limits declared in a `maxp` table.
+    ///
+    /// `glyphs` is the full list of glyphs in the font, which is needed to
+    /// count the points and contours of composite glyphs and the depth of
+    /// their components. Returns the first limit found to be exceeded.
+    pub fn exceeds_limits(&self, glyphs: &[Glyph], limits: &maxp10) -> Option<LimitViolation> {
+        if !self.has_components() {
+            let points = self.num_points(false);
+            if points > limits.maxPoints as usize {
+                return Some(LimitViolation::TooManyPoints {
+                    points,
+                    limit: limits.maxPoints,
+                });
+            }
+            if self.contours.len() > limits.maxContours as usize {
+                return Some(LimitViolation::TooManyContours {
+                    contours: self.contours.len(),
+                    limit: limits.maxContours,
+                });
+            }
+            return None;
+        }
+        if self.components.len() > limits.maxComponentElements as usize {
+            return Some(LimitViolation::TooManyComponents {
+                components: self.components.len(),
+                limit: limits.maxComponentElements,
+            });
+        }
+        let info = self.composite_maxp_values(glyphs)?;
+        if info.num_points > limits.maxCompositePoints {
+            return Some(LimitViolation::TooManyPoints {
+                points: info.num_points.into(),
+                limit: limits.maxCompositePoints,
+            });
+        }
+        if info.num_contours > limits.maxCompositeContours {
+            return Some(LimitViolation::TooManyContours {
+                contours: info.num_contours.into(),
+                limit: limits.maxCompositeContours,
+            });
+        }
+        if info.max_depth > limits.maxComponentDepth {
+            return Some(LimitViolation::TooDeep {
+                depth: info.max_depth,
+                limit: limits.maxComponentDepth,
+            });
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tables::glyf::tests::{sample_glyf, square_glyph};
+    use crate::tables::maxp::MaxpVariant;
+
+    fn limits(glyf: &crate::tables::glyf::glyf) -> maxp10 {
+        match glyf.as_maxp10().table {
+            MaxpVariant::Maxp10(t) => t,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_exceeds_limits() {
+        let glyf = sample_glyf();
+        let mut limits = limits(&glyf);
+        for g in glyf.glyphs.iter() {
+            assert_eq!(g.exceeds_limits(&glyf.glyphs, &limits), None);
+        }
+
+        let square = square_glyph();
+        limits.maxPoints = 3;
+        assert_eq!(
+            square.exceeds_limits(&glyf.glyphs, &limits),
+            Some(LimitViolation::TooManyPoints {
+                points: 4,
+                limit: 3
+            })
+        );
+        limits.maxPoints = 4;
+        assert_eq!(square.exceeds_limits(&glyf.glyphs, &limits), None);
+
+        limits.maxComponentDepth = 0;
+        assert_eq!(
+            glyf.glyphs[1].exceeds_limits(&glyf.glyphs, &limits),
+            Some(LimitViolation::TooDeep { depth: 1, limit: 0 })
+        );
+    }
+}
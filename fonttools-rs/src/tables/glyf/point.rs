@@ -1,4 +1,5 @@
 use kurbo::Affine;
+use otmath::RoundMode;
 use otspec::types::*;
 
 /// Represents a point inside a glyf::Contour
@@ -16,7 +17,8 @@ impl Point {
     /// Transforms the point using the given affine transformation
     ///
     /// When supplied with a kurbo::Affine object, returns a new
-    /// point with the transformation applied.
+    /// point with the transformation applied. Coordinates are truncated
+    /// towards zero; see [`Point::transform_with_rounding`] to round them.
     pub fn transform(&self, t: Affine) -> Point {
         let kurbo_point = t * kurbo::Point::new(self.x as f64, self.y as f64);
         Point {
@@ -25,4 +27,39 @@ impl Point {
             on_curve: self.on_curve,
         }
     }
+
+    /// Transforms the point using the given affine transformation, rounding
+    /// the resulting coordinates with the given strategy.
+    pub fn transform_with_rounding(&self, t: Affine, rounding: RoundMode) -> Point {
+        let kurbo_point = t * kurbo::Point::new(self.x as f64, self.y as f64);
+        Point {
+            x: rounding.round(kurbo_point.x) as i16,
+            y: rounding.round(kurbo_point.y) as i16,
+            on_curve: self.on_curve,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transform_rounding() {
+        let pt = Point {
+            x: 5,
+            y: -5,
+            on_curve: true,
+        };
+        let half = Affine::scale(0.5);
+        assert_eq!((pt.transform(half).x, pt.transform(half).y), (2, -2));
+        let rounded = |mode| {
+            let p = pt.transform_with_rounding(half, mode);
+            (p.x, p.y)
+        };
+        assert_eq!(rounded(RoundMode::Floor), (2, -3));
+        assert_eq!(rounded(RoundMode::Ceil), (3, -2));
+        assert_eq!(rounded(RoundMode::Round), (3, -3));
+        assert_eq!(rounded(RoundMode::RoundHalfToEven), (2, -2));
+    }
 }
@@ -0,0 +1,125 @@
+use super::contourutils::glyf_contour_to_kurbo_contour;
+use super::glyf;
+use super::glyph::Glyph;
+use kurbo::{BezPath, PathEl, Point, Rect, Shape};
+
+impl Glyph {
+    /// Returns the glyph's contours as a kurbo path of quadratic curves.
+    ///
+    /// Each non-empty contour becomes a closed subpath starting at an
+    /// on-curve point. Components are not included.
+    pub fn to_bezpath(&self) -> BezPath {
+        let mut path = BezPath::new();
+        for contour in self.contours.iter().filter(|c| !c.is_empty()) {
+            path.extend(glyf_contour_to_kurbo_contour(contour));
+        }
+        path
+    }
+}
+
+/// A glyph in a `glyf` table, as a kurbo shape including its components.
+///
+/// The glyph is decomposed (see [`glyf::decompose_glyph`]) each time the
+/// shape is queried, so when making many queries of the same glyph it is
+/// cheaper to decompose it once and use [`Glyph::to_bezpath`] on the result.
+/// A glyph which cannot be decomposed is treated as empty.
+#[derive(Debug, Clone, Copy)]
+pub struct GlyphShape<'a> {
+    /// The table containing the glyph and its components.
+    pub table: &'a glyf,
+    /// The ID of the glyph.
+    pub gid: usize,
+}
+
+impl GlyphShape<'_> {
+    fn outline(&self) -> Glyph {
+        self.table.decompose_glyph(self.gid).unwrap_or_else(|e| {
+            log::warn!("Could not decompose glyph {}: {}", self.gid, e);
+            Glyph::empty()
+        })
+    }
+}
+
+impl Shape for GlyphShape<'_> {
+    type PathElementsIter = std::vec::IntoIter<PathEl>;
+
+    fn path_elements(&self, _tolerance: f64) -> Self::PathElementsIter {
+        self.outline().to_bezpath().elements().to_vec().into_iter()
+    }
+
+    fn area(&self) -> f64 {
+        self.outline().to_bezpath().area()
+    }
+
+    fn perimeter(&self, accuracy: f64) -> f64 {
+        self.outline().to_bezpath().perimeter(accuracy)
+    }
+
+    fn winding(&self, pt: Point) -> i32 {
+        self.outline().to_bezpath().winding(pt)
+    }
+
+    fn bounding_box(&self) -> Rect {
+        self.outline().to_bezpath().bounding_box()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tables::glyf::tests::sample_glyf;
+    use crate::tables::glyf::GlyphBuilder;
+
+    /// The signed area of a polygon, by the shoelace formula.
+    fn shoelace(points: &[(f64, f64)]) -> f64 {
+        points
+            .iter()
+            .zip(points.iter().cycle().skip(1))
+            .map(|((x0, y0), (x1, y1))| x0 * y1 - x1 * y0)
+            .sum::<f64>()
+            / 2.0
+    }
+
+    #[test]
+    fn test_glyph_shape_area() {
+        let rect = GlyphBuilder::new()
+            .move_to(0, 0)
+            .line_to(100, 0)
+            .line_to(100, 50)
+            .line_to(0, 50)
+            .build()
+            .to_bezpath();
+        let expected = shoelace(&[(0.0, 0.0), (100.0, 0.0), (100.0, 50.0), (0.0, 50.0)]);
+        assert_eq!(rect.area(), expected);
+        assert_eq!(rect.area(), 5000.0);
+        assert_eq!(rect.bounding_box(), Rect::new(0.0, 0.0, 100.0, 50.0));
+        assert_eq!(rect.winding(Point::new(50.0, 25.0)), 1);
+        assert_eq!(rect.perimeter(1e-3), 300.0);
+
+        // The capital A of the sample font is made of straight lines only
+        let table = sample_glyf();
+        let cap_a = &table.glyphs[0];
+        let expected: f64 = cap_a
+            .contours
+            .iter()
+            .map(|c| {
+                let points: Vec<(f64, f64)> =
+                    c.iter().map(|pt| (pt.x as f64, pt.y as f64)).collect();
+                shoelace(&points)
+            })
+            .sum();
+        assert!((cap_a.to_bezpath().area() - expected).abs() < 1e-9);
+
+        // A composite includes its components
+        let aacute = GlyphShape {
+            table: &table,
+            gid: 1,
+        };
+        let acute_area = table.glyphs[7].to_bezpath().area();
+        assert!((aacute.area() - (expected + acute_area)).abs() < 1e-6);
+        assert_eq!(
+            aacute.path_elements(0.1).count(),
+            cap_a.to_bezpath().elements().len() + table.glyphs[7].to_bezpath().elements().len()
+        );
+    }
+}
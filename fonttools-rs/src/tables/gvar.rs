@@ -1,12 +1,16 @@
-use super::glyf::{glyf, Glyph};
+use super::glyf::{
+    check_interpolatable, glyf, Component, Glyph, IncompatibilityReport, MAX_COMPONENT_DEPTH,
+};
 use crate::otvar::iup::optimize_deltas;
 use crate::otvar::{
     Delta, TupleIndexFlags, TupleVariation, TupleVariationHeader, TupleVariationStore,
 };
 use counter::Counter;
+use otmath::{ot_round, support_scalar, Location, RoundMode, Support};
 use otspec::types::*;
 use otspec::{DeserializationError, Deserializer, ReaderContext, SerializationError, Serialize};
 use otspec_macros::tables;
+use std::collections::HashMap;
 use std::convert::TryInto;
 
 #[cfg(feature = "rayon")]
@@ -53,7 +57,7 @@ impl DeltaSet {
     ) -> TupleVariation {
         let mut serialized_peak: Vec<u8> = vec![];
         for p in &self.peak {
-            F2DOT14::from(*p).to_bytes(&mut serialized_peak).unwrap();
+            f32_to_f2dot14(*p).to_bytes(&mut serialized_peak).unwrap();
         }
         let index = shared_tuples.iter().position(|t| t == &serialized_peak);
         let mut flags = TupleIndexFlags::empty();
@@ -64,8 +68,7 @@ impl DeltaSet {
             shared_tuple_index = 0;
             flags |= TupleIndexFlags::EMBEDDED_PEAK_TUPLE;
         }
-        // This check is wrong. See Python compileIntermediateCoord
-        if self.peak != self.start || self.peak != self.end {
+        if self.has_intermediate_region() {
             flags |= TupleIndexFlags::INTERMEDIATE_REGION;
         }
 
@@ -124,6 +127,27 @@ impl DeltaSet {
         TupleVariation(tvh, deltas)
     }
 
+    /// Whether any of the deltas are non-zero.
+    ///
+    /// Delta sets which have no effect are not written to the font.
+    pub fn has_effect(&self) -> bool {
+        self.deltas.iter().any(|&(x, y)| x != 0 || y != 0)
+    }
+
+    /// Whether the start and end of this region differ from those which
+    /// would be inferred from the peak alone, so that they must be stored.
+    ///
+    /// Axes with a zero peak do not contribute to the region, so their start
+    /// and end are not considered.
+    fn has_intermediate_region(&self) -> bool {
+        let (default_start, default_end) = default_region(&self.peak);
+        self.peak.iter().enumerate().any(|(ix, &peak)| {
+            peak != 0.0
+                && (f32_to_f2dot14(self.start[ix]) != f32_to_f2dot14(default_start[ix])
+                    || f32_to_f2dot14(self.end[ix]) != f32_to_f2dot14(default_end[ix]))
+        })
+    }
+
     pub(crate) fn combine(&self, other: &Self) -> Self {
         let mut new = self.clone();
         if new.deltas.len() != other.deltas.len() {
@@ -144,6 +168,28 @@ impl DeltaSet {
     }
 }
 
+/// The start and end of the region implied by a peak tuple when no
+/// intermediate region is stored.
+///
+/// Each axis runs from zero to its peak. Axes with a zero peak are given a
+/// start of -1.0, although they do not contribute to the region.
+fn default_region(peak: &[f32]) -> (Tuple, Tuple) {
+    let start = peak
+        .iter()
+        .map(|&x| {
+            if x > 0.0 {
+                0.0
+            } else if x < 0.0 {
+                x
+            } else {
+                -1.0
+            }
+        })
+        .collect();
+    let end = peak.iter().map(|&x| x.max(0.0)).collect();
+    (start, end)
+}
+
 #[derive(Debug, PartialEq, Clone)]
 /// A description of how an individual glyph's outline varies across the designspace.
 pub struct GlyphVariationData {
@@ -151,6 +197,71 @@ pub struct GlyphVariationData {
     pub deltasets: Vec<DeltaSet>,
 }
 
+/// Computes variation data for a glyph from a default master and a set of
+/// region masters.
+///
+/// Each master is given with its peak location in normalized coordinates,
+/// and produces one [`DeltaSet`] whose deltas are the master's coordinates
+/// minus the default's. The region runs from the default to the peak on
+/// each axis, so the masters should not overlap each other's regions (for
+/// example, one master at the extreme of each axis); use a
+/// `VariationModel` when intermediate or corner masters are involved.
+/// Component offsets are varied along with contour points. The deltas for
+/// the phantom points are zero.
+///
+/// Every master is checked with [`check_interpolatable`] against the default
+/// first, and the first incompatibility found is returned as an error. A
+/// master whose deltas do not fit in 16 bits is reported as
+/// [`IncompatibilityReport::DeltaOverflow`].
+pub fn deltas_from_masters(
+    default: &Glyph,
+    masters: &[(Tuple, Glyph)],
+) -> Result<GlyphVariationData, IncompatibilityReport> {
+    for (_, master) in masters {
+        check_interpolatable(default, master)?;
+    }
+    let deltasets = masters
+        .iter()
+        .map(|(peak, master)| {
+            let (start, end) = default_region(peak);
+            Ok(DeltaSet {
+                peak: peak.clone(),
+                start,
+                end,
+                deltas: master
+                    .coord_deltas(default)
+                    .ok_or(IncompatibilityReport::DeltaOverflow)?,
+            })
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(GlyphVariationData { deltasets })
+}
+
+/// Computes the deltas of a glyph's four phantom points for a change in its
+/// horizontal metrics.
+///
+/// The phantom points follow a glyph's points in each delta set, in the
+/// order left, right, top, bottom. The left phantom point sits at the
+/// glyph's `xMin` minus its left side bearing, and the right one an advance
+/// width further on. Assuming the outline's `xMin` itself is unchanged (any
+/// movement of the outline is carried by the deltas of its own points), the
+/// left point moves opposite to the change in side bearing and the right
+/// point additionally moves by the change in advance. The vertical phantom
+/// points are left where they are.
+///
+/// This is the inverse of [`gvar::advance_delta`].
+pub fn phantom_deltas(
+    default_advance: u16,
+    default_lsb: i16,
+    region_advance: u16,
+    region_lsb: i16,
+) -> [(i16, i16); 4] {
+    let left = default_lsb as i32 - region_lsb as i32;
+    let right = left + region_advance as i32 - default_advance as i32;
+    let clamp = |x: i32| x.clamp(i16::MIN as i32, i16::MAX as i32) as i16;
+    [(clamp(left), 0), (clamp(right), 0), (0, 0), (0, 0)]
+}
+
 #[derive(Debug, PartialEq, Clone)]
 #[allow(non_camel_case_types)]
 /// A Glyph Variations table, describing how glyph outlines vary across the
@@ -167,14 +278,19 @@ pub fn from_bytes(
     s: &[u8],
     coords_and_ends: CoordsAndEndsVec,
 ) -> Result<gvar, DeserializationError> {
-    let mut c = ReaderContext::new(s.to_vec());
+    let mut c = ReaderContext::from_slice(s);
     c.push();
     let core: gvarcore = c.de()?;
-    let offset_count = (core.glyphCount + 1) as usize;
+    // A table for no glyphs has nothing worth reading after the header, and
+    // some writers omit even the single final offset, so stop here
+    if core.glyphCount == 0 {
+        return Ok(gvar { variations: vec![] });
+    }
+    let offset_count = core.glyphCount as usize + 1;
     let data_offsets: Vec<u32> = if core.flags & 0x1 == 0 {
         // u16 offsets, need doubling
         let u16_and_halved: Vec<u16> = c.de_counted(offset_count)?;
-        u16_and_halved.iter().map(|x| (x * 2).into()).collect()
+        u16_and_halved.iter().map(|x| u32::from(*x) * 2).collect()
     } else {
         c.de_counted(offset_count)?
     };
@@ -184,10 +300,10 @@ pub fn from_bytes(
     /* Shared tuples */
     let mut shared_tuples: Vec<Tuple> = Vec::with_capacity(core.sharedTupleCount as usize);
     c.ptr = c.top_of_table() + (core.sharedTuplesOffset as usize);
-    for _ in 0..core.sharedTupleCount + 1 {
+    for _ in 0..core.sharedTupleCount {
         // println!("Trying to deserialize shared tuple array {:?}", bytes);
-        let tuple: Vec<F2DOT14> = c.de_counted(axis_count)?;
-        let tuple_f32: Vec<f32> = tuple.iter().map(|t| (*t).into()).collect();
+        let tuple: Vec<i16> = c.de_counted(axis_count)?;
+        let tuple_f32: Vec<f32> = tuple.iter().map(|t| f2dot14_to_f32(*t)).collect();
         shared_tuples.push(tuple_f32);
     }
 
@@ -202,6 +318,12 @@ pub fn from_bytes(
             + (core.glyphVariationDataArrayOffset))
             .try_into()
             .unwrap();
+        if next_offset < offset {
+            return Err(DeserializationError(format!(
+                "Glyph variation data for glyph {} ends before it starts",
+                i
+            )));
+        }
         let length = next_offset - offset;
         if length == 0 {
             glyph_variations.push(None);
@@ -218,7 +340,7 @@ pub fn from_bytes(
             for tvh in tvs.0 {
                 let deltas = tvh.iup_delta(&coords_and_ends[i].0, &coords_and_ends[i].1);
                 let index = tvh.0.sharedTupleIndex as usize;
-                if index > shared_tuples.len() {
+                if tvh.0.peakTuple.is_none() && index >= shared_tuples.len() {
                     return Err(DeserializationError(format!(
                         "Invalid shared tuple index {:}",
                         index
@@ -228,18 +350,9 @@ pub fn from_bytes(
                     .0
                     .peakTuple
                     .unwrap_or_else(|| shared_tuples[index].clone());
-                let start_tuple = tvh.0.startTuple.unwrap_or_else(|| {
-                    peak_tuple
-                        .iter()
-                        .map(|&x| if x > 0.0 { 0.0 } else { -1.0 })
-                        .collect()
-                });
-                let end_tuple = tvh.0.endTuple.unwrap_or_else(|| {
-                    peak_tuple
-                        .iter()
-                        .map(|&x| if x > 0.0 { 1.0 } else { 0.0 })
-                        .collect()
-                });
+                let (default_start, default_end) = default_region(&peak_tuple);
+                let start_tuple = tvh.0.startTuple.unwrap_or(default_start);
+                let end_tuple = tvh.0.endTuple.unwrap_or(default_end);
                 deltasets.push(DeltaSet {
                     deltas,
                     peak: peak_tuple,
@@ -257,70 +370,382 @@ pub fn from_bytes(
 }
 
 impl gvar {
+    /// Checks that this table's tuples have the number of axes declared in
+    /// the font's `fvar` table.
+    ///
+    /// The `gvar` table stores its own axis count, and every tuple is read
+    /// using it; if it does not match `fvar`, every tuple is misinterpreted.
+    /// An error describing the first mismatched tuple is returned.
+    pub fn validate_against_fvar(&self, axis_count: usize) -> Result<(), DeserializationError> {
+        for (gid, variation) in self.variations.iter().enumerate() {
+            let deltasets = variation.iter().flat_map(|v| v.deltasets.iter());
+            for ds in deltasets {
+                let found = [&ds.peak, &ds.start, &ds.end]
+                    .iter()
+                    .map(|t| t.len())
+                    .find(|&len| len != axis_count);
+                if let Some(found) = found {
+                    return Err(DeserializationError(format!(
+                        "gvar tuple for glyph {} has {} axes, but fvar has {}",
+                        gid, found, axis_count
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Computes the change in a glyph's advance width at a location.
+    ///
+    /// `coords` is a normalized location in the design space, with one value
+    /// per axis. The advance varies through the deltas of the glyph's first
+    /// two phantom points (the last four points of each delta set), so this
+    /// is the difference between the interpolated x-deltas of the right and
+    /// left phantom points. Glyphs without variation data, or whose delta
+    /// sets do not include phantom points, have an adjustment of zero.
+    pub fn advance_delta(&self, gid: usize, coords: &[f32]) -> i16 {
+        let variation = match self.variations.get(gid) {
+            Some(Some(variation)) => variation,
+            _ => return 0,
+        };
+        let location: Location<usize> = coords.iter().copied().enumerate().collect();
+        let mut delta = 0.0;
+        for ds in &variation.deltasets {
+            if ds.deltas.len() < 4 {
+                continue;
+            }
+            let support: Support<usize> = (0..ds.peak.len())
+                .map(|ix| (ix, (ds.start[ix], ds.peak[ix], ds.end[ix])))
+                .collect();
+            let scalar = support_scalar(&location, &support);
+            let phantom = ds.deltas.len() - 4;
+            let (left, right) = (ds.deltas[phantom].0, ds.deltas[phantom + 1].0);
+            delta += (right as f32 - left as f32) * scalar;
+        }
+        ot_round(delta) as i16
+    }
+
+    /// Sums the deltas of a glyph's delta sets, each scaled by its
+    /// contribution at a normalized location. Returns `None` if the glyph
+    /// has no variation data.
+    fn interpolated_deltas(&self, gid: usize, coords: &[f32]) -> Option<Vec<(f32, f32)>> {
+        let variation = self.variations.get(gid)?.as_ref()?;
+        let location: Location<usize> = coords.iter().copied().enumerate().collect();
+        let mut total: Vec<(f32, f32)> = vec![];
+        for ds in &variation.deltasets {
+            let support: Support<usize> = (0..ds.peak.len())
+                .map(|ix| (ix, (ds.start[ix], ds.peak[ix], ds.end[ix])))
+                .collect();
+            let scalar = support_scalar(&location, &support);
+            if total.len() < ds.deltas.len() {
+                total.resize(ds.deltas.len(), (0.0, 0.0));
+            }
+            for (acc, (x, y)) in total.iter_mut().zip(ds.deltas.iter()) {
+                acc.0 += *x as f32 * scalar;
+                acc.1 += *y as f32 * scalar;
+            }
+        }
+        Some(total)
+    }
+
+    /// Applies the glyph's deltas at a normalized location to its default
+    /// outline `base`.
+    ///
+    /// Contour points and component offsets are moved by their interpolated
+    /// deltas and rounded with [`ot_round`]; components positioned by
+    /// matching points are not moved. The stored bounds are left as they
+    /// are. If the glyph has no variation data, or its deltas do not match
+    /// the number of points in `base`, the default outline is returned.
+    pub fn instance_glyph(&self, gid: usize, coords: &[f32], base: &Glyph) -> Glyph {
+        self.instance_glyph_by(gid, coords, base, |v| ot_round(v) as f64)
+    }
+
+    /// Applies the glyph's deltas at a normalized location to its default
+    /// outline `base`, rounding the varied coordinates with the given
+    /// strategy.
+    ///
+    /// This is otherwise the same as [`gvar::instance_glyph`].
+    pub fn instance_glyph_with_rounding(
+        &self,
+        gid: usize,
+        coords: &[f32],
+        base: &Glyph,
+        rounding: RoundMode,
+    ) -> Glyph {
+        self.instance_glyph_by(gid, coords, base, |v| rounding.round(v.into()))
+    }
+
+    fn instance_glyph_by(
+        &self,
+        gid: usize,
+        coords: &[f32],
+        base: &Glyph,
+        round: impl Fn(f32) -> f64,
+    ) -> Glyph {
+        let mut instance = base.clone();
+        let deltas = match self.interpolated_deltas(gid, coords) {
+            Some(deltas) => deltas,
+            None => return instance,
+        };
+        if deltas.len() != base.num_points(true) {
+            log::warn!(
+                "gvar has {} deltas for glyph {}, but it has {} points",
+                deltas.len(),
+                gid,
+                base.num_points(true)
+            );
+            return instance;
+        }
+        let mut deltas = deltas.into_iter();
+        for pt in instance.contours.iter_mut().flatten() {
+            let (x, y) = deltas.next().unwrap();
+            pt.x = round(pt.x as f32 + x) as i16;
+            pt.y = round(pt.y as f32 + y) as i16;
+        }
+        for comp in instance.components.iter_mut() {
+            let (x, y) = deltas.next().unwrap();
+            if comp.match_points.is_none() {
+                let shift = kurbo::Affine::translate((round(x), round(y)));
+                comp.transformation = shift * comp.transformation;
+            }
+        }
+        instance
+    }
+
+    /// Computes the bounds of the glyph with ID `gid` at a normalized
+    /// location, without modifying either table.
+    ///
+    /// The glyph is instanced with [`gvar::instance_glyph`], taking its
+    /// default outline from `glyf`, and its bounds are computed from the
+    /// varied points in the same way as [`glyf::glyph_bounds`]. Composite
+    /// glyphs are handled by instancing each component glyph in turn. Bounds
+    /// of glyphs which do not exist, or whose components are nested too
+    /// deeply, are empty.
+    pub fn instance_bounds(&self, gid: usize, coords: &[f32], glyf: &glyf) -> kurbo::Rect {
+        self._instance_bounds(gid, coords, glyf, 0)
+    }
+
+    fn _instance_bounds(&self, gid: usize, coords: &[f32], glyf: &glyf, depth: u32) -> kurbo::Rect {
+        let base = match glyf.glyphs.get(gid) {
+            Some(base) if depth <= MAX_COMPONENT_DEPTH => base,
+            _ => return kurbo::Rect::ZERO,
+        };
+        let instance = self.instance_glyph(gid, coords, base);
+        if !instance.has_components() {
+            return instance.ink_bounds();
+        }
+        let component_bounds = |comp: &Component| {
+            self._instance_bounds(comp.glyph_index as usize, coords, glyf, depth + 1)
+        };
+        instance
+            .components
+            .iter()
+            .map(|comp| {
+                comp.transformation
+                    .transform_rect_bbox(component_bounds(comp))
+            })
+            .reduce(|a, b| a.union(b))
+            .unwrap_or(kurbo::Rect::ZERO)
+    }
+
+    /// Pins some axes of the design space to fixed locations.
+    ///
+    /// `pinned` maps axis indices to normalized coordinates. Each delta set
+    /// is scaled by its contribution at the pinned location, and the pinned
+    /// axes are removed from its tuples. Delta sets which then apply
+    /// everywhere are folded into a set of default deltas for the glyph,
+    /// which should be added to its outline in the `glyf` table; delta sets
+    /// which end up with the same region are merged, and those which have no
+    /// effect are dropped. The ranges of the remaining axes are unchanged, so
+    /// no renormalization is needed.
+    ///
+    /// Returns the new table, along with the default deltas (including
+    /// phantom points) for each glyph which has any.
+    #[allow(clippy::type_complexity)]
+    pub fn instance_partial(
+        &self,
+        pinned: &HashMap<usize, f32>,
+    ) -> (gvar, Vec<Option<Vec<(i16, i16)>>>) {
+        let location: Location<usize> = pinned.iter().map(|(&k, &v)| (k, v)).collect();
+        let mut default_deltas = vec![];
+        let variations = self
+            .variations
+            .iter()
+            .map(|var| {
+                let var = match var {
+                    Some(var) => var,
+                    None => {
+                        default_deltas.push(None);
+                        return None;
+                    }
+                };
+                let mut defaults: Option<Vec<(f32, f32)>> = None;
+                let mut merged: Vec<(Tuple, Tuple, Tuple, Vec<(f32, f32)>)> = vec![];
+                for ds in &var.deltasets {
+                    let support: Support<usize> = pinned
+                        .keys()
+                        .filter(|&&ix| ix < ds.peak.len())
+                        .map(|&ix| (ix, (ds.start[ix], ds.peak[ix], ds.end[ix])))
+                        .collect();
+                    let scalar = support_scalar(&location, &support);
+                    if scalar == 0.0 {
+                        continue;
+                    }
+                    let keep = |t: &Tuple| -> Tuple {
+                        t.iter()
+                            .enumerate()
+                            .filter(|(ix, _)| !pinned.contains_key(ix))
+                            .map(|(_, &v)| v)
+                            .collect()
+                    };
+                    let (peak, start, end) = (keep(&ds.peak), keep(&ds.start), keep(&ds.end));
+                    let target = if peak.iter().all(|&p| p == 0.0) {
+                        defaults.get_or_insert_with(|| vec![(0.0, 0.0); ds.deltas.len()])
+                    } else {
+                        let position = merged
+                            .iter()
+                            .position(|(p, s, e, _)| p == &peak && s == &start && e == &end);
+                        let ix = match position {
+                            Some(ix) => ix,
+                            None => {
+                                merged.push((peak, start, end, vec![(0.0, 0.0); ds.deltas.len()]));
+                                merged.len() - 1
+                            }
+                        };
+                        &mut merged[ix].3
+                    };
+                    for (acc, (x, y)) in target.iter_mut().zip(ds.deltas.iter()) {
+                        acc.0 += *x as f32 * scalar;
+                        acc.1 += *y as f32 * scalar;
+                    }
+                }
+                let round = |deltas: Vec<(f32, f32)>| -> Vec<(i16, i16)> {
+                    deltas
+                        .iter()
+                        .map(|(x, y)| (ot_round(*x) as i16, ot_round(*y) as i16))
+                        .collect()
+                };
+                default_deltas.push(
+                    defaults
+                        .map(round)
+                        .filter(|d| d.iter().any(|&(x, y)| x != 0 || y != 0)),
+                );
+                let deltasets: Vec<DeltaSet> = merged
+                    .into_iter()
+                    .map(|(peak, start, end, deltas)| DeltaSet {
+                        peak,
+                        start,
+                        end,
+                        deltas: round(deltas),
+                    })
+                    .filter(|ds| ds.deltas.iter().any(|&(x, y)| x != 0 || y != 0))
+                    .collect();
+                if deltasets.is_empty() {
+                    None
+                } else {
+                    Some(GlyphVariationData { deltasets })
+                }
+            })
+            .collect();
+        (gvar { variations }, default_deltas)
+    }
+
     /// Serializes this table to binary, given a reference to the `glyf` table.
     pub fn to_bytes(&self, glyf: Option<&glyf>) -> Vec<u8> {
-        let mut out: Vec<u8> = vec![];
-        // Determine all the shared tuples.
+        let (axis_count, shared_tuples) = self.shared_tuples();
+        let serialized_variations: Vec<Vec<u8>> = (0..self.variations.len())
+            .map(|ix| self.serialize_variation(ix, &shared_tuples, glyf))
+            .collect();
+        let lengths: Vec<usize> = serialized_variations.iter().map(|v| v.len()).collect();
+        let mut out = self.serialize_header(axis_count, &shared_tuples, &lengths);
+        for data in serialized_variations {
+            out.extend(data);
+        }
+        out
+    }
+
+    /// Serializes this table to binary, writing it to `writer` as it goes.
+    ///
+    /// The output is identical to that of [`gvar::to_bytes`], but only the
+    /// variation data of one glyph is held in memory at a time. As the
+    /// offsets to each glyph's data come before any of the data, each glyph's
+    /// variations are serialized twice: once to find their length, and again
+    /// to write them out.
+    pub fn write_to(
+        &self,
+        glyf: Option<&glyf>,
+        mut writer: impl std::io::Write,
+    ) -> std::io::Result<()> {
+        let (axis_count, shared_tuples) = self.shared_tuples();
+        let lengths: Vec<usize> = (0..self.variations.len())
+            .map(|ix| self.serialize_variation(ix, &shared_tuples, glyf).len())
+            .collect();
+        writer.write_all(&self.serialize_header(axis_count, &shared_tuples, &lengths))?;
+        for ix in 0..self.variations.len() {
+            writer.write_all(&self.serialize_variation(ix, &shared_tuples, glyf))?;
+        }
+        Ok(())
+    }
+
+    /// Determines the axis count and the shared tuples: peaks used by more
+    /// than one delta set, most common first, up to the number a tuple index
+    /// can refer to. Each tuple is returned in its binary form.
+    fn shared_tuples(&self) -> (uint16, Vec<Vec<u8>>) {
         let mut shared_tuple_counter: Counter<Vec<u8>> = Counter::new();
         let mut axis_count: uint16 = 0;
         for var in self.variations.iter().flatten() {
             for ds in &var.deltasets {
                 axis_count = ds.peak.len() as uint16;
-                // println!("Peak: {:?}", ds.peak);
+                if !ds.has_effect() {
+                    // This delta set will not be written
+                    continue;
+                }
                 let mut tuple: Vec<u8> = vec![];
                 for t in &ds.peak {
-                    F2DOT14::from(*t).to_bytes(&mut tuple).unwrap();
+                    f32_to_f2dot14(*t).to_bytes(&mut tuple).unwrap();
                 }
                 shared_tuple_counter[&tuple] += 1;
             }
         }
-        // shared_tuple_counter.retain(|_, &mut v| v > 1);
-        let most_common_tuples: Vec<(Vec<u8>, usize)> = shared_tuple_counter.most_common();
-        if most_common_tuples.is_empty() {
-            panic!("Some more sensible error checking here for null case");
-        }
-        let shared_tuple_count = most_common_tuples.len() as u16;
-        let flags = 1; // XXX
-
-        let mut glyph_variation_data_offsets: Vec<u8> = vec![];
+        shared_tuple_counter.retain(|_, &mut v| v > 1);
+        let max_shared_tuples = TupleIndexFlags::TUPLE_INDEX_MASK.bits() as usize + 1;
+        let shared_tuples = shared_tuple_counter
+            .most_common_ordered()
+            .into_iter()
+            .take(max_shared_tuples)
+            .map(|(tuple, _)| tuple)
+            .collect();
+        (axis_count, shared_tuples)
+    }
 
-        // println!("Most common tuples: {:?}", most_common_tuples);
-        let mut shared_tuples = vec![];
-        let mut serialized_tuples = vec![];
+    /// Serializes the variation data of a single glyph, padded to an even
+    /// length. A glyph with no variations left has no data at all.
+    fn serialize_variation(
+        &self,
+        ix: usize,
+        shared_tuples: &[Vec<u8>],
+        glyf: Option<&glyf>,
+    ) -> Vec<u8> {
         let mut serialized_tvs = vec![];
-        for (a, _) in most_common_tuples {
-            serialized_tuples.extend(otspec::ser::to_bytes(&a).unwrap());
-            shared_tuples.push(a);
-        }
-        // Now we need a bunch of TVSes
-        for (ix, var) in self.variations.iter().enumerate() {
-            // Data offset
-            if flags != 0 {
-                glyph_variation_data_offsets
-                    .extend(&otspec::ser::to_bytes(&(serialized_tvs.len() as u32)).unwrap());
-            } else {
-                glyph_variation_data_offsets
-                    .extend(&otspec::ser::to_bytes(&(serialized_tvs.len() as u16 / 2)).unwrap());
-            }
-
-            if let Some(var) = var {
-                let maybe_glyph = glyf.map(|g| &g.glyphs[ix]);
-                #[cfg(feature = "rayon")]
-                let tuple_variations = var
-                    .deltasets
-                    .par_iter()
-                    .map(|ds| ds.to_tuple_variation(&shared_tuples, maybe_glyph))
-                    .filter(|tv| tv.has_effect())
-                    .collect();
+        if let Some(var) = &self.variations[ix] {
+            let maybe_glyph = glyf.map(|g| &g.glyphs[ix]);
+            #[cfg(feature = "rayon")]
+            let tuple_variations: Vec<TupleVariation> = var
+                .deltasets
+                .par_iter()
+                .filter(|ds| ds.has_effect())
+                .map(|ds| ds.to_tuple_variation(shared_tuples, maybe_glyph))
+                .collect();
 
-                #[cfg(not(feature = "rayon"))]
-                let tuple_variations = var
-                    .deltasets
-                    .iter()
-                    .map(|ds| ds.to_tuple_variation(&shared_tuples, maybe_glyph))
-                    .filter(|tv| tv.has_effect())
-                    .collect();
+            #[cfg(not(feature = "rayon"))]
+            let tuple_variations: Vec<TupleVariation> = var
+                .deltasets
+                .iter()
+                .filter(|ds| ds.has_effect())
+                .map(|ds| ds.to_tuple_variation(shared_tuples, maybe_glyph))
+                .collect();
 
+            if !tuple_variations.is_empty() {
                 let tvs = TupleVariationStore(tuple_variations);
                 serialized_tvs.extend(otspec::ser::to_bytes(&tvs).unwrap());
                 // Add a byte of padding
@@ -329,34 +754,52 @@ impl gvar {
                 }
             }
         }
-        // Final data offset
-        if flags != 0 {
-            glyph_variation_data_offsets
-                .extend(&otspec::ser::to_bytes(&(serialized_tvs.len() as u32)).unwrap());
-        } else {
-            glyph_variation_data_offsets
-                .extend(&otspec::ser::to_bytes(&(serialized_tvs.len() as u16 / 2)).unwrap());
+        serialized_tvs
+    }
+
+    /// Serializes everything which comes before the glyph variation data:
+    /// the table header, the data offsets (given the length of each glyph's
+    /// data) and the shared tuples.
+    fn serialize_header(
+        &self,
+        axis_count: uint16,
+        shared_tuples: &[Vec<u8>],
+        lengths: &[usize],
+    ) -> Vec<u8> {
+        let flags = 1; // XXX
+        let mut glyph_variation_data_offsets: Vec<u8> = vec![];
+        let mut offset = 0;
+        // One offset for each glyph, plus a final one for the end of the data
+        for length in lengths.iter().chain(std::iter::once(&0)) {
+            if flags != 0 {
+                glyph_variation_data_offsets
+                    .extend(&otspec::ser::to_bytes(&(offset as u32)).unwrap());
+            } else {
+                glyph_variation_data_offsets
+                    .extend(&otspec::ser::to_bytes(&((offset / 2) as u16)).unwrap());
+            }
+            offset += length;
+        }
+        let mut serialized_tuples = vec![];
+        for tuple in shared_tuples {
+            serialized_tuples.extend(otspec::ser::to_bytes(tuple).unwrap());
         }
-        out.extend(
-            otspec::ser::to_bytes(&gvarcore {
-                majorVersion: 1,
-                minorVersion: 0,
-                axisCount: axis_count,
-                sharedTupleCount: shared_tuple_count,
-                sharedTuplesOffset: 20 + glyph_variation_data_offsets.len() as u32,
-                glyphCount: self.variations.len() as u16,
-                flags,
-                glyphVariationDataArrayOffset: 20
-                    + glyph_variation_data_offsets.len() as u32
-                    + serialized_tuples.len() as u32,
-            })
-            .unwrap(),
-        );
 
+        let mut out = otspec::ser::to_bytes(&gvarcore {
+            majorVersion: 1,
+            minorVersion: 0,
+            axisCount: axis_count,
+            sharedTupleCount: shared_tuples.len() as u16,
+            sharedTuplesOffset: 20 + glyph_variation_data_offsets.len() as u32,
+            glyphCount: self.variations.len() as u16,
+            flags,
+            glyphVariationDataArrayOffset: 20
+                + glyph_variation_data_offsets.len() as u32
+                + serialized_tuples.len() as u32,
+        })
+        .unwrap();
         out.extend(glyph_variation_data_offsets);
         out.extend(serialized_tuples);
-        out.extend(serialized_tvs);
-
         out
     }
 }
@@ -374,7 +817,11 @@ impl Serialize for gvar {
 
 #[cfg(test)]
 mod tests {
-    use super::GlyphVariationData;
+    use super::{DeltaSet, GlyphVariationData};
+    use crate::otvar::TupleIndexFlags;
+    use crate::tables::glyf::tests::{sample_glyf, square_glyph};
+    use crate::tables::glyf::ComponentFlags;
+    use std::collections::HashMap;
 
     #[test]
     fn gvar_de() {
@@ -573,4 +1020,493 @@ mod tests {
 
         // assert_eq!(serialized, binary_gvar); // Are they the same binary?
     }
+
+    #[test]
+    fn gvar_de_large_short_offsets() {
+        // Short offsets are stored halved; 0x8001 * 2 does not fit in a u16
+        let mut binary_gvar = vec![
+            0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1a, 0x00, 0x02,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x1a, 0x00, 0x00, 0x80, 0x01, 0x80, 0x01,
+        ];
+        binary_gvar.extend(vec![0; 0x10002]);
+        let points = vec![
+            (vec![(0, 0), (0, 0), (0, 0), (0, 0)], vec![0, 1, 2, 3]),
+            (vec![(0, 0), (0, 0), (0, 0), (0, 0)], vec![0, 1, 2, 3]),
+        ];
+        let deserialized = super::from_bytes(&binary_gvar, points).unwrap();
+        assert_eq!(
+            deserialized.variations,
+            vec![Some(GlyphVariationData { deltasets: vec![] }), None]
+        );
+    }
+
+    #[test]
+    fn gvar_instance_partial() {
+        let deltaset = |peak: Vec<f32>, deltas: Vec<(i16, i16)>| {
+            let (start, end) = super::default_region(&peak);
+            DeltaSet {
+                peak,
+                start,
+                end,
+                deltas,
+            }
+        };
+        let table = super::gvar {
+            variations: vec![
+                None,
+                Some(GlyphVariationData {
+                    deltasets: vec![
+                        deltaset(vec![1.0, 0.0], vec![(10, 0), (0, 0)]),
+                        deltaset(vec![0.0, 1.0], vec![(0, 20), (0, 0)]),
+                        deltaset(vec![1.0, 1.0], vec![(4, 4), (0, 0)]),
+                    ],
+                }),
+                Some(GlyphVariationData {
+                    deltasets: vec![deltaset(vec![-1.0, 0.0], vec![(7, 7), (0, 0)])],
+                }),
+            ],
+        };
+        let pinned: HashMap<usize, f32> = [(0, 0.5)].into_iter().collect();
+        let (instanced, defaults) = table.instance_partial(&pinned);
+        assert_eq!(defaults, vec![None, Some(vec![(5, 0), (0, 0)]), None]);
+        assert_eq!(
+            instanced.variations,
+            vec![
+                None,
+                Some(GlyphVariationData {
+                    deltasets: vec![DeltaSet {
+                        peak: vec![1.0],
+                        start: vec![0.0],
+                        end: vec![1.0],
+                        deltas: vec![(2, 22), (0, 0)],
+                    }]
+                }),
+                // Inactive at the pinned location
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn gvar_instance_glyph_rounding() {
+        use crate::tables::glyf::{Glyph, GlyphBuilder, RoundMode};
+        let square = GlyphBuilder::new()
+            .move_to(0, 0)
+            .line_to(0, 100)
+            .line_to(100, 100)
+            .line_to(100, 0)
+            .build();
+        let mut deltas = vec![(0, 0); square.num_points(true)];
+        deltas[0] = (1, -1);
+        let table = super::gvar {
+            variations: vec![Some(GlyphVariationData {
+                deltasets: vec![DeltaSet {
+                    peak: vec![1.0],
+                    start: vec![0.0],
+                    end: vec![1.0],
+                    deltas,
+                }],
+            })],
+        };
+        // Half way along the axis, the first point moves by (0.5, -0.5)
+        let first_point = |glyph: Glyph| (glyph.contours[0][0].x, glyph.contours[0][0].y);
+        assert_eq!(
+            first_point(table.instance_glyph(0, &[0.5], &square)),
+            (1, 0)
+        );
+        let rounded =
+            |mode| first_point(table.instance_glyph_with_rounding(0, &[0.5], &square, mode));
+        assert_eq!(rounded(RoundMode::Round), (1, -1));
+        assert_eq!(rounded(RoundMode::Floor), (0, -1));
+        assert_eq!(rounded(RoundMode::Ceil), (1, 0));
+        assert_eq!(rounded(RoundMode::RoundHalfToEven), (0, 0));
+    }
+
+    #[test]
+    fn gvar_advance_delta() {
+        // A two-point glyph whose advance grows by 100 units along wght,
+        // with the left side bearing moving by 10.
+        let table = super::gvar {
+            variations: vec![
+                Some(GlyphVariationData {
+                    deltasets: vec![DeltaSet {
+                        peak: vec![1.0, 0.0],
+                        start: vec![0.0, 0.0],
+                        end: vec![1.0, 0.0],
+                        deltas: vec![(10, 0), (20, 0), (10, 0), (110, 0), (0, 0), (0, 0)],
+                    }],
+                }),
+                None,
+            ],
+        };
+        assert_eq!(table.advance_delta(0, &[0.0, 0.0]), 0);
+        assert_eq!(table.advance_delta(0, &[0.5, 0.0]), 50);
+        assert_eq!(table.advance_delta(0, &[1.0, 1.0]), 100);
+        // Outside the region
+        assert_eq!(table.advance_delta(0, &[-1.0, 0.0]), 0);
+        // No variation data
+        assert_eq!(table.advance_delta(1, &[1.0, 0.0]), 0);
+        assert_eq!(table.advance_delta(2, &[1.0, 0.0]), 0);
+    }
+
+    #[test]
+    fn gvar_phantom_deltas() {
+        // Advance grows from 500 to 600, left side bearing from 20 to 50
+        let phantom = super::phantom_deltas(500, 20, 600, 50);
+        assert_eq!(phantom, [(-30, 0), (70, 0), (0, 0), (0, 0)]);
+        let mut deltas = vec![(0, 0), (0, 0)];
+        deltas.extend(phantom);
+        let table = super::gvar {
+            variations: vec![Some(GlyphVariationData {
+                deltasets: vec![DeltaSet {
+                    peak: vec![1.0],
+                    start: vec![0.0],
+                    end: vec![1.0],
+                    deltas,
+                }],
+            })],
+        };
+        assert_eq!(table.advance_delta(0, &[1.0]), 100);
+        assert_eq!(table.advance_delta(0, &[0.5]), 50);
+
+        assert_eq!(super::phantom_deltas(500, 20, 500, 20), [(0, 0); 4]);
+        assert_eq!(
+            super::phantom_deltas(600, 0, 450, 0),
+            [(0, 0), (-150, 0), (0, 0), (0, 0)]
+        );
+    }
+
+    #[test]
+    fn gvar_instance_bounds() {
+        let glyf = sample_glyf();
+        let cap_a = &glyf.glyphs[0];
+        // Along the first axis, the right-hand points of the A move out by
+        // 100 units and everything rises by 10.
+        let deltas: Vec<(i16, i16)> = cap_a
+            .gvar_coords_and_ends()
+            .0
+            .iter()
+            .map(|&(x, _)| (if x > 600 { 100 } else { 0 }, 10))
+            .collect();
+        let mut variations = vec![None; glyf.glyphs.len()];
+        variations[0] = Some(GlyphVariationData {
+            deltasets: vec![DeltaSet {
+                peak: vec![1.0],
+                start: vec![0.0],
+                end: vec![1.0],
+                deltas,
+            }],
+        });
+        let table = super::gvar { variations };
+
+        let default = table.instance_bounds(0, &[0.0], &glyf);
+        assert_eq!(default, kurbo::Rect::new(5.0, 0.0, 751.0, 700.0));
+        assert_eq!(table.instance_bounds(0, &[-1.0], &glyf), default);
+        let bold = table.instance_bounds(0, &[1.0], &glyf);
+        assert_ne!(bold, default);
+        assert_eq!(bold, kurbo::Rect::new(5.0, 10.0, 851.0, 710.0));
+        assert_eq!(
+            table.instance_bounds(0, &[0.5], &glyf),
+            kurbo::Rect::new(5.0, 5.0, 801.0, 705.0)
+        );
+
+        // The composite Aacute uses the A, so its bounds vary too
+        let aacute_default = table.instance_bounds(1, &[0.0], &glyf);
+        assert_eq!(aacute_default, glyf.glyph_bounds(1));
+        let aacute_bold = table.instance_bounds(1, &[1.0], &glyf);
+        assert_eq!(aacute_bold.max_x(), 851.0);
+        assert_eq!(aacute_bold.min_y(), 10.0);
+        assert_eq!(aacute_bold.max_y(), aacute_default.max_y());
+
+        // Taking metrics from the A does not leave the acute out of the bounds
+        let mut metrics_from_a = glyf.clone();
+        metrics_from_a.glyphs[1].components[0].flags |= ComponentFlags::USE_MY_METRICS;
+        assert_eq!(
+            table.instance_bounds(1, &[1.0], &metrics_from_a),
+            aacute_bold
+        );
+    }
+
+    #[test]
+    fn gvar_deltas_from_masters() {
+        use crate::tables::glyf::{Glyph, GlyphBuilder, IncompatibilityReport};
+        use otmath::{support_scalar, Location, Support};
+
+        let triangle = |x, y| {
+            GlyphBuilder::new()
+                .move_to(0, 0)
+                .line_to(x, 0)
+                .quad_to(x, y, 0, y)
+                .build()
+        };
+        let default = triangle(100, 100);
+        let masters = vec![
+            (vec![1.0, 0.0], triangle(180, 100)),
+            (vec![0.0, -1.0], triangle(100, 60)),
+        ];
+        let variations = super::deltas_from_masters(&default, &masters).unwrap();
+        assert_eq!(variations.deltasets.len(), 2);
+        assert_eq!(
+            variations.deltasets[0].deltas[..4],
+            [(0, 0), (80, 0), (80, 0), (0, 0)]
+        );
+
+        let apply = |peak: &Vec<f32>| -> Vec<(i16, i16)> {
+            let location: Location<usize> = peak.iter().copied().enumerate().collect();
+            let (mut coords, _) = default.gvar_coords_and_ends();
+            for ds in &variations.deltasets {
+                let support: Support<usize> = (0..ds.peak.len())
+                    .map(|ix| (ix, (ds.start[ix], ds.peak[ix], ds.end[ix])))
+                    .collect();
+                let scalar = support_scalar(&location, &support);
+                for (coord, delta) in coords.iter_mut().zip(ds.deltas.iter()) {
+                    coord.0 += (delta.0 as f32 * scalar) as i16;
+                    coord.1 += (delta.1 as f32 * scalar) as i16;
+                }
+            }
+            coords
+        };
+        for (peak, master) in &masters {
+            assert_eq!(apply(peak), master.gvar_coords_and_ends().0);
+        }
+
+        let incompatible = GlyphBuilder::new().move_to(0, 0).line_to(1, 1).build();
+        assert_eq!(
+            super::deltas_from_masters(&default, &[(vec![1.0, 0.0], incompatible)]),
+            Err(IncompatibilityReport::PointCount {
+                contour: 0,
+                a: 4,
+                b: 2
+            })
+        );
+        assert_eq!(
+            super::deltas_from_masters(&Glyph::empty(), &[]),
+            Ok(GlyphVariationData { deltasets: vec![] })
+        );
+    }
+
+    #[test]
+    fn gvar_intermediate_region_ser() {
+        let points = vec![(
+            vec![(0, 0), (100, 0), (100, 100), (0, 0), (0, 0), (0, 0), (0, 0)],
+            vec![2, 3, 4, 5, 6],
+        )];
+        let deltas = vec![(10, 0), (20, 0), (30, 0), (0, 0), (0, 0), (0, 0), (0, 0)];
+        let simple = DeltaSet {
+            peak: vec![0.5, 0.0],
+            start: vec![0.0, -1.0],
+            end: vec![0.5, 0.0],
+            deltas: deltas.clone(),
+        };
+        let intermediate = DeltaSet {
+            peak: vec![0.5, 0.0],
+            start: vec![0.25, -1.0],
+            end: vec![1.0, 0.0],
+            deltas: vec![(5, 0), (0, 5), (5, 5), (0, 0), (0, 0), (0, 0), (0, 0)],
+        };
+        let shared_tuples = vec![otspec::ser::to_bytes(&vec![0x2000_i16, 0]).unwrap()];
+        let tv = simple.to_tuple_variation(&shared_tuples, None);
+        assert_eq!(tv.0.flags, TupleIndexFlags::empty());
+        assert_eq!(tv.0.startTuple, None);
+        let tv = intermediate.to_tuple_variation(&shared_tuples, None);
+        assert_eq!(tv.0.flags, TupleIndexFlags::INTERMEDIATE_REGION);
+        assert_eq!(tv.0.startTuple, Some(vec![0.25, -1.0]));
+        assert_eq!(tv.0.endTuple, Some(vec![1.0, 0.0]));
+
+        let gvar = super::gvar {
+            variations: vec![Some(GlyphVariationData {
+                deltasets: vec![simple, intermediate],
+            })],
+        };
+        let serialized = gvar.to_bytes(None);
+        let re_de = super::from_bytes(&serialized, points).unwrap();
+        assert_eq!(re_de, gvar);
+    }
+
+    #[test]
+    fn gvar_shared_tuples_ser() {
+        let points: Vec<_> = (0..3)
+            .map(|_| {
+                (
+                    vec![(0, 0), (100, 0), (100, 100), (0, 0), (0, 0), (0, 0), (0, 0)],
+                    vec![2, 3, 4, 5, 6],
+                )
+            })
+            .collect();
+        let deltaset = |peak: Vec<f32>| {
+            let (start, end) = super::default_region(&peak);
+            DeltaSet {
+                peak,
+                start,
+                end,
+                deltas: vec![(10, 0), (20, 0), (30, 0), (0, 0), (0, 0), (0, 0), (0, 0)],
+            }
+        };
+        let gvar = super::gvar {
+            variations: vec![
+                Some(GlyphVariationData {
+                    deltasets: vec![deltaset(vec![1.0, 0.0]), deltaset(vec![0.0, 1.0])],
+                }),
+                Some(GlyphVariationData {
+                    deltasets: vec![deltaset(vec![1.0, 0.0])],
+                }),
+                None,
+            ],
+        };
+        let serialized = gvar.to_bytes(None);
+        // Only the peak used by both glyphs is shared
+        let shared_tuple_count = u16::from_be_bytes([serialized[6], serialized[7]]);
+        assert_eq!(shared_tuple_count, 1);
+        let shared_tuples_offset = u32::from_be_bytes(serialized[8..12].try_into().unwrap());
+        let shared = &serialized[shared_tuples_offset as usize..][..4];
+        assert_eq!(shared, &[0x40, 0x00, 0x00, 0x00]);
+        let mut streamed = vec![];
+        gvar.write_to(None, &mut streamed).unwrap();
+        assert_eq!(streamed, serialized);
+        let re_de = super::from_bytes(&serialized, points).unwrap();
+        assert_eq!(re_de, gvar);
+    }
+
+    #[test]
+    fn gvar_empty() {
+        let header = vec![
+            0x00, 0x01, 0x00, 0x00, // version 1.0
+            0x00, 0x02, // axisCount
+            0x00, 0x00, // sharedTupleCount
+            0x00, 0x00, 0x00, 0x14, // sharedTuplesOffset
+            0x00, 0x00, // glyphCount
+            0x00, 0x00, // flags: short offsets
+            0x00, 0x00, 0x00, 0x16, // glyphVariationDataArrayOffset
+        ];
+        let empty = super::gvar { variations: vec![] };
+        // With the final offset...
+        let mut binary = header.clone();
+        binary.extend([0x00, 0x00]);
+        assert_eq!(super::from_bytes(&binary, vec![]).unwrap(), empty);
+        // ...and without it
+        assert_eq!(super::from_bytes(&header, vec![]).unwrap(), empty);
+
+        let serialized = empty.to_bytes(None);
+        assert_eq!(super::from_bytes(&serialized, vec![]).unwrap(), empty);
+    }
+
+    #[test]
+    fn gvar_advance_only_variations() {
+        use crate::otvar::{Delta, TupleVariation, TupleVariationHeader, TupleVariationStore};
+        use crate::tables::glyf::{Glyph, GlyphBuilder};
+        let square = GlyphBuilder::new()
+            .move_to(0, 0)
+            .line_to(0, 100)
+            .line_to(100, 100)
+            .line_to(100, 0)
+            .build();
+        let space = Glyph::empty();
+        let phantom = super::phantom_deltas(500, 0, 600, 0);
+
+        // Variation data whose private point numbers select only the
+        // phantom points, as written by tools which vary only advances
+        let header = TupleVariationHeader {
+            size: 0,
+            flags: TupleIndexFlags::EMBEDDED_PEAK_TUPLE,
+            sharedTupleIndex: 0,
+            peakTuple: Some(vec![1.0]),
+            startTuple: None,
+            endTuple: None,
+        };
+        let glyphs = [&square, &space];
+        let serialized_variations: Vec<Vec<u8>> = glyphs
+            .iter()
+            .map(|glyph| {
+                let mut deltas = vec![None; glyph.num_points(false)];
+                deltas.extend(phantom.iter().map(|&d| Some(Delta::Delta2D(d))));
+                let tvs = TupleVariationStore(vec![TupleVariation(header.clone(), deltas)]);
+                let mut data = otspec::ser::to_bytes(&tvs).unwrap();
+                data.resize((data.len() + 1) & !1, 0);
+                data
+            })
+            .collect();
+        let lengths: Vec<usize> = serialized_variations.iter().map(|v| v.len()).collect();
+        let mut binary = super::gvar {
+            variations: vec![None, None],
+        }
+        .serialize_header(1, &[], &lengths);
+        binary.extend(serialized_variations.concat());
+
+        let coords = glyphs.iter().map(|g| g.gvar_coords_and_ends()).collect();
+        let table = super::from_bytes(&binary, coords).unwrap();
+        let expected = |glyph: &Glyph| {
+            let mut deltas = vec![(0, 0); glyph.num_points(false)];
+            deltas.extend(phantom);
+            Some(GlyphVariationData {
+                deltasets: vec![DeltaSet {
+                    peak: vec![1.0],
+                    start: vec![0.0],
+                    end: vec![1.0],
+                    deltas,
+                }],
+            })
+        };
+        assert_eq!(table.variations, vec![expected(&square), expected(&space)]);
+
+        for (gid, glyph) in glyphs.iter().enumerate() {
+            assert_eq!(table.advance_delta(gid, &[1.0]), 100);
+            assert_eq!(table.advance_delta(gid, &[0.5]), 50);
+            // The outline is left untouched
+            assert_eq!(&table.instance_glyph(gid, &[1.0], glyph), *glyph);
+        }
+
+        // Such data survives being written out again
+        let glyf = crate::tables::glyf::glyf {
+            glyphs: vec![square.clone(), space.clone()],
+        };
+        let coords = glyphs.iter().map(|g| g.gvar_coords_and_ends()).collect();
+        let reserialized = table.to_bytes(Some(&glyf));
+        assert_eq!(super::from_bytes(&reserialized, coords).unwrap(), table);
+    }
+
+    #[test]
+    fn gvar_drops_empty_variations() {
+        let square = square_glyph();
+        let mut wide = square.clone();
+        wide.contours[0][1].x = 200;
+        wide.contours[0][2].x = 200;
+        let unchanged = super::deltas_from_masters(&square, &[(vec![1.0], square.clone())]);
+        let changed = super::deltas_from_masters(&square, &[(vec![1.0], wide)]);
+        let gvar = super::gvar {
+            variations: vec![Some(unchanged.unwrap()), Some(changed.unwrap())],
+        };
+        let serialized = gvar.to_bytes(None);
+        // The unused peak is not shared
+        assert_eq!(u16::from_be_bytes([serialized[6], serialized[7]]), 0);
+        // The first glyph has no data, so its offsets are equal
+        let offset =
+            |ix: usize| u32::from_be_bytes(serialized[20 + ix * 4..][..4].try_into().unwrap());
+        assert_eq!(offset(0), offset(1));
+        assert!(offset(2) > offset(1));
+
+        let coords = vec![square.gvar_coords_and_ends(), square.gvar_coords_and_ends()];
+        let re_de = super::from_bytes(&serialized, coords).unwrap();
+        assert_eq!(re_de.variations[0], None);
+        assert_eq!(re_de.variations[1], gvar.variations[1]);
+    }
+
+    #[test]
+    fn gvar_validate_against_fvar() {
+        let gvar = super::gvar {
+            variations: vec![
+                None,
+                Some(GlyphVariationData {
+                    deltasets: vec![DeltaSet {
+                        peak: vec![1.0, 0.0],
+                        start: vec![0.0, 0.0],
+                        end: vec![1.0, 0.0],
+                        deltas: vec![(10, 0), (0, 0), (0, 0), (0, 0), (0, 0)],
+                    }],
+                }),
+            ],
+        };
+        assert!(gvar.validate_against_fvar(2).is_ok());
+        let err = gvar.validate_against_fvar(1).unwrap_err();
+        assert_eq!(err.0, "gvar tuple for glyph 1 has 2 axes, but fvar has 1");
+    }
 }
@@ -1,9 +1,32 @@
+use super::glyf::glyf;
+use otmath::ot_round;
 use otspec::types::*;
+use otspec::Deserializer;
+use otspec_macros::tables;
 
 /// The 'head' OpenType tag.
 pub const TAG: Tag = crate::tag!("head");
 
-pub use otspec::tables::head::head;
+tables!(head {
+    uint16 majorVersion
+    uint16 minorVersion
+    Fixed fontRevision
+    uint32 checksumAdjustment
+    uint32 magicNumber
+    uint16 flags
+    uint16 unitsPerEm
+    LONGDATETIME created
+    LONGDATETIME modified
+    int16 xMin
+    int16 yMin
+    int16 xMax
+    int16 yMax
+    uint16 macStyle
+    uint16 lowestRecPPEM
+    int16 fontDirectionHint
+    int16 indexToLocFormat
+    int16 glyphDataFormat
+});
 
 /// Create a new `head` table, given a float font revision, units-per-em
 /// value and the global glyph coordinate maxima/minima.
@@ -37,3 +60,56 @@ pub fn new(
         glyphDataFormat: 0,
     }
 }
+
+impl head {
+    /// Sets the font-wide bounding box to the union of the bounds of all
+    /// non-empty glyphs in the `glyf` table.
+    ///
+    /// The stored bounds of each glyph are used, so if composite glyphs have
+    /// been edited you should call [`glyf::recalc_bounds`] first. Edges are
+    /// rounded with [`ot_round`] and clamped to the range of an `i16`.
+    pub fn recalc_bbox_from_glyf(&mut self, glyf: &glyf) {
+        let bbox = glyf
+            .glyphs
+            .iter()
+            .filter(|g| !g.is_empty())
+            .map(|g| g.bounds_rect())
+            .reduce(|a, b| a.union(b))
+            .unwrap_or(kurbo::Rect::ZERO);
+        let clamp = |v: f64| ot_round(v).clamp(i16::MIN.into(), i16::MAX.into()) as i16;
+        self.xMin = clamp(bbox.min_x());
+        self.yMin = clamp(bbox.min_y());
+        self.xMax = clamp(bbox.max_x());
+        self.yMax = clamp(bbox.max_y());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tables::glyf::tests::sample_font;
+
+    #[test]
+    fn head_recalc_bbox() {
+        let font = sample_font();
+        let glyf = font.tables.glyf().unwrap().unwrap();
+        let head = font.tables.head().unwrap().unwrap();
+        assert_eq!(
+            (head.xMin, head.yMin, head.xMax, head.yMax),
+            (-141, -76, 751, 915)
+        );
+        let mut recalculated = (*head).clone();
+        recalculated.xMin = 0;
+        recalculated.yMin = 0;
+        recalculated.xMax = 0;
+        recalculated.yMax = 0;
+        recalculated.recalc_bbox_from_glyf(&glyf);
+        assert_eq!(recalculated, *head);
+
+        // Moving a glyph grows the bounding box
+        let mut glyf = (*glyf).clone();
+        glyf.glyphs[0].xMax = 900;
+        let mut head = head.into_owned();
+        head.recalc_bbox_from_glyf(&glyf);
+        assert_eq!(head.xMax, 900);
+    }
+}
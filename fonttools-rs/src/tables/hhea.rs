@@ -1,3 +1,5 @@
+use super::glyf::glyf;
+use super::hmtx::hmtx;
 use otspec::types::*;
 use otspec::Deserializer;
 use otspec_macros::tables;
@@ -26,10 +28,84 @@ tables!(hhea {
     uint16  numberOfHMetrics
 });
 
+impl hhea {
+    /// Updates the horizontal metric summaries of this table from the `glyf`
+    /// and `hmtx` tables.
+    ///
+    /// `advanceWidthMax` and `numberOfHMetrics` are taken from the metrics of
+    /// all glyphs. `minLeftSideBearing`, `minRightSideBearing` and
+    /// `xMaxExtent` are computed from the stored bounds of non-empty glyphs
+    /// only, so if composite glyphs have been edited you should call
+    /// [`glyf::recalc_bounds`] first. If there are no non-empty glyphs these
+    /// are set to zero. Right side bearings beyond the range of an `i16` are
+    /// clamped to it.
+    pub fn recalc_from_glyf_and_hmtx(&mut self, glyf: &glyf, hmtx: &hmtx) {
+        self.advanceWidthMax = hmtx
+            .metrics
+            .iter()
+            .map(|m| m.advanceWidth)
+            .max()
+            .unwrap_or(0);
+        self.numberOfHMetrics = hmtx.number_of_hmetrics();
+        let mut min_lsb: Option<i16> = None;
+        let mut min_rsb: Option<i16> = None;
+        let mut max_extent: Option<i16> = None;
+        for (g, metric) in glyf.glyphs.iter().zip(hmtx.metrics.iter()) {
+            if g.is_empty() {
+                continue;
+            }
+            let bounds = g.bounds_rect();
+            let (x_min, x_max) = (bounds.min_x() as i16, bounds.max_x() as i16);
+            let rsb = (i32::from(metric.advanceWidth) - i32::from(x_max))
+                .clamp(i16::MIN.into(), i16::MAX.into()) as i16;
+            min_lsb = Some(min_lsb.map_or(x_min, |v| v.min(x_min)));
+            min_rsb = Some(min_rsb.map_or(rsb, |v| v.min(rsb)));
+            max_extent = Some(max_extent.map_or(x_max, |v| v.max(x_max)));
+        }
+        self.minLeftSideBearing = min_lsb.unwrap_or(0);
+        self.minRightSideBearing = min_rsb.unwrap_or(0);
+        self.xMaxExtent = max_extent.unwrap_or(0);
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::tables::glyf::tests::sample_font;
     use otspec::ser;
 
+    #[test]
+    fn hhea_recalc() {
+        let font = sample_font();
+        let glyf = font.tables.glyf().unwrap().unwrap();
+        let hmtx = font.tables.hmtx().unwrap().unwrap();
+        let original = font.tables.hhea().unwrap().unwrap().into_owned();
+        let mut hhea = original.clone();
+        hhea.advanceWidthMax = 0;
+        hhea.minLeftSideBearing = 0;
+        hhea.minRightSideBearing = 0;
+        hhea.xMaxExtent = 0;
+        hhea.recalc_from_glyf_and_hmtx(&glyf, &hmtx);
+        assert_eq!(hhea, original);
+
+        // Widen a glyph so it sticks out past its advance
+        let mut glyf = glyf.into_owned();
+        glyf.glyphs[2].xMax = hmtx.metrics[2].advanceWidth as i16 + 400;
+        hhea.recalc_from_glyf_and_hmtx(&glyf, &hmtx);
+        assert_eq!(hhea.minRightSideBearing, -400);
+        assert_eq!(
+            hhea.xMaxExtent,
+            original.xMaxExtent.max(glyf.glyphs[2].xMax)
+        );
+
+        // Side bearings too large for an i16 are clamped rather than wrapping
+        let mut hmtx = hmtx.into_owned();
+        for metric in hmtx.metrics.iter_mut() {
+            metric.advanceWidth = u16::MAX;
+        }
+        hhea.recalc_from_glyf_and_hmtx(&glyf, &hmtx);
+        assert_eq!(hhea.minRightSideBearing, i16::MAX);
+    }
+
     #[test]
     fn hhea_ser() {
         let fhhea = super::hhea {
@@ -36,6 +36,14 @@ pub(crate) fn from_bytes(
     }
     for ab in raw_indices.windows(2) {
         if let [a, b] = ab {
+            if *b < *a {
+                return Err(DeserializationError(format!(
+                    "loca offsets decrease from {} to {} at glyph {}",
+                    a,
+                    b,
+                    res.indices.len() + 1
+                )));
+            }
             if *a == *b {
                 res.indices.push(None);
             } else {
@@ -70,4 +78,12 @@ mod tests {
         // println!("{:?}", floca);
         assert_eq!(floca.indices, locations);
     }
+
+    #[test]
+    fn loca_de_decreasing() {
+        let binary_loca = vec![0x00, 0x00, 0x01, 0x30, 0x00, 0x10, 0x01, 0x4c];
+        let mut reader = ReaderContext::new(binary_loca);
+        let err = super::from_bytes(&mut reader, false).unwrap_err();
+        assert_eq!(err.0, "loca offsets decrease from 608 to 32 at glyph 2");
+    }
 }
@@ -1,3 +1,4 @@
+use super::glyf::glyf;
 use otspec::types::*;
 use otspec::{DeserializationError, Deserialize, Deserializer, ReaderContext, Serialize};
 use otspec_macros::{tables, Serialize};
@@ -117,6 +118,27 @@ impl maxp {
             MaxpVariant::Maxp10(s) => s.numGlyphs = num,
         }
     }
+
+    /// Updates the glyph count and the outline statistics from the `glyf`
+    /// table.
+    ///
+    /// For a version 1.0 table, the point, contour, component and instruction
+    /// size maxima are recalculated; the other fields relating to hinting are
+    /// left unchanged. For a version 0.5 table, only the number of glyphs is
+    /// set.
+    pub fn recalc_from_glyf(&mut self, glyf: &glyf) {
+        self.set_num_glyphs(glyf.glyphs.len() as u16);
+        if let MaxpVariant::Maxp10(table) = &mut self.table {
+            let stats = glyf.build_maxp();
+            table.maxPoints = stats.max_points;
+            table.maxContours = stats.max_contours;
+            table.maxCompositePoints = stats.max_composite_points;
+            table.maxCompositeContours = stats.max_composite_contours;
+            table.maxComponentElements = stats.max_component_elements;
+            table.maxComponentDepth = stats.max_component_depth;
+            table.maxSizeOfInstructions = stats.max_size_of_instructions;
+        }
+    }
 }
 
 impl Deserialize for maxp {
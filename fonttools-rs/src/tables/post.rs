@@ -342,6 +342,15 @@ impl post {
     pub fn set_version(&mut self, version: f32) {
         self.version = U16F16::from_num(version);
     }
+
+    /// Returns the name of the glyph with the given ID, if this table
+    /// contains glyph names (i.e. is version 2.0) and the ID is in range.
+    pub fn glyph_name(&self, gid: u16) -> Option<&str> {
+        self.glyphnames
+            .as_ref()
+            .and_then(|names| names.get(gid as usize))
+            .map(|name| name.as_str())
+    }
 }
 impl Serialize for post {
     fn to_bytes(&self, data: &mut Vec<u8>) -> Result<(), SerializationError> {
@@ -358,21 +367,29 @@ impl Serialize for post {
         };
         core.to_bytes(data)?;
         let mut glyph_name_table: Vec<u8> = Vec::new();
-        let mut glyph_name_table_items = 0;
+        let mut glyph_name_table_items: Vec<&str> = Vec::new();
         if core.version == U16F16::from_num(2.0) {
             if let Some(v) = &self.glyphnames {
                 (v.len() as u16).to_bytes(data)?;
                 for name in v {
-                    match APPLE_NAMES.iter().position(|&r| r == name) {
-                        Some(index) => {
-                            (index as u16).to_bytes(data)?;
-                        }
-                        None => {
-                            ((258 + glyph_name_table_items) as u16).to_bytes(data)?;
-                            glyph_name_table.push(name.len() as u8);
-                            glyph_name_table.extend(name.as_bytes());
-                            glyph_name_table_items += 1;
+                    if let Some(index) = APPLE_NAMES.iter().position(|&r| r == name) {
+                        (index as u16).to_bytes(data)?;
+                    } else if let Some(index) =
+                        glyph_name_table_items.iter().position(|&r| r == name)
+                    {
+                        // Names which appear more than once share a string
+                        ((258 + index) as u16).to_bytes(data)?;
+                    } else {
+                        if name.len() > 255 {
+                            return Err(SerializationError(format!(
+                                "Glyph name {} is too long",
+                                name
+                            )));
                         }
+                        ((258 + glyph_name_table_items.len()) as u16).to_bytes(data)?;
+                        glyph_name_table.push(name.len() as u8);
+                        glyph_name_table.extend(name.as_bytes());
+                        glyph_name_table_items.push(name);
                     }
                 }
             }
@@ -398,14 +415,27 @@ impl Deserialize for post {
                 }
                 let byte_count = byte_count.unwrap() as usize;
                 let name: Vec<u8> = c.de_counted(byte_count)?;
-                glyph_name_table.push(String::from_utf8(name).unwrap());
+                glyph_name_table.push(
+                    String::from_utf8(name)
+                        .map_err(|_| DeserializationError("Invalid glyph name".to_string()))?,
+                );
             }
-            for i in 0..num_glyphs {
-                let offset = glyph_offsets[i as usize] as usize;
+            for offset in glyph_offsets {
+                let offset = offset as usize;
                 if offset < 258 {
                     glyphnames_vec.push(String::from(APPLE_NAMES[offset]));
                 } else {
-                    glyphnames_vec.push(glyph_name_table[offset - 258].clone());
+                    glyphnames_vec.push(
+                        glyph_name_table
+                            .get(offset - 258)
+                            .ok_or_else(|| {
+                                DeserializationError(format!(
+                                    "Glyph name index {} out of range",
+                                    offset
+                                ))
+                            })?
+                            .clone(),
+                    );
                 }
             }
             glyphnames = Some(glyphnames_vec);
@@ -500,4 +530,57 @@ mod tests {
         let serialized = ser::to_bytes(&deserialized).unwrap();
         assert_eq!(serialized, binary_post);
     }
+
+    #[test]
+    fn post_glyph_names() {
+        // post table from the sample font in the glyf tests
+        let binary_post = vec![
+            0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x08, 0x00, 0x24, 0x00, 0xc9, 0x00, 0x32, 0x00, 0x39,
+            0x00, 0x03, 0x00, 0x07, 0x01, 0x02, 0x01, 0x03, 0x0b, 0x64, 0x6f, 0x6c, 0x6c, 0x61,
+            0x72, 0x2e, 0x62, 0x6f, 0x6c, 0x64, 0x09, 0x61, 0x63, 0x75, 0x74, 0x65, 0x63, 0x6f,
+            0x6d, 0x62,
+        ];
+        let deserialized: super::post = otspec::de::from_bytes(&binary_post).unwrap();
+        assert_eq!(deserialized.glyph_name(0), Some("A"));
+        assert_eq!(deserialized.glyph_name(1), Some("Aacute"));
+        assert_eq!(deserialized.glyph_name(5), Some("dollar"));
+        assert_eq!(deserialized.glyph_name(6), Some("dollar.bold"));
+        assert_eq!(deserialized.glyph_name(7), Some("acutecomb"));
+        assert_eq!(deserialized.glyph_name(8), None);
+        let serialized = ser::to_bytes(&deserialized).unwrap();
+        assert_eq!(serialized, binary_post);
+    }
+
+    #[test]
+    fn post_shared_glyph_names() {
+        let table = super::post::new(
+            2.0,
+            0.0,
+            0,
+            0,
+            false,
+            Some(vec![
+                ".notdef".to_string(),
+                "foo".to_string(),
+                "foo".to_string(),
+            ]),
+        );
+        let serialized = ser::to_bytes(&table).unwrap();
+        assert_eq!(
+            serialized[32..],
+            [0x00, 0x03, 0x00, 0x00, 0x01, 0x02, 0x01, 0x02, 0x03, 0x66, 0x6f, 0x6f]
+        );
+        let deserialized: super::post = otspec::de::from_bytes(&serialized).unwrap();
+        assert_eq!(deserialized, table);
+    }
+
+    #[test]
+    fn post_bad_glyph_name_index() {
+        let mut binary_post = vec![0; 32];
+        binary_post[1] = 0x02;
+        binary_post.extend([0x00, 0x01, 0x01, 0x02]);
+        assert!(otspec::de::from_bytes::<super::post>(&binary_post).is_err());
+    }
 }
@@ -14,7 +14,7 @@ pub struct prep(Vec<uint8>);
 impl Deserialize for prep {
     fn from_bytes(c: &mut ReaderContext) -> Result<Self, DeserializationError> {
         // This is very silly
-        Ok(prep(c.input.clone()))
+        Ok(prep(c.input.to_vec()))
     }
 }
 
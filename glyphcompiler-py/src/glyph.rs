@@ -267,16 +267,10 @@ impl<'a> ConvertedMaster {
     }
 
     fn into_glyph(self) -> glyf::Glyph {
-        glyf::Glyph {
-            xMin: 0,
-            xMax: 0,
-            yMin: 0,
-            yMax: 0,
-            contours: self.glyf_contours,
-            instructions: vec![],
-            components: self.components,
-            overlap: false,
-        }
+        let mut glyph = glyf::Glyph::empty();
+        glyph.contours = self.glyf_contours;
+        glyph.components = self.components;
+        glyph
     }
 }
 
@@ -21,6 +21,41 @@ where
     (value.into() as f32 + 0.5_f32).floor() as i32
 }
 
+/// A strategy for rounding floating point coordinates to integers
+///
+/// Different toolchains round coordinates in different ways; this allows
+/// geometry-producing operations to match the behaviour of other tools.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RoundMode {
+    /// Round to the nearest integer, with halves rounded away from zero.
+    #[default]
+    Round,
+    /// Round towards negative infinity.
+    Floor,
+    /// Round towards positive infinity.
+    Ceil,
+    /// Round to the nearest integer, with halves rounded to the even integer.
+    RoundHalfToEven,
+}
+
+impl RoundMode {
+    /// Rounds a value according to this strategy.
+    pub fn round(self, value: f64) -> f64 {
+        match self {
+            RoundMode::Round => value.round(),
+            RoundMode::Floor => value.floor(),
+            RoundMode::Ceil => value.ceil(),
+            RoundMode::RoundHalfToEven => {
+                if (value - value.trunc()).abs() == 0.5 {
+                    2.0 * (value / 2.0).round()
+                } else {
+                    value.round()
+                }
+            }
+        }
+    }
+}
+
 /// Compare two floating point values using their OpenType fixed-point
 /// equivalents.
 pub fn ot_cmp(a: f32, b: f32) -> Ordering {
@@ -434,6 +469,25 @@ mod tests {
         };
     }
 
+    #[test]
+    fn test_round_mode() {
+        let cases = [
+            (RoundMode::Round, [3.0, -3.0, 4.0, -4.0]),
+            (RoundMode::Floor, [2.0, -3.0, 3.0, -4.0]),
+            (RoundMode::Ceil, [3.0, -2.0, 4.0, -3.0]),
+            (RoundMode::RoundHalfToEven, [2.0, -2.0, 4.0, -4.0]),
+        ];
+        for (mode, expected) in cases {
+            let rounded: Vec<f64> = [2.5, -2.5, 3.5, -3.5]
+                .iter()
+                .map(|v| mode.round(*v))
+                .collect();
+            assert_eq!(rounded, expected, "{:?}", mode);
+        }
+        assert_eq!(RoundMode::RoundHalfToEven.round(2.6), 3.0);
+        assert_eq!(RoundMode::default(), RoundMode::Round);
+    }
+
     #[test]
     fn test_support_scalar() {
         assert_approx_eq!(
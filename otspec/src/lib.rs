@@ -6,6 +6,7 @@ extern crate shrinkwraprs;
 extern crate self as _tag_macro_crate;
 
 use crate::types::*;
+use std::borrow::Cow;
 use std::convert::TryInto;
 use std::mem;
 mod counted;
@@ -23,16 +24,30 @@ pub struct SerializationError(pub String);
 #[derive(Clone, Debug)]
 pub struct DeserializationError(pub String);
 
-pub struct ReaderContext {
-    pub input: Vec<u8>,
+/// A cursor over binary data being deserialized.
+///
+/// The data is either owned by the context (see [`ReaderContext::new`]) or
+/// borrowed from the caller without copying (see [`ReaderContext::from_slice`]).
+pub struct ReaderContext<'a> {
+    pub input: Cow<'a, [u8]>,
     pub ptr: usize,
     top_of_table_stack: Vec<usize>,
 }
 
-impl ReaderContext {
+impl ReaderContext<'static> {
     pub fn new(input: Vec<u8>) -> Self {
         ReaderContext {
-            input,
+            input: Cow::Owned(input),
+            ptr: 0,
+            top_of_table_stack: vec![0],
+        }
+    }
+}
+
+impl<'a> ReaderContext<'a> {
+    pub fn from_slice(input: &'a [u8]) -> Self {
+        ReaderContext {
+            input: Cow::Borrowed(input),
             ptr: 0,
             top_of_table_stack: vec![0],
         }
@@ -115,7 +130,7 @@ where
     fn de_counted(&mut self, s: usize) -> Result<Vec<T>, DeserializationError>;
 }
 
-impl<T> Deserializer<T> for ReaderContext
+impl<T> Deserializer<T> for ReaderContext<'_>
 where
     T: Deserialize,
 {
@@ -313,7 +328,7 @@ pub mod ser {
 pub mod de {
     pub use crate::{DeserializationError, Deserialize, Deserializer, ReaderContext};
     pub fn from_bytes<T: Deserialize>(data: &[u8]) -> Result<T, DeserializationError> {
-        let mut rc = ReaderContext::new(data.to_vec());
+        let mut rc = ReaderContext::from_slice(data);
         rc.de()
     }
 }
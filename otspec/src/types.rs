@@ -106,6 +106,20 @@ impl From<Fixed> for f32 {
     }
 }
 
+/// Converts a packed F2DOT14 value to a float.
+pub fn f2dot14_to_f32(packed: i16) -> f32 {
+    packed as f32 / 16384.0
+}
+
+/// Converts a float to a packed F2DOT14 value.
+///
+/// The value is rounded to the nearest representable value, and clamped to
+/// the range [-2.0, 1.999939]. Converting the result back with
+/// [`f2dot14_to_f32`] and packing it again always gives the same value.
+pub fn f32_to_f2dot14(num: f32) -> i16 {
+    ot_round(num * 16384.0).clamp(i16::MIN.into(), i16::MAX.into()) as i16
+}
+
 #[derive(Shrinkwrap, Debug, Copy, Clone)]
 pub struct F2DOT14(pub f32);
 
@@ -117,7 +131,7 @@ impl F2DOT14 {
     }
 
     pub fn from_packed(packed: i16) -> Self {
-        F2DOT14(packed as f32 / 16384.0)
+        F2DOT14(f2dot14_to_f32(packed))
     }
 
     pub fn round(f: f32) -> f32 {
@@ -283,4 +297,26 @@ mod tests {
         assert_eq!(F2DOT14::from(1.99999), F2DOT14(F2DOT14::MAX));
         assert_eq!(F2DOT14::from(1.9), F2DOT14(1.9));
     }
+
+    #[test]
+    fn test_f2dot14_conversion() {
+        assert_eq!(f2dot14_to_f32(-16384), -1.0);
+        assert_eq!(f32_to_f2dot14(-1.0), -16384);
+        assert_eq!(f2dot14_to_f32(16384), 1.0);
+        assert_eq!(f32_to_f2dot14(1.0), 16384);
+        assert_eq!(f32_to_f2dot14(1.0 / 16384.0), 1);
+        assert_eq!(f32_to_f2dot14(-1.0 / 16384.0), -1);
+        assert_eq!(f32_to_f2dot14(0.4 / 16384.0), 0);
+        assert_eq!(f32_to_f2dot14(0.6 / 16384.0), 1);
+        assert_eq!(f32_to_f2dot14(2.0), i16::MAX);
+        assert_eq!(f32_to_f2dot14(5.0), i16::MAX);
+        assert_eq!(f32_to_f2dot14(-2.0), i16::MIN);
+        assert_eq!(f32_to_f2dot14(-5.0), i16::MIN);
+        for packed in [i16::MIN, -16384, -1, 0, 1, 16383, 16384, i16::MAX] {
+            assert_eq!(f32_to_f2dot14(f2dot14_to_f32(packed)), packed);
+        }
+        for packed in i16::MIN..=i16::MAX {
+            assert_eq!(f32_to_f2dot14(f2dot14_to_f32(packed)), packed);
+        }
+    }
 }